@@ -1,12 +1,18 @@
-use std::path::PathBuf;
+use std::fs::File;
+use std::path::{Path, PathBuf};
 
-use crate::{PciInfo, PciInfoError};
+use crate::pci_headers::PciConfigFile;
+use crate::{PciInfo, PciInfoError, PciLocation};
 
 #[cfg(target_os = "linux")]
 mod proc_fs;
 
 /// A PCI Enumerator for Linux that uses in the
 /// virtual `/proc` file system to extract PCI data
+///
+/// The Vital Product Data of devices is not read by default, as it is
+/// slow to read and usually requires elevated privileges; see
+/// `LinuxProcFsPciEnumerator::with_vital_product_data`.
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum LinuxProcFsPciEnumerator {
     /// Enumerates the PCI devices in the fastest way possible,
@@ -37,7 +43,14 @@ impl crate::PciEnumerator for LinuxProcFsPciEnumerator {
         let path = PathBuf::from("/proc/bus");
 
         #[cfg(target_os = "linux")]
-        proc_fs::enumerate_pci(path, read_headers, read_extended_headers, read_device_file)
+        proc_fs::enumerate_pci(
+            path,
+            None,
+            read_headers,
+            read_extended_headers,
+            true,
+            read_device_file,
+        )
     }
 }
 
@@ -47,19 +60,44 @@ impl LinuxProcFsPciEnumerator {
     /// a `pci/` subdirectory in it.
     pub fn with_custom_path<P>(self, path: P) -> CustomPathLinuxProcFsPciEnumerator
     where
-        P: AsRef<std::path::Path>,
+        P: AsRef<Path>,
     {
         let path = path.as_ref().to_owned();
         let (read_headers, read_extended_headers, read_device_file) = self.into_arguments();
 
         CustomPathLinuxProcFsPciEnumerator {
             path,
+            sysfs_path: None,
             read_device_file,
+            read_config_space: true,
             read_extended_headers,
             read_headers,
         }
     }
 
+    /// Creates an enumerator that reads only the headers from the configuration
+    /// space of devices in `/proc/bus/pci`. See
+    /// `CustomPathLinuxProcFsPciEnumerator::with_lazy_config_space`.
+    pub fn with_lazy_config_space(self) -> CustomPathLinuxProcFsPciEnumerator {
+        self.with_custom_path("/proc/bus").with_lazy_config_space()
+    }
+
+    /// Creates an enumerator that also reads the Vital Product Data of devices
+    /// in `/proc/bus/pci` from `/sys/bus/pci/devices`. See
+    /// `CustomPathLinuxProcFsPciEnumerator::with_vital_product_data`.
+    pub fn with_vital_product_data(self) -> CustomPathLinuxProcFsPciEnumerator {
+        self.with_custom_path("/proc/bus")
+            .with_vital_product_data("/sys/bus/pci/devices")
+    }
+
+    /// Opens the configuration space of the device at `location` in
+    /// `/proc/bus/pci`, so that its registers can be read on demand.
+    pub fn open_config_file(location: PciLocation) -> Result<PciConfigFile<File>, PciInfoError> {
+        Self::Exhaustive
+            .with_custom_path("/proc/bus")
+            .open_config_file(location)
+    }
+
     fn into_arguments(self) -> (bool, bool, bool) {
         match self {
             Self::Fastest => (false, false, true),
@@ -73,20 +111,73 @@ impl LinuxProcFsPciEnumerator {
 /// An enumerator that enumerates PCI devices reading from a copy of
 /// the /proc/bus/pci directory. See `LinuxProcFsPciEnumerator::with_custom_path`
 /// to build an enumerator of this type.
+#[derive(Clone, Debug)]
 pub struct CustomPathLinuxProcFsPciEnumerator {
-    path: std::path::PathBuf,
+    path: PathBuf,
+    sysfs_path: Option<PathBuf>,
     read_headers: bool,
     read_extended_headers: bool,
+    read_config_space: bool,
     read_device_file: bool,
 }
 
+impl CustomPathLinuxProcFsPciEnumerator {
+    /// Reads only the headers from the configuration space of each device
+    /// (the first 64 bytes, or 72 for CardBus bridges), instead of as much of
+    /// the configuration space as possible. The configuration space and the
+    /// capabilities of the enumerated devices are left unsupported; use
+    /// `open_config_file` to read them on demand. Has no effect on the options
+    /// that do not parse specific headers.
+    pub fn with_lazy_config_space(mut self) -> Self {
+        self.read_config_space = false;
+        self
+    }
+
+    /// Also reads the Vital Product Data of each device from the `vpd` file of
+    /// its directory in `sysfs_path`, which should point to
+    /// `/sys/bus/pci/devices` or a copy of it. Devices that do not provide VPD
+    /// get `None`, while VPD that cannot be read for lack of privileges is left
+    /// unsupported, like the capabilities of a truncated configuration space.
+    /// Has no effect on the options that do not parse specific headers.
+    pub fn with_vital_product_data<P>(mut self, sysfs_path: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        self.sysfs_path = Some(sysfs_path.as_ref().to_owned());
+        self
+    }
+
+    /// Opens the configuration space of the device at `location`, so that its
+    /// registers can be read on demand.
+    pub fn open_config_file(
+        &self,
+        location: PciLocation,
+    ) -> Result<PciConfigFile<File>, PciInfoError> {
+        let bus_dir = match location.segment() {
+            0 => format!("{:02x}", location.bus()),
+            segment => format!("{segment:04x}:{:02x}", location.bus()),
+        };
+        let mut path = self.path.join("pci");
+        path.push(bus_dir);
+        path.push(format!(
+            "{:02x}.{:x}",
+            location.device(),
+            location.function()
+        ));
+
+        PciConfigFile::open(path)
+    }
+}
+
 impl crate::PciEnumerator for CustomPathLinuxProcFsPciEnumerator {
     fn enumerate_pci(self) -> Result<PciInfo, PciInfoError> {
         #[cfg(target_os = "linux")]
         proc_fs::enumerate_pci(
             self.path,
+            self.sysfs_path,
             self.read_headers,
             self.read_extended_headers,
+            self.read_config_space,
             self.read_device_file,
         )
     }
@@ -116,3 +207,86 @@ test_enumerator!(
     LinuxProcFsPciEnumeratorExhaustiveAarch64,
     LinuxProcFsPciEnumerator::Exhaustive.with_custom_path("test-data/linux/aarch64")
 );
+test_enumerator!(
+    LinuxProcFsPciEnumeratorLazyAmd64,
+    LinuxProcFsPciEnumerator::Exhaustive
+        .with_custom_path("test-data/linux/amd64")
+        .with_lazy_config_space()
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_headers::{PciCapabilityIterator, PciCommonHeader};
+    use crate::{PciEnumerator, PciInfoPropertyError};
+
+    #[test]
+    fn lazy_config_space_is_read_on_demand() {
+        let enumerator = LinuxProcFsPciEnumerator::Exhaustive
+            .with_custom_path("test-data/linux/amd64")
+            .with_lazy_config_space();
+        let info = enumerator.clone().enumerate_pci().unwrap();
+        let mut capabilities = 0;
+
+        for device in info.iter() {
+            let device = device.unwrap();
+
+            assert!(matches!(
+                device.pci_config_space(),
+                Err(PciInfoPropertyError::Unsupported)
+            ));
+            assert!(device.pci_express_capability().is_err());
+            assert!(device.subsystem_vendor_id().is_ok());
+
+            let config = enumerator
+                .open_config_file(device.location().unwrap())
+                .unwrap();
+            let header = PciCommonHeader::with_access(&config).unwrap();
+
+            assert_eq!(header.vendor_id, device.vendor_id());
+            assert_eq!(header.device_id, device.device_id());
+
+            capabilities += PciCapabilityIterator::with_access(&config).unwrap().count();
+        }
+
+        assert!(capabilities > 0);
+    }
+
+    #[test]
+    fn vital_product_data_is_read_only_when_requested() {
+        let sysfs_path =
+            std::env::temp_dir().join(format!("pci-info-sysfs-{}", std::process::id()));
+        let device_dir = sysfs_path.join("0000:00:00.0");
+        std::fs::create_dir_all(&device_dir).unwrap();
+        std::fs::write(device_dir.join("vpd"), b"\x82\x04\x00Test\x78").unwrap();
+
+        let enumerator =
+            LinuxProcFsPciEnumerator::Exhaustive.with_custom_path("test-data/linux/amd64");
+        let info = enumerator.clone().enumerate_pci().unwrap();
+
+        for device in info.iter() {
+            assert!(matches!(
+                device.unwrap().vital_product_data(),
+                Err(PciInfoPropertyError::Unsupported)
+            ));
+        }
+
+        let info = enumerator
+            .with_vital_product_data(&sysfs_path)
+            .enumerate_pci()
+            .unwrap();
+
+        for device in info.iter() {
+            let device = device.unwrap();
+            let vpd = device.vital_product_data().unwrap();
+
+            if device.location().unwrap() == PciLocation::with_bdf(0, 0, 0).unwrap() {
+                assert_eq!(vpd.as_ref().unwrap().identifier.as_deref(), Some("Test"));
+            } else {
+                assert!(vpd.is_none());
+            }
+        }
+
+        std::fs::remove_dir_all(&sysfs_path).unwrap();
+    }
+}
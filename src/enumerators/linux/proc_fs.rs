@@ -1,11 +1,14 @@
 use std::fs;
 use std::io::{self, BufRead, Read};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::pci_device::PciDeviceProperties;
-use crate::pci_headers::{PciCommonHeader, PciSpecializedHeader};
+use crate::pci_headers::{
+    PciBar, PciCommonHeader, PciConfigSpace, PciSpecializedHeader, PciToCardbusBridgeHeader,
+};
 use crate::pci_info::PciInfo;
 use crate::pci_property_result::PropertyResult;
+use crate::pci_vpd::PciVitalProductData;
 use crate::PciBusNumber;
 use crate::{
     PciDevice, PciDeviceEnumerationError, PciDeviceEnumerationErrorImpact, PciInfoError,
@@ -16,6 +19,8 @@ fn read_bus_directory(
     bus_dir: Result<fs::DirEntry, std::io::Error>,
     pi: &mut PciInfo,
     read_extended_headers: bool,
+    read_config_space: bool,
+    sysfs_path: Option<&Path>,
 ) -> Result<(), PciInfoError> {
     let bus_dir = bus_dir?;
 
@@ -27,7 +32,14 @@ fn read_bus_directory(
     let devices_files = fs::read_dir(bus_dir.path())?;
 
     for device_file in devices_files {
-        if let Err(e) = read_pci_header_file(device_file, bus_num, pi, read_extended_headers) {
+        if let Err(e) = read_pci_header_file(
+            device_file,
+            bus_num,
+            pi,
+            read_extended_headers,
+            read_config_space,
+            sysfs_path,
+        ) {
             pi.push_error(PciDeviceEnumerationError::new_at_bus(
                 bus_num,
                 PciDeviceEnumerationErrorImpact::Device,
@@ -44,6 +56,8 @@ fn read_pci_header_file(
     bus_num: PciBusNumber,
     pi: &mut PciInfo,
     read_extended_headers: bool,
+    read_config_space: bool,
+    sysfs_path: Option<&Path>,
 ) -> Result<(), PciInfoError> {
     let device_file = device_file?;
 
@@ -55,29 +69,50 @@ fn read_pci_header_file(
 
     let location = PciLocation::with_segment(bus_num.segment(), bus_num.bus(), slot, func);
 
-    let mut buffer = [0; PciCommonHeader::MAX_HEADER_LEN];
+    let mut buffer = Vec::new();
     let mut f = fs::File::open(device_file.path())?;
 
-    read_loop(&mut f, &mut buffer[0..PciCommonHeader::COMMON_HEADER_LEN])?;
+    if read_extended_headers && read_config_space {
+        // Read as much of the configuration space as we are allowed to; this is
+        // usually the first 64 bytes for unprivileged users, and the whole
+        // configuration space (256 or 4096 bytes) otherwise, as the device keeps
+        // a copy of it.
+        f.read_to_end(&mut buffer)?;
+    } else if read_extended_headers {
+        // Read only the headers, the longest of which is the CardBus bridge
+        // header; the rest of the configuration space is read on demand.
+        f.take(PciToCardbusBridgeHeader::LENGTH as u64)
+            .read_to_end(&mut buffer)?;
+    } else {
+        buffer.resize(PciCommonHeader::COMMON_HEADER_LEN, 0);
+        read_loop(&mut f, &mut buffer)?;
+    }
 
     let header = PciCommonHeader::with_bytes(&buffer)?;
 
     let mut device = if read_extended_headers {
-        let subheader_bytes = PciSpecializedHeader::length_of_subheader(header.header_type);
-
-        let specialized = if let Some(subheader_bytes) = subheader_bytes {
-            match read_loop(
-                &mut f,
-                &mut buffer[PciCommonHeader::COMMON_HEADER_LEN..subheader_bytes],
-            ) {
-                Ok(()) => PciSpecializedHeader::read_subheader(header.header_type, &buffer, true),
-                Err(e) => Err(e),
+        let specialized = match PciSpecializedHeader::length_of_subheader(header.header_type) {
+            Some(subheader_bytes) if buffer.len() < subheader_bytes => {
+                Err(PciInfoError::UnexpectedEof)
             }
-        } else {
-            Err(PciInfoError::UnknownPciHeaderType(header.header_type))
+            Some(_) => PciSpecializedHeader::read_subheader(header.header_type, &buffer, true),
+            None => Err(PciInfoError::UnknownPciHeaderType(header.header_type)),
         };
 
-        PciDevice::from_pci_header_result(header, specialized)
+        let mut device = PciDevice::from_pci_header_result(header, specialized);
+
+        if read_config_space {
+            match PciConfigSpace::with_bytes(&buffer) {
+                Ok(config) => device.set_config_space(config),
+                Err(e) => device.properties.pci_config_space.set_err(e),
+            }
+        }
+
+        if let (Some(sysfs_path), Ok(location)) = (sysfs_path, &location) {
+            set_vital_product_data(&mut device, read_vpd_file(sysfs_path, *location));
+        }
+
+        device
     } else {
         PciDevice::from_pci_header_set(header, None)
     };
@@ -88,11 +123,45 @@ fn read_pci_header_file(
     Ok(())
 }
 
+// VPD is not available in /proc, but the kernel exposes it in the `vpd` file
+// of the sysfs directory of the device, which exists only for devices that
+// have the VPD capability.
+fn read_vpd_file(
+    sysfs_path: &Path,
+    location: PciLocation,
+) -> Result<Option<PciVitalProductData>, PciInfoError> {
+    let path = sysfs_path.join(format!(
+        "{:04x}:{:02x}:{:02x}.{:x}/vpd",
+        location.segment(),
+        location.bus(),
+        location.device(),
+        location.function()
+    ));
+
+    match fs::read(path) {
+        Ok(bytes) => PciVitalProductData::with_bytes(&bytes).map(Some),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// As with the capabilities of a configuration space truncated for unprivileged
+// users, VPD that we are not allowed to read is left unsupported.
+fn set_vital_product_data(
+    device: &mut PciDevice,
+    vpd: Result<Option<PciVitalProductData>, PciInfoError>,
+) {
+    match vpd {
+        Err(PciInfoError::IoError(kind)) if *kind == io::ErrorKind::PermissionDenied => (),
+        vpd => device.properties.vital_product_data.set_res(vpd),
+    }
+}
+
 fn read_loop(f: &mut fs::File, buffer: &mut [u8]) -> Result<(), PciInfoError> {
     let mut read_total = 0;
 
     while read_total < buffer.len() {
-        let read_now = f.read(buffer)?;
+        let read_now = f.read(&mut buffer[read_total..])?;
 
         if read_now == 0 {
             return Err(PciInfoError::UnexpectedEof);
@@ -182,6 +251,7 @@ struct DeviceFileEntry {
     device_id: u16,
     irq: Option<u8>,
     kernel_driver: Option<String>,
+    bars: Option<Result<Vec<PciBar>, PciInfoError>>,
 }
 
 impl DeviceFileEntry {
@@ -214,6 +284,11 @@ impl DeviceFileEntry {
             .get(17)
             .filter(|s| !s.is_empty())
             .map(|s| s.to_string());
+        let bars = if fields.len() >= 17 {
+            Some(Self::parse_bars(&fields[3..9], &fields[10..16]))
+        } else {
+            None
+        };
 
         Ok(Self {
             location,
@@ -221,8 +296,37 @@ impl DeviceFileEntry {
             device_id,
             irq,
             kernel_driver,
+            bars,
         })
     }
+
+    // Base addresses in the devices file are the full (possibly 64-bit) start
+    // addresses of the resources, with the flags of the BAR in the lowest bits,
+    // while the second register of a 64-bit BAR is reported as 0. We convert
+    // them back to raw register values to share the BAR decoding logic.
+    fn parse_bars(bases: &[&str], sizes: &[&str]) -> Result<Vec<PciBar>, PciInfoError> {
+        let parse_hex = |s: &&str| {
+            u64::from_str_radix(s, 16).map_err(|_| {
+                PciInfoError::ParseError(
+                    format!("resource in devices file is invalid hex: '{s}'").into(),
+                )
+            })
+        };
+
+        let bases = bases.iter().map(parse_hex).collect::<Result<Vec<_>, _>>()?;
+        let sizes = sizes.iter().map(parse_hex).collect::<Result<Vec<_>, _>>()?;
+        let mut registers = vec![0u32; bases.len()];
+
+        for (i, base) in bases.iter().enumerate() {
+            registers[i] |= *base as u32;
+
+            if base & 0x7 == 0x4 && i + 1 < registers.len() {
+                registers[i + 1] = (base >> 32) as u32;
+            }
+        }
+
+        Ok(PciBar::from_registers_with_sizes(&registers, &sizes))
+    }
 }
 
 fn parse_device_file(
@@ -247,8 +351,10 @@ fn parse_device_file(
 
 pub(super) fn enumerate_pci(
     mut path: PathBuf,
+    sysfs_path: Option<PathBuf>,
     read_headers: bool,
     read_extended_headers: bool,
+    read_config_space: bool,
     read_device_file: bool,
 ) -> Result<PciInfo, PciInfoError> {
     path.push("pci");
@@ -264,7 +370,13 @@ pub(super) fn enumerate_pci(
 
     if read_headers {
         for bus_dir in bus_directories {
-            if let Err(e) = read_bus_directory(bus_dir, &mut pi, read_extended_headers) {
+            if let Err(e) = read_bus_directory(
+                bus_dir,
+                &mut pi,
+                read_extended_headers,
+                read_config_space,
+                sysfs_path.as_deref(),
+            ) {
                 pi.push_error(PciDeviceEnumerationError::new(
                     PciDeviceEnumerationErrorImpact::Bus,
                     e,
@@ -282,6 +394,10 @@ pub(super) fn enumerate_pci(
                                 if let Some(dev) = pi.find_device_mut(entry.location) {
                                     dev.properties.os_driver.set_val(entry.kernel_driver.take());
                                     dev.properties.os_irq.set_val(entry.irq);
+
+                                    if let Some(bars) = entry.bars.take() {
+                                        dev.properties.bars.set_res(bars);
+                                    }
                                 }
                             }
                             Err(e) => pi.push_error(PciDeviceEnumerationError::new(
@@ -310,6 +426,7 @@ pub(super) fn enumerate_pci(
                             location: PropertyResult::with_val(d.location),
                             os_irq: PropertyResult::with_val(d.irq),
                             os_driver: PropertyResult::with_val(d.kernel_driver),
+                            bars: d.bars.map(PropertyResult::with_res).unwrap_or_default(),
                             ..Default::default()
                         },
                     ));
@@ -328,3 +445,93 @@ pub(super) fn enumerate_pci(
 
     Ok(pi)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PciInfoPropertyError;
+
+    #[test]
+    fn devices_file_bars_are_decoded() {
+        // A 64-bit BAR 0, an I/O BAR 2 and a truncated 64-bit BAR 5 with no
+        // upper half to pair it with
+        let line = "0300\t10de1eb8\t3c\t\
+            fb000004\t0\te001\t0\t0\td0000004\t0\t\
+            1000000\t0\t80\t0\t0\t2000\t0\tnvidia";
+        let entry = DeviceFileEntry::new(line.split('\t').collect()).unwrap();
+
+        assert_eq!(entry.vendor_id, 0x10DE);
+        assert_eq!(entry.kernel_driver.as_deref(), Some("nvidia"));
+        assert_eq!(
+            entry.bars.unwrap().unwrap(),
+            vec![
+                PciBar::Memory64 {
+                    index: 0,
+                    base: 0xFB00_0000,
+                    prefetchable: false,
+                    size: Some(0x100_0000),
+                },
+                PciBar::Io {
+                    index: 2,
+                    base: 0xE000,
+                    size: Some(0x80),
+                },
+            ]
+        );
+
+        let line = line.replace("e001", "zz");
+        let entry = DeviceFileEntry::new(line.split('\t').collect()).unwrap();
+
+        assert!(entry.bars.unwrap().is_err());
+    }
+
+    #[test]
+    fn vpd_is_read_from_sysfs() {
+        let sysfs_path = std::env::temp_dir().join(format!("pci-info-vpd-{}", std::process::id()));
+        let location = PciLocation::with_segment(1, 0x03, 0x00, 1).unwrap();
+        let device_dir = sysfs_path.join("0001:03:00.1");
+        fs::create_dir_all(&device_dir).unwrap();
+
+        // Devices without the VPD capability have no vpd file
+        assert!(matches!(read_vpd_file(&sysfs_path, location), Ok(None)));
+
+        fs::write(device_dir.join("vpd"), b"\x82\x04\x00Test\x78").unwrap();
+        let vpd = read_vpd_file(&sysfs_path, location).unwrap().unwrap();
+        assert_eq!(vpd.identifier.as_deref(), Some("Test"));
+
+        fs::write(device_dir.join("vpd"), b"\x82\x0B\x00Te").unwrap();
+        assert!(matches!(
+            read_vpd_file(&sysfs_path, location),
+            Err(PciInfoError::UnexpectedEof)
+        ));
+
+        fs::remove_file(device_dir.join("vpd")).unwrap();
+        fs::create_dir(device_dir.join("vpd")).unwrap();
+        assert!(matches!(
+            read_vpd_file(&sysfs_path, location),
+            Err(PciInfoError::IoError(_))
+        ));
+
+        fs::remove_dir_all(&sysfs_path).unwrap();
+    }
+
+    #[test]
+    fn unreadable_vpd_is_left_unsupported() {
+        let mut device = PciDevice::new(0x8086, 0x1234, Default::default());
+
+        set_vital_product_data(
+            &mut device,
+            Err(io::Error::from(io::ErrorKind::PermissionDenied).into()),
+        );
+        assert!(matches!(
+            device.vital_product_data(),
+            Err(PciInfoPropertyError::Unsupported)
+        ));
+
+        set_vital_product_data(&mut device, Err(PciInfoError::UnexpectedEof));
+        assert!(matches!(
+            device.vital_product_data(),
+            Err(PciInfoPropertyError::Error(_))
+        ));
+    }
+}
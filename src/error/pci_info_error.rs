@@ -72,9 +72,24 @@ pub enum PciInfoError {
     /// attempted.
     UnknownPciHeaderType(u8),
 
+    /// A loop was detected while walking a list of PCI capabilities.
+    /// The offset in configuration space of the capability that was
+    /// visited twice is contained within.
+    PciCapabilityLoop(u16),
+
+    /// A PCI capability pointer points outside of the area of the
+    /// configuration space where capabilities can be located. The
+    /// offending pointer is contained within.
+    PciCapabilityPointerOutOfRange(u16),
+
     /// The enumeration has been retried because the device list changed
     /// and the maximum number of iterations has been exceeded.
     DevicesChangedTooManyTimes,
+
+    /// A configuration space image was requested that cannot be built,
+    /// for example because a field does not exist in the requested header
+    /// type. The argument describes the invalid request.
+    InvalidBuildRequest(PciInfoErrorString),
 }
 
 impl Display for PciInfoError {
@@ -105,10 +120,20 @@ impl Display for PciInfoError {
                 write!(f, "this platform does not support a default PCI enumerator")
             }
             UnknownPciHeaderType(h) => write!(f, "unknown PCI header type 0x{h:02X}"),
+            PciCapabilityLoop(o) => {
+                write!(
+                    f,
+                    "loop detected in PCI capability list at offset 0x{o:03X}"
+                )
+            }
+            PciCapabilityPointerOutOfRange(p) => {
+                write!(f, "PCI capability pointer 0x{p:03X} is out of range")
+            }
             EnumerationInterrupted(e) => write!(f, "the enumeration has been interrupted: {e}"),
             DevicesChangedTooManyTimes => {
                 write!(f, "the list of PCI devices changed too many times")
             }
+            InvalidBuildRequest(e) => write!(f, "invalid configuration space build request: {e}"),
         }
     }
 }
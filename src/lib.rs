@@ -12,6 +12,8 @@
 //! # Functionality
 //! - Enumeration of devices using OS usermode APIs on Windows, Linux and macOS, with more platforms to be added. See the [`PciInfo`] type.
 //! - Parsing of PCI headers starting from byte arrays of the PCI configuration space. See the [`pci_headers`] module.
+//! - Parsing of PCI expansion ROM images. See the [`pci_rom`] module.
+//! - Parsing of the Vital Product Data of PCI devices. See the [`pci_vpd`] module.
 //! - Parsing of PCI device classes, subclasses and interface-functions from their codes. See the [`pci_enums`] module.
 //!
 //!
@@ -136,6 +138,8 @@ mod pci_property_result;
 pub mod enumerators;
 pub mod pci_enums;
 pub mod pci_headers;
+pub mod pci_rom;
+pub mod pci_vpd;
 
 pub use error::{
     PciDeviceEnumerationError, PciDeviceEnumerationErrorImpact, PciDeviceEnumerationErrorLocation,
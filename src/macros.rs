@@ -24,3 +24,86 @@ macro_rules! test_enumerator {
 macro_rules! test_enumerator {
     ($enumeratorname:ident, $initializer:expr) => {};
 }
+
+/// Defines a newtype over the raw value of a PCI register, with a getter
+/// and a setter for every single-bit flag of the register. Multi-bit fields
+/// are implemented by hand in a separate `impl` block.
+macro_rules! pci_register_bits {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident($ty:ty) {
+            $(
+                $(#[$fmeta:meta])*
+                $getter:ident, $setter:ident: $bit:literal => $label:literal;
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Copy, Clone, Default, Eq, PartialEq, Hash)]
+        pub struct $name(pub $ty);
+
+        #[allow(dead_code)]
+        impl $name {
+            /// The names and masks of the single-bit flags of this register.
+            pub(crate) const FLAGS: &'static [(&'static str, $ty)] = &[$(($label, 1 << $bit)),*];
+
+            /// Returns the raw value of the register.
+            pub fn bits(&self) -> $ty {
+                self.0
+            }
+
+            $(
+                $(#[$fmeta])*
+                pub fn $getter(&self) -> bool {
+                    self.0 & (1 << $bit) != 0
+                }
+
+                #[doc = concat!("Sets or clears the flag returned by `", stringify!($getter), "`.")]
+                pub fn $setter(&mut self, value: bool) {
+                    if value {
+                        self.0 |= 1 << $bit;
+                    } else {
+                        self.0 &= !(1 << $bit);
+                    }
+                }
+            )*
+
+            fn field(&self, shift: u32, mask: $ty) -> $ty {
+                (self.0 >> shift) & mask
+            }
+
+            fn set_field(&mut self, shift: u32, mask: $ty, value: $ty) {
+                self.0 = (self.0 & !(mask << shift)) | ((value & mask) << shift);
+            }
+        }
+
+        impl From<$ty> for $name {
+            fn from(value: $ty) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for $ty {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}(0x{:X}", stringify!($name), self.0)?;
+
+                let mut separator = ": ";
+
+                for (label, mask) in Self::FLAGS {
+                    if self.0 & mask != 0 {
+                        write!(f, "{separator}{label}")?;
+                        separator = " | ";
+                    }
+                }
+
+                write!(f, ")")
+            }
+        }
+    };
+}
@@ -4,7 +4,13 @@
 use crate::pci_property_result::PropertyResult;
 use crate::{
     pci_enums::{PciDeviceClass, PciDeviceInterfaceFunc, PciDeviceSubclass},
-    pci_headers::{PciCommonHeader, PciSpecializedHeader},
+    pci_headers::{
+        diff_config_spaces, PciAcsCapability, PciAtsCapability, PciBar, PciCommonHeader,
+        PciConfigSpace, PciDeviceSerialNumberCapability, PciExpressCapability, PciPasidCapability,
+        PciPowerManagementCapability, PciPriCapability, PciRegisterChange, PciSpecializedHeader,
+        PciSrIovCapability,
+    },
+    pci_vpd::PciVitalProductData,
     PciInfoError, PciInfoPropertyError, PciLocation,
 };
 use std::fmt;
@@ -34,6 +40,18 @@ pub(crate) struct PciDeviceProperties {
     pub(crate) os_driver: PropertyResult<Option<String>>,
     pub(crate) pci_common_header: PropertyResult<PciCommonHeader>,
     pub(crate) pci_specialized_header: PropertyResult<PciSpecializedHeader>,
+    pub(crate) bars: PropertyResult<Vec<PciBar>>,
+    pub(crate) pci_config_space: PropertyResult<PciConfigSpace>,
+    pub(crate) pci_express_capability: PropertyResult<Option<PciExpressCapability>>,
+    pub(crate) power_management_capability: PropertyResult<Option<PciPowerManagementCapability>>,
+    pub(crate) sr_iov_capability: PropertyResult<Option<PciSrIovCapability>>,
+    pub(crate) acs_capability: PropertyResult<Option<PciAcsCapability>>,
+    pub(crate) device_serial_number_capability:
+        PropertyResult<Option<PciDeviceSerialNumberCapability>>,
+    pub(crate) ats_capability: PropertyResult<Option<PciAtsCapability>>,
+    pub(crate) pri_capability: PropertyResult<Option<PciPriCapability>>,
+    pub(crate) pasid_capability: PropertyResult<Option<PciPasidCapability>>,
+    pub(crate) vital_product_data: PropertyResult<Option<PciVitalProductData>>,
 }
 
 impl PciDevice {
@@ -82,6 +100,7 @@ impl PciDevice {
                 device_iface: PropertyResult::with_val(header.prog_iface_code),
                 subsystem_device_id,
                 subsystem_vendor_id,
+                bars: Self::bars_of_header(pci_specialized_header.as_option()),
                 pci_common_header: PropertyResult::with_val(header),
                 pci_specialized_header,
                 ..Default::default()
@@ -127,6 +146,7 @@ impl PciDevice {
                 device_iface: PropertyResult::with_val(header.prog_iface_code),
                 subsystem_device_id,
                 subsystem_vendor_id,
+                bars: Self::bars_of_header(pci_specialized_header.as_option()),
                 pci_common_header: PropertyResult::with_val(header),
                 pci_specialized_header,
                 ..Default::default()
@@ -134,6 +154,97 @@ impl PciDevice {
         }
     }
 
+    fn bars_of_header(header: Option<&PciSpecializedHeader>) -> PropertyResult<Vec<PciBar>> {
+        match header {
+            Some(PciSpecializedHeader::GenericDevice(h)) => PropertyResult::with_val(h.bars()),
+            Some(PciSpecializedHeader::PciToPciBridge(h)) => PropertyResult::with_val(h.bars()),
+            Some(PciSpecializedHeader::PciToCardbusBridge(_)) => {
+                PropertyResult::with_val(Vec::new())
+            }
+            None => PropertyResult::default(),
+        }
+    }
+
+    /// Stores the configuration space of the device, and parses the
+    /// properties that are stored in its capabilities if it contains at
+    /// least the standard configuration space. Properties stored in extended
+    /// capabilities are parsed only if it also contains the extended
+    /// configuration space. Otherwise those properties are left untouched.
+    pub(crate) fn set_config_space(&mut self, config: PciConfigSpace) {
+        self.set_capabilities_from_config_space(&config);
+        self.properties.pci_config_space.set_val(config);
+    }
+
+    fn set_capabilities_from_config_space(&mut self, config: &PciConfigSpace) {
+        if config.len() < PciConfigSpace::STANDARD_LEN {
+            return;
+        }
+
+        let find_capability = |id| config.find_capability(id);
+
+        self.properties.pci_express_capability.set_res(
+            find_capability(PciExpressCapability::ID).and_then(|cap| {
+                cap.map(|cap| PciExpressCapability::with_capability(&cap))
+                    .transpose()
+            }),
+        );
+
+        self.properties.power_management_capability.set_res(
+            find_capability(PciPowerManagementCapability::ID).and_then(|cap| {
+                cap.map(|cap| PciPowerManagementCapability::with_capability(&cap))
+                    .transpose()
+            }),
+        );
+
+        if config.len() <= PciConfigSpace::STANDARD_LEN {
+            return;
+        }
+
+        let find_extended_capability = |id| config.find_extended_capability(id);
+
+        self.properties.sr_iov_capability.set_res(
+            find_extended_capability(PciSrIovCapability::ID).and_then(|cap| {
+                cap.map(|cap| PciSrIovCapability::with_capability(&cap))
+                    .transpose()
+            }),
+        );
+
+        self.properties.acs_capability.set_res(
+            find_extended_capability(PciAcsCapability::ID).and_then(|cap| {
+                cap.map(|cap| PciAcsCapability::with_capability(&cap))
+                    .transpose()
+            }),
+        );
+
+        self.properties.device_serial_number_capability.set_res(
+            find_extended_capability(PciDeviceSerialNumberCapability::ID).and_then(|cap| {
+                cap.map(|cap| PciDeviceSerialNumberCapability::with_capability(&cap))
+                    .transpose()
+            }),
+        );
+
+        self.properties.ats_capability.set_res(
+            find_extended_capability(PciAtsCapability::ID).and_then(|cap| {
+                cap.map(|cap| PciAtsCapability::with_capability(&cap))
+                    .transpose()
+            }),
+        );
+
+        self.properties.pri_capability.set_res(
+            find_extended_capability(PciPriCapability::ID).and_then(|cap| {
+                cap.map(|cap| PciPriCapability::with_capability(&cap))
+                    .transpose()
+            }),
+        );
+
+        self.properties.pasid_capability.set_res(
+            find_extended_capability(PciPasidCapability::ID).and_then(|cap| {
+                cap.map(|cap| PciPasidCapability::with_capability(&cap))
+                    .transpose()
+            }),
+        );
+    }
+
     /// Returns the id of the vendor of this device. The vendor is usually
     /// the provider of the chipset or technology upon which the device is
     /// based.
@@ -242,6 +353,177 @@ impl PciDevice {
     pub fn pci_specialized_header(&self) -> Result<&PciSpecializedHeader, &PciInfoPropertyError> {
         self.properties.pci_specialized_header.as_result_ref()
     }
+
+    /// Returns a copy of the configuration space of this device, as read by
+    /// the enumerator. Usually only the first 64 bytes are available to
+    /// unprivileged users; see [`PciConfigSpace::len`].
+    pub fn pci_config_space(&self) -> Result<&PciConfigSpace, &PciInfoPropertyError> {
+        self.properties.pci_config_space.as_result_ref()
+    }
+
+    /// Returns the Base Address Registers of this device, with the two
+    /// halves of 64-bit BARs merged and unused registers skipped. The sizes
+    /// of the BARs are available only if the enumerator in use provides them.
+    pub fn bars(&self) -> Result<&[PciBar], &PciInfoPropertyError> {
+        self.properties.bars.as_result_deref()
+    }
+
+    /// Returns the PCI Express capability of this device, or `None` if
+    /// the device is not a PCI Express device.
+    ///
+    /// This requires the enumerator to read the configuration space past
+    /// the first 64 bytes, which usually requires elevated privileges.
+    pub fn pci_express_capability(
+        &self,
+    ) -> Result<&Option<PciExpressCapability>, &PciInfoPropertyError> {
+        self.properties.pci_express_capability.as_result_ref()
+    }
+
+    /// Returns the Power Management capability of this device, or `None` if
+    /// the device does not support PCI power management.
+    ///
+    /// This requires the enumerator to read the configuration space past
+    /// the first 64 bytes, which usually requires elevated privileges.
+    pub fn power_management_capability(
+        &self,
+    ) -> Result<&Option<PciPowerManagementCapability>, &PciInfoPropertyError> {
+        self.properties.power_management_capability.as_result_ref()
+    }
+
+    /// Returns the SR-IOV capability of this device, or `None` if the device
+    /// is not an SR-IOV physical function. The locations of the virtual
+    /// functions of the device can be computed with
+    /// [`PciSrIovCapability::vf_locations`].
+    ///
+    /// This requires the enumerator to read the extended configuration
+    /// space, which usually requires elevated privileges.
+    pub fn sr_iov_capability(&self) -> Result<&Option<PciSrIovCapability>, &PciInfoPropertyError> {
+        self.properties.sr_iov_capability.as_result_ref()
+    }
+
+    /// Returns the Access Control Services capability of this device, or
+    /// `None` if the device does not implement ACS. Whether the device
+    /// isolates peer-to-peer traffic can be checked with
+    /// [`PciAcsCapability::enforces_isolation`].
+    ///
+    /// This requires the enumerator to read the extended configuration
+    /// space, which usually requires elevated privileges.
+    pub fn acs_capability(&self) -> Result<&Option<PciAcsCapability>, &PciInfoPropertyError> {
+        self.properties.acs_capability.as_result_ref()
+    }
+
+    /// Returns the Address Translation Services capability of this device,
+    /// or `None` if the device cannot cache address translations.
+    ///
+    /// This requires the enumerator to read the extended configuration
+    /// space, which usually requires elevated privileges.
+    pub fn ats_capability(&self) -> Result<&Option<PciAtsCapability>, &PciInfoPropertyError> {
+        self.properties.ats_capability.as_result_ref()
+    }
+
+    /// Returns the Page Request Interface capability of this device, or
+    /// `None` if the device cannot request that pages are made resident.
+    ///
+    /// This requires the enumerator to read the extended configuration
+    /// space, which usually requires elevated privileges.
+    pub fn pri_capability(&self) -> Result<&Option<PciPriCapability>, &PciInfoPropertyError> {
+        self.properties.pri_capability.as_result_ref()
+    }
+
+    /// Returns the Process Address Space ID capability of this device, or
+    /// `None` if the device cannot tag its requests with a PASID.
+    ///
+    /// This requires the enumerator to read the extended configuration
+    /// space, which usually requires elevated privileges.
+    pub fn pasid_capability(&self) -> Result<&Option<PciPasidCapability>, &PciInfoPropertyError> {
+        self.properties.pasid_capability.as_result_ref()
+    }
+
+    /// Returns true if this device has the capabilities required for
+    /// shared virtual addressing (SVA), i.e. to work directly on the
+    /// virtual address space of a process: ATS, PRI with a non-zero
+    /// outstanding page request capacity, and PASID with a non-zero PASID
+    /// width. Whether SVA can be enabled also depends on the IOMMU and on
+    /// the OS.
+    ///
+    /// This requires the enumerator to read the extended configuration
+    /// space, which usually requires elevated privileges.
+    pub fn supports_shared_virtual_addressing(&self) -> Result<bool, &PciInfoPropertyError> {
+        let (Some(_), Some(pri), Some(pasid)) = (
+            self.ats_capability()?,
+            self.pri_capability()?,
+            self.pasid_capability()?,
+        ) else {
+            return Ok(false);
+        };
+
+        Ok(pri.outstanding_page_request_capacity > 0 && pasid.capabilities.max_pasid_width() > 0)
+    }
+
+    /// Returns the Device Serial Number capability of this device, or `None`
+    /// if the device does not report a serial number. Unlike the location,
+    /// the serial number identifies the device across reboots and slot
+    /// changes; see [`PciDeviceSerialNumberCapability::eui64_string`].
+    ///
+    /// This requires the enumerator to read the extended configuration
+    /// space, which usually requires elevated privileges.
+    pub fn device_serial_number_capability(
+        &self,
+    ) -> Result<&Option<PciDeviceSerialNumberCapability>, &PciInfoPropertyError> {
+        self.properties
+            .device_serial_number_capability
+            .as_result_ref()
+    }
+
+    /// Returns the Vital Product Data of this device, or `None` if the
+    /// device does not provide VPD. See the [`pci_vpd`](crate::pci_vpd)
+    /// module for details.
+    ///
+    /// VPD is not part of the configuration space and is returned only by
+    /// enumerators that can read it from the OS, when asked to (see
+    /// `LinuxProcFsPciEnumerator::with_vital_product_data`); reading it
+    /// usually requires elevated privileges.
+    pub fn vital_product_data(
+        &self,
+    ) -> Result<&Option<PciVitalProductData>, &PciInfoPropertyError> {
+        self.properties.vital_product_data.as_result_ref()
+    }
+
+    /// Compares the configuration space of this device with a later snapshot
+    /// of the same device, reporting the registers that changed; see
+    /// [`diff_config_spaces`]. The raw configuration spaces are compared if
+    /// the enumerator provides them, otherwise the headers are.
+    ///
+    /// Returns `None` if the headers of either device are not available.
+    pub fn config_space_changes(&self, after: &PciDevice) -> Option<Vec<PciRegisterChange>> {
+        let before = self.config_space_bytes()?;
+        let after = after.config_space_bytes()?;
+
+        Some(diff_config_spaces(before.as_slice(), after.as_slice()))
+    }
+
+    fn config_space_bytes(&self) -> Option<Vec<u8>> {
+        if let Ok(config) = self.pci_config_space() {
+            return Some(config.as_bytes().to_vec());
+        }
+
+        let mut bytes = vec![0u8; PciCommonHeader::MAX_HEADER_LEN];
+        self.pci_common_header()
+            .ok()?
+            .write_bytes(&mut bytes)
+            .ok()?;
+
+        let len = match self.pci_specialized_header() {
+            Ok(header) => {
+                header.write_subheader(&mut bytes, true).ok()?;
+                PciSpecializedHeader::length_of_subheader(header.header_type())?
+            }
+            Err(_) => PciCommonHeader::COMMON_HEADER_LEN,
+        };
+
+        bytes.truncate(len);
+        Some(bytes)
+    }
 }
 
 impl fmt::Debug for PciDevice {
@@ -290,3 +572,95 @@ impl fmt::Debug for PciDevice {
         write!(f, "]")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_headers::{PciConfigSpaceBuilder, PciExtendedCapabilityId};
+
+    fn device_with_config(config: &[u8]) -> PciDevice {
+        let common = PciCommonHeader::with_bytes(config).unwrap();
+        let specialized =
+            PciSpecializedHeader::read_subheader(common.header_type, config, true).unwrap();
+        let mut device = PciDevice::from_pci_header_set(common, Some(specialized));
+        device.set_config_space(PciConfigSpace::with_bytes(config).unwrap());
+        device
+    }
+
+    #[test]
+    fn shared_virtual_addressing_requires_ats_pri_and_pasid() {
+        let mut pri = vec![0x01, 0x00, 0x00, 0x01];
+        pri.extend_from_slice(&512u32.to_le_bytes());
+        pri.extend_from_slice(&0u32.to_le_bytes());
+
+        let builder = |pri: &[u8], pasid: &[u8]| {
+            PciConfigSpaceBuilder::new(0x8086, 0x0B25)
+                .with_extended_capability(
+                    PciExtendedCapabilityId::AddressTranslationServices,
+                    1,
+                    &[0x00, 0x00, 0x00, 0x80],
+                )
+                .with_extended_capability(PciExtendedCapabilityId::PageRequestInterface, 1, pri)
+                .with_extended_capability(PciExtendedCapabilityId::ProcessAddressSpaceId, 1, pasid)
+        };
+
+        let device = device_with_config(&builder(&pri, &[0x00, 0x14, 0x00, 0x00]).build().unwrap());
+
+        assert!(device.ats_capability().unwrap().is_some());
+        assert_eq!(
+            device
+                .pri_capability()
+                .unwrap()
+                .as_ref()
+                .map(|pri| pri.outstanding_page_request_capacity),
+            Some(512)
+        );
+        assert_eq!(
+            device
+                .pasid_capability()
+                .unwrap()
+                .as_ref()
+                .map(|pasid| pasid.capabilities.max_pasid_width()),
+            Some(20)
+        );
+        assert!(device.supports_shared_virtual_addressing().unwrap());
+
+        // PASID width of zero
+        let device = device_with_config(&builder(&pri, &[0x00, 0x00, 0x00, 0x00]).build().unwrap());
+        assert!(!device.supports_shared_virtual_addressing().unwrap());
+
+        // No outstanding page request capacity
+        let device = device_with_config(
+            &builder(
+                &[0x01, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0, 0, 0],
+                &[0x00, 0x14, 0x00, 0x00],
+            )
+            .build()
+            .unwrap(),
+        );
+        assert!(!device.supports_shared_virtual_addressing().unwrap());
+
+        // No PRI capability
+        let config = PciConfigSpaceBuilder::new(0x8086, 0x0B25)
+            .with_extended_capability(
+                PciExtendedCapabilityId::AddressTranslationServices,
+                1,
+                &[0x00, 0x00, 0x00, 0x80],
+            )
+            .with_extended_capability(
+                PciExtendedCapabilityId::ProcessAddressSpaceId,
+                1,
+                &[0x00, 0x14, 0x00, 0x00],
+            )
+            .build()
+            .unwrap();
+        let device = device_with_config(&config);
+
+        assert!(device.pri_capability().unwrap().is_none());
+        assert!(!device.supports_shared_virtual_addressing().unwrap());
+
+        // The extended configuration space is not available
+        let device = device_with_config(&config[..PciConfigSpace::STANDARD_LEN]);
+        assert!(device.supports_shared_virtual_addressing().is_err());
+    }
+}
@@ -4,7 +4,35 @@
 //!
 //! Access starts from the [`PciCommonHeader`] type; for more property information
 //! create a [`PciSpecializedHeader`] using the [`PciCommonHeader::header_type`]
-//! field.
+//! field. Headers can also be written back to configuration space bytes
+//! with [`PciCommonHeader::write_bytes`] and
+//! [`PciSpecializedHeader::write_subheader`].
+//!
+//! The standard capability list of a device can be walked with a
+//! [`PciCapabilityIterator`], provided that the buffer contains more than the
+//! first 64 bytes of the configuration space. Similarly, the extended
+//! capability list of PCI Express devices can be walked with a
+//! [`PciExtendedCapabilityIterator`] when the full 4096 bytes of the
+//! configuration space are available.
+//!
+//! An owned copy of the whole configuration space can be kept in a
+//! [`PciConfigSpace`], which offers bounds-checked reads at any offset and
+//! access to the headers and the capabilities.
+//!
+//! Headers and capabilities can also be read lazily through the
+//! [`PciConfigAccess`] trait, which is implemented by byte slices, by
+//! [`PciConfigSpace`], by files such as `/proc/bus/pci/<bus>/<device>.<function>`
+//! (see [`PciConfigFile`]) and by the [`PciConfigMock`] used for testing.
+//!
+//! Configuration spaces of unknown origin, such as dumps taken on other
+//! machines, can be checked for structural problems with
+//! [`validate_config_space`] before being parsed.
+//!
+//! Two snapshots of the configuration space of a device can be compared
+//! register by register with [`diff_config_spaces`].
+//!
+//! Capabilities found this way can be decoded into typed structures, such as
+//! the [`PciExpressCapability`], through their `with_capability` constructor.
 //!
 //! # Example
 //! ```rust
@@ -35,15 +63,92 @@
 //! }
 //! ```
 
+mod pci_acs_capability;
+mod pci_aer_capability;
+mod pci_ats_capability;
+mod pci_bar;
+mod pci_bridge_window;
+mod pci_capabilities;
+mod pci_capability_id;
 mod pci_common_header;
+mod pci_config_access;
 mod pci_config_buffer;
+mod pci_config_diff;
+mod pci_config_space;
+mod pci_config_space_builder;
+mod pci_config_validator;
+mod pci_dpc_capability;
+mod pci_dsn_capability;
+mod pci_express_capability;
+mod pci_extended_capabilities;
+mod pci_extended_capability_id;
 mod pci_generic_device_header;
+mod pci_l1ss_capability;
+mod pci_ltr_capability;
+mod pci_msi_capability;
+mod pci_msix_capability;
+mod pci_pasid_capability;
+mod pci_power_management_capability;
+mod pci_pri_capability;
+mod pci_resizable_bar_capability;
 mod pci_specialized_header;
+mod pci_sriov_capability;
 mod pci_to_cardbus_bridge_header;
 mod pci_to_pci_bridge_header;
+#[cfg(test)]
+mod test_support;
 
-pub use pci_common_header::PciCommonHeader;
+pub use pci_acs_capability::{PciAcsCapabilities, PciAcsCapability, PciAcsControl};
+pub use pci_aer_capability::{
+    PciAerAssertedError, PciAerCapabilitiesControl, PciAerCapability, PciAerCorrectableErrors,
+    PciAerRootErrorCommand, PciAerRootErrorRegisters, PciAerRootErrorStatus, PciAerSeverity,
+    PciAerUncorrectableErrors,
+};
+pub use pci_ats_capability::{PciAtsCapabilities, PciAtsCapability, PciAtsControl};
+pub use pci_bar::PciBar;
+pub use pci_bridge_window::PciBridgeWindow;
+pub use pci_capabilities::{PciCapability, PciCapabilityIterator};
+pub use pci_capability_id::PciCapabilityId;
+pub use pci_common_header::{PciCommand, PciCommonHeader, PciDevselTiming, PciStatus};
+pub use pci_config_access::{PciConfigAccess, PciConfigFile, PciConfigMock};
+pub use pci_config_diff::{diff_config_spaces, PciRegisterChange};
+pub use pci_config_space::PciConfigSpace;
+pub use pci_config_space_builder::PciConfigSpaceBuilder;
+pub use pci_config_validator::{
+    validate_config_space, PciConfigFinding, PciConfigIssue, PciConfigSeverity,
+};
+pub use pci_dpc_capability::{
+    PciDpcCapabilities, PciDpcCapability, PciDpcControl, PciDpcRpPioErrors, PciDpcRpPioRegisters,
+    PciDpcStatus, PciDpcTriggerReason,
+};
+pub use pci_dsn_capability::PciDeviceSerialNumberCapability;
+pub use pci_express_capability::{
+    PciExpressCapabilitiesRegister, PciExpressCapability, PciExpressDeviceCapabilities,
+    PciExpressDeviceControl, PciExpressDevicePortType, PciExpressDeviceStatus,
+    PciExpressLinkCapabilities, PciExpressLinkControl, PciExpressLinkSpeed, PciExpressLinkStatus,
+    PciExpressLinkWidth, PciExpressSlotCapabilities, PciExpressSlotControl, PciExpressSlotStatus,
+};
+pub use pci_extended_capabilities::{PciExtendedCapability, PciExtendedCapabilityIterator};
+pub use pci_extended_capability_id::PciExtendedCapabilityId;
 pub use pci_generic_device_header::PciGenericDeviceHeader;
+pub use pci_l1ss_capability::{
+    PciL1PmSubstatesCapabilities, PciL1PmSubstatesCapability, PciL1PmSubstatesControl1,
+};
+pub use pci_ltr_capability::PciLtrCapability;
+pub use pci_msi_capability::{PciMsiCapability, PciMsiMessageControl};
+pub use pci_msix_capability::{PciMsixCapability, PciMsixMessageControl};
+pub use pci_pasid_capability::{PciPasidCapabilities, PciPasidCapability, PciPasidControl};
+pub use pci_power_management_capability::{
+    PciPowerManagementCapabilities, PciPowerManagementCapability, PciPowerManagementControlStatus,
+    PciPowerState,
+};
+pub use pci_pri_capability::{PciPriCapability, PciPriControl, PciPriStatus};
+pub use pci_resizable_bar_capability::{
+    PciResizableBar, PciResizableBarCapability, PciResizableBarSize,
+};
 pub use pci_specialized_header::PciSpecializedHeader;
-pub use pci_to_cardbus_bridge_header::PciToCardbusBridgeHeader;
-pub use pci_to_pci_bridge_header::PciToPciBridgeHeader;
+pub use pci_sriov_capability::{
+    PciSrIovCapabilities, PciSrIovCapability, PciSrIovControl, PciSrIovStatus,
+};
+pub use pci_to_cardbus_bridge_header::{PciCardbusBridgeControl, PciToCardbusBridgeHeader};
+pub use pci_to_pci_bridge_header::{PciBridgeControl, PciSecondaryStatus, PciToPciBridgeHeader};
@@ -0,0 +1,197 @@
+use crate::PciInfoError;
+
+use super::{PciConfigAccess, PciExtendedCapability, PciExtendedCapabilityId};
+
+pci_register_bits! {
+    /// The ACS Capability register of the Access Control Services capability.
+    pub struct PciAcsCapabilities(u16) {
+        /// ACS Source Validation is implemented.
+        source_validation, set_source_validation: 0 => "SrcValid";
+        /// ACS Translation Blocking is implemented.
+        translation_blocking, set_translation_blocking: 1 => "TransBlk";
+        /// ACS P2P Request Redirect is implemented.
+        p2p_request_redirect, set_p2p_request_redirect: 2 => "ReqRedir";
+        /// ACS P2P Completion Redirect is implemented.
+        p2p_completion_redirect, set_p2p_completion_redirect: 3 => "CmpltRedir";
+        /// ACS Upstream Forwarding is implemented.
+        upstream_forwarding, set_upstream_forwarding: 4 => "UpstreamFwd";
+        /// ACS P2P Egress Control is implemented.
+        p2p_egress_control, set_p2p_egress_control: 5 => "EgressCtrl";
+        /// ACS Direct Translated P2P is implemented.
+        direct_translated_p2p, set_direct_translated_p2p: 6 => "DirectTrans";
+    }
+}
+
+impl PciAcsCapabilities {
+    /// The number of bits in the Egress Control Vector register, if P2P
+    /// egress control is implemented.
+    pub fn egress_control_vector_size(&self) -> u16 {
+        match self.field(8, 0xFF) {
+            0 => 256,
+            n => n,
+        }
+    }
+}
+
+pci_register_bits! {
+    /// The ACS Control register of the Access Control Services capability.
+    pub struct PciAcsControl(u16) {
+        /// ACS Source Validation is enabled.
+        source_validation, set_source_validation: 0 => "SrcValid";
+        /// ACS Translation Blocking is enabled.
+        translation_blocking, set_translation_blocking: 1 => "TransBlk";
+        /// ACS P2P Request Redirect is enabled.
+        p2p_request_redirect, set_p2p_request_redirect: 2 => "ReqRedir";
+        /// ACS P2P Completion Redirect is enabled.
+        p2p_completion_redirect, set_p2p_completion_redirect: 3 => "CmpltRedir";
+        /// ACS Upstream Forwarding is enabled.
+        upstream_forwarding, set_upstream_forwarding: 4 => "UpstreamFwd";
+        /// ACS P2P Egress Control is enabled.
+        p2p_egress_control, set_p2p_egress_control: 5 => "EgressCtrl";
+        /// ACS Direct Translated P2P is enabled.
+        direct_translated_p2p, set_direct_translated_p2p: 6 => "DirectTrans";
+    }
+}
+
+/// The Access Control Services extended capability (extended capability
+/// ID 000Dh) of a PCI Express port or multi-function device.
+///
+/// The format of the capability in PCI configuration space is the following.
+///
+///  ```text
+/// +---------+-------------+--------------+----------------+----------------+
+/// | Offset  | Bits 31-24  | Bits 23-16   | Bits 15-8      | Bits 7-0       |
+/// +---------+-------------+--------------+----------------+----------------+
+/// |   0x00  | Next offset | Version      |       Extended capability ID    |
+/// |   0x04  |        ACS Control         |         ACS Capability          |
+/// |   0x08  |         Egress Control Vector (if implemented)               |
+/// +---------+-------------+--------------+----------------+----------------+
+/// ```
+#[derive(Clone, Debug)]
+pub struct PciAcsCapability {
+    pub capabilities: PciAcsCapabilities,
+    pub control: PciAcsControl,
+    /// The Egress Control Vector, present only if P2P egress control is
+    /// implemented. Bit `n` of the vector is bit `n % 32` of the dword
+    /// `n / 32`.
+    pub egress_control_vector: Option<Vec<u32>>,
+}
+
+impl PciAcsCapability {
+    pub const ID: PciExtendedCapabilityId = PciExtendedCapabilityId::AccessControlServices;
+
+    /// The controls that must be enabled, if implemented, for a port to
+    /// isolate its downstream devices from peer-to-peer transactions. These
+    /// are the same controls required by the Linux IOMMU grouping logic
+    /// (`REQ_ACS_FLAGS`): source validation, P2P request redirect, P2P
+    /// completion redirect and upstream forwarding.
+    pub const ISOLATION_CONTROLS: PciAcsControl = PciAcsControl(0x001D);
+
+    /// Decodes an ACS capability found in the extended capability list.
+    pub fn with_capability<A: PciConfigAccess + ?Sized>(
+        cap: &PciExtendedCapability<'_, A>,
+    ) -> Result<Self, PciInfoError> {
+        cap.assert_id(Self::ID)?;
+
+        let capabilities = PciAcsCapabilities(cap.read_u16(0x04)?);
+        let control = PciAcsControl(cap.read_u16(0x06)?);
+
+        let egress_control_vector = if capabilities.p2p_egress_control() {
+            let dwords = (capabilities.egress_control_vector_size() as usize + 31) / 32;
+
+            Some(
+                (0..dwords)
+                    .map(|i| cap.read_u32(0x08 + i * 4))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )
+        } else {
+            None
+        };
+
+        Ok(Self {
+            capabilities,
+            control,
+            egress_control_vector,
+        })
+    }
+
+    /// The length of the capability in configuration space, which depends
+    /// on the size of the Egress Control Vector.
+    pub fn length(&self) -> usize {
+        0x08 + self
+            .egress_control_vector
+            .as_ref()
+            .map_or(0, |v| v.len() * 4)
+    }
+
+    /// Returns the isolation controls that are implemented by this port but
+    /// are not enabled. Controls that are not implemented are not reported,
+    /// as the port is not capable of the corresponding peer-to-peer traffic.
+    pub fn missing_isolation_controls(&self) -> PciAcsControl {
+        PciAcsControl(Self::ISOLATION_CONTROLS.0 & self.capabilities.0 & !self.control.0)
+    }
+
+    /// Returns true if all the isolation controls implemented by this port
+    /// are enabled, i.e. if the port would be considered isolating by the
+    /// Linux IOMMU grouping logic.
+    ///
+    /// Note that a device without an ACS capability does not provide any
+    /// isolation, and that isolation must be enforced by all the ports
+    /// between a device and the root complex.
+    pub fn enforces_isolation(&self) -> bool {
+        self.missing_isolation_controls().0 == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn acs_config(capabilities: u16, control: u16) -> Vec<u8> {
+        let mut config = vec![0u8; 4096];
+        config[0x100..0x104].copy_from_slice(&0x0001_000Du32.to_le_bytes());
+        config[0x104..0x106].copy_from_slice(&capabilities.to_le_bytes());
+        config[0x106..0x108].copy_from_slice(&control.to_le_bytes());
+        config[0x108..0x10C].copy_from_slice(&0x0000_0005u32.to_le_bytes());
+        config
+    }
+
+    fn decode(config: &[u8]) -> PciAcsCapability {
+        let cap = crate::pci_headers::PciExtendedCapabilityIterator::with_bytes(config)
+            .find_capability(PciAcsCapability::ID)
+            .unwrap()
+            .unwrap();
+        PciAcsCapability::with_capability(&cap).unwrap()
+    }
+
+    #[test]
+    fn isolation_is_masked_by_capabilities() {
+        // Root port: SV, RR, CR, UF implemented and enabled, TB implemented
+        // but disabled.
+        let acs = decode(&acs_config(0x001F, 0x001D));
+        assert!(acs.enforces_isolation());
+        assert!(acs.egress_control_vector.is_none());
+        assert_eq!(acs.length(), 0x08);
+
+        // Multi-function endpoint implementing only RR and CR
+        let acs = decode(&acs_config(0x000C, 0x000C));
+        assert!(acs.enforces_isolation());
+
+        // Switch downstream port with upstream forwarding disabled
+        let acs = decode(&acs_config(0x001D, 0x000D));
+        assert!(!acs.enforces_isolation());
+        assert!(acs.missing_isolation_controls().upstream_forwarding());
+        assert_eq!(acs.missing_isolation_controls().bits(), 0x0010);
+    }
+
+    #[test]
+    fn reads_egress_control_vector() {
+        // Egress control implemented with an 8 bit vector
+        let acs = decode(&acs_config(0x083F, 0x003D));
+
+        assert_eq!(acs.capabilities.egress_control_vector_size(), 8);
+        assert_eq!(acs.egress_control_vector, Some(vec![5]));
+        assert_eq!(acs.length(), 0x0C);
+        assert!(acs.enforces_isolation());
+    }
+}
@@ -0,0 +1,400 @@
+use crate::{PciInfoError, PciLocation};
+
+use super::pci_config_buffer::write_u32_at;
+use super::{PciConfigAccess, PciExtendedCapability, PciExtendedCapabilityId};
+
+pci_register_bits! {
+    /// The uncorrectable errors reported by the Uncorrectable Error Status,
+    /// Mask and Severity registers of the AER capability.
+    pub struct PciAerUncorrectableErrors(u32) {
+        /// Data Link Protocol Error.
+        data_link_protocol_error, set_data_link_protocol_error: 4 => "DLP";
+        /// Surprise Down Error.
+        surprise_down_error, set_surprise_down_error: 5 => "SDES";
+        /// Poisoned TLP Received.
+        poisoned_tlp_received, set_poisoned_tlp_received: 12 => "TLP";
+        /// Flow Control Protocol Error.
+        flow_control_protocol_error, set_flow_control_protocol_error: 13 => "FCP";
+        /// Completion Timeout.
+        completion_timeout, set_completion_timeout: 14 => "CmpltTO";
+        /// Completer Abort.
+        completer_abort, set_completer_abort: 15 => "CmpltAbrt";
+        /// Unexpected Completion.
+        unexpected_completion, set_unexpected_completion: 16 => "UnxCmplt";
+        /// Receiver Overflow.
+        receiver_overflow, set_receiver_overflow: 17 => "RxOF";
+        /// Malformed TLP.
+        malformed_tlp, set_malformed_tlp: 18 => "MalfTLP";
+        /// ECRC Error.
+        ecrc_error, set_ecrc_error: 19 => "ECRC";
+        /// Unsupported Request Error.
+        unsupported_request_error, set_unsupported_request_error: 20 => "UnsupReq";
+        /// ACS Violation.
+        acs_violation, set_acs_violation: 21 => "ACSViol";
+        /// Uncorrectable Internal Error.
+        uncorrectable_internal_error, set_uncorrectable_internal_error: 22 => "UncorrIntErr";
+        /// MC Blocked TLP.
+        mc_blocked_tlp, set_mc_blocked_tlp: 23 => "BlockedTLP";
+        /// AtomicOp Egress Blocked.
+        atomic_op_egress_blocked, set_atomic_op_egress_blocked: 24 => "AtomicOpBlocked";
+        /// TLP Prefix Blocked Error.
+        tlp_prefix_blocked_error, set_tlp_prefix_blocked_error: 25 => "TLPBlockedErr";
+        /// Poisoned TLP Egress Blocked.
+        poisoned_tlp_egress_blocked, set_poisoned_tlp_egress_blocked: 26 => "PoisonTLPBlocked";
+        /// DMWr Request Egress Blocked.
+        dmwr_request_egress_blocked, set_dmwr_request_egress_blocked: 27 => "DMWrReqBlocked";
+        /// IDE Check Failed.
+        ide_check_failed, set_ide_check_failed: 28 => "IDECheck";
+        /// Misrouted IDE TLP.
+        misrouted_ide_tlp, set_misrouted_ide_tlp: 29 => "MisIDETLP";
+        /// PCRC Check Failed.
+        pcrc_check_failed, set_pcrc_check_failed: 30 => "PCRC_CHECK";
+        /// TLP Translation Egress Blocked.
+        tlp_translation_egress_blocked, set_tlp_translation_egress_blocked: 31 => "TLPXlatBlocked";
+    }
+}
+
+pci_register_bits! {
+    /// The correctable errors reported by the Correctable Error Status and
+    /// Mask registers of the AER capability.
+    pub struct PciAerCorrectableErrors(u32) {
+        /// Receiver Error.
+        receiver_error, set_receiver_error: 0 => "RxErr";
+        /// Bad TLP.
+        bad_tlp, set_bad_tlp: 6 => "BadTLP";
+        /// Bad DLLP.
+        bad_dllp, set_bad_dllp: 7 => "BadDLLP";
+        /// REPLAY_NUM Rollover.
+        replay_num_rollover, set_replay_num_rollover: 8 => "Rollover";
+        /// Replay Timer Timeout.
+        replay_timer_timeout, set_replay_timer_timeout: 12 => "Timeout";
+        /// Advisory Non-Fatal Error.
+        advisory_non_fatal_error, set_advisory_non_fatal_error: 13 => "AdvNonFatalErr";
+        /// Corrected Internal Error.
+        corrected_internal_error, set_corrected_internal_error: 14 => "CorrIntErr";
+        /// Header Log Overflow.
+        header_log_overflow, set_header_log_overflow: 15 => "HeaderOF";
+    }
+}
+
+pci_register_bits! {
+    /// The Advanced Error Capabilities and Control register of the AER capability.
+    pub struct PciAerCapabilitiesControl(u32) {
+        /// ECRC generation is supported.
+        ecrc_generation_capable, set_ecrc_generation_capable: 5 => "GenCap";
+        /// ECRC generation is enabled.
+        ecrc_generation_enable, set_ecrc_generation_enable: 6 => "GenEn";
+        /// ECRC checking is supported.
+        ecrc_check_capable, set_ecrc_check_capable: 7 => "ChkCap";
+        /// ECRC checking is enabled.
+        ecrc_check_enable, set_ecrc_check_enable: 8 => "ChkEn";
+        /// Recording of multiple headers is supported.
+        multiple_header_recording_capable, set_multiple_header_recording_capable: 9 => "MultHdrRecCap";
+        /// Recording of multiple headers is enabled.
+        multiple_header_recording_enable, set_multiple_header_recording_enable: 10 => "MultHdrRecEn";
+        /// The TLP Prefix Log register is present.
+        tlp_prefix_log_present, set_tlp_prefix_log_present: 11 => "TLPPfxPres";
+        /// Headers of TLPs causing completion timeouts can be logged.
+        completion_timeout_prefix_header_log_capable, set_completion_timeout_prefix_header_log_capable: 12 => "HdrLogCap";
+    }
+}
+
+impl PciAerCapabilitiesControl {
+    /// The bit position, in the Uncorrectable Error Status register, of the
+    /// first uncorrectable error that was reported.
+    pub fn first_error_pointer(&self) -> u8 {
+        self.field(0, 0x1F) as u8
+    }
+
+    /// Sets the First Error Pointer field.
+    pub fn set_first_error_pointer(&mut self, bit: u8) {
+        self.set_field(0, 0x1F, bit as u32)
+    }
+}
+
+/// The severity of an error reported through AER.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PciAerSeverity {
+    /// A correctable error.
+    Correctable,
+    /// An uncorrectable error reported as non-fatal.
+    NonFatal,
+    /// An uncorrectable error reported as fatal.
+    Fatal,
+}
+
+/// An error whose status bit is set in an AER capability.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PciAerAssertedError {
+    /// The short name of the error, as printed by `lspci`.
+    pub name: &'static str,
+    /// The bit of the error in the status register.
+    pub bit: u8,
+    /// The severity of the error.
+    pub severity: PciAerSeverity,
+    /// True if reporting of the error is masked.
+    pub masked: bool,
+    /// True if the First Error Pointer points to this error.
+    pub first: bool,
+}
+
+/// The Advanced Error Reporting extended capability (extended capability ID
+/// 0001h) of a PCI Express function.
+///
+/// Only the registers common to every function are decoded by this type;
+/// the registers specific to root ports and root complex event collectors
+/// can be decoded with [`PciAerRootErrorRegisters`].
+///
+/// The format of the capability in PCI configuration space is the following.
+///
+///  ```text
+/// +---------+-------------+--------------+----------------+----------------+
+/// | Offset  | Bits 31-24  | Bits 23-16   | Bits 15-8      | Bits 7-0       |
+/// +---------+-------------+--------------+----------------+----------------+
+/// |   0x00  | Next offset | Version      |       Extended capability ID    |
+/// |   0x04  |              Uncorrectable Error Status                      |
+/// |   0x08  |              Uncorrectable Error Mask                        |
+/// |   0x0C  |              Uncorrectable Error Severity                    |
+/// |   0x10  |               Correctable Error Status                       |
+/// |   0x14  |                Correctable Error Mask                        |
+/// |   0x18  |       Advanced Error Capabilities and Control                |
+/// |   0x1C  |                 Header Log (4 registers)                     |
+/// |   0x2C  |  Root Error Command (root ports and event collectors only)    |
+/// |   0x30  |  Root Error Status (root ports and event collectors only)     |
+/// |   0x34  |  Error Source Identification (root ports and collectors only) |
+/// +---------+-------------+--------------+----------------+----------------+
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PciAerCapability {
+    pub uncorrectable_status: PciAerUncorrectableErrors,
+    pub uncorrectable_mask: PciAerUncorrectableErrors,
+    pub uncorrectable_severity: PciAerUncorrectableErrors,
+    pub correctable_status: PciAerCorrectableErrors,
+    pub correctable_mask: PciAerCorrectableErrors,
+    pub capabilities_control: PciAerCapabilitiesControl,
+    pub header_log: [u32; 4],
+}
+
+impl PciAerCapability {
+    pub const ID: PciExtendedCapabilityId = PciExtendedCapabilityId::AdvancedErrorReporting;
+    /// The length of the capability registers common to all functions.
+    pub const LENGTH: usize = 0x2C;
+
+    /// Decodes an AER capability found in the extended capability list.
+    pub fn with_capability<A: PciConfigAccess + ?Sized>(
+        cap: &PciExtendedCapability<'_, A>,
+    ) -> Result<Self, PciInfoError> {
+        cap.assert_id(Self::ID)?;
+
+        Ok(Self {
+            uncorrectable_status: PciAerUncorrectableErrors(cap.read_u32(0x04)?),
+            uncorrectable_mask: PciAerUncorrectableErrors(cap.read_u32(0x08)?),
+            uncorrectable_severity: PciAerUncorrectableErrors(cap.read_u32(0x0C)?),
+            correctable_status: PciAerCorrectableErrors(cap.read_u32(0x10)?),
+            correctable_mask: PciAerCorrectableErrors(cap.read_u32(0x14)?),
+            capabilities_control: PciAerCapabilitiesControl(cap.read_u32(0x18)?),
+            header_log: [
+                cap.read_u32(0x1C)?,
+                cap.read_u32(0x20)?,
+                cap.read_u32(0x24)?,
+                cap.read_u32(0x28)?,
+            ],
+        })
+    }
+
+    /// Encodes the capability as it appears in configuration space, as a
+    /// version 1 capability with a null next capability offset. The root
+    /// port registers can be added with [`PciAerRootErrorRegisters::to_bytes`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0u8; Self::LENGTH];
+        write_u32_at(&mut bytes, 0x00, Self::ID.as_code() as u32 | 1 << 16);
+        write_u32_at(&mut bytes, 0x04, self.uncorrectable_status.bits());
+        write_u32_at(&mut bytes, 0x08, self.uncorrectable_mask.bits());
+        write_u32_at(&mut bytes, 0x0C, self.uncorrectable_severity.bits());
+        write_u32_at(&mut bytes, 0x10, self.correctable_status.bits());
+        write_u32_at(&mut bytes, 0x14, self.correctable_mask.bits());
+        write_u32_at(&mut bytes, 0x18, self.capabilities_control.bits());
+
+        for (i, dw) in self.header_log.iter().enumerate() {
+            write_u32_at(&mut bytes, 0x1C + i * 4, *dw);
+        }
+
+        bytes
+    }
+
+    /// Lists the errors whose status bits are currently set, uncorrectable
+    /// errors first.
+    pub fn asserted_errors(&self) -> Vec<PciAerAssertedError> {
+        let first_error = self.capabilities_control.first_error_pointer();
+        let uncorrectable = PciAerUncorrectableErrors::FLAGS
+            .iter()
+            .filter(|(_, mask)| self.uncorrectable_status.0 & mask != 0)
+            .map(|(name, mask)| PciAerAssertedError {
+                name,
+                bit: mask.trailing_zeros() as u8,
+                severity: match self.uncorrectable_severity.0 & mask {
+                    0 => PciAerSeverity::NonFatal,
+                    _ => PciAerSeverity::Fatal,
+                },
+                masked: self.uncorrectable_mask.0 & mask != 0,
+                first: mask.trailing_zeros() as u8 == first_error,
+            });
+        let correctable = PciAerCorrectableErrors::FLAGS
+            .iter()
+            .filter(|(_, mask)| self.correctable_status.0 & mask != 0)
+            .map(|(name, mask)| PciAerAssertedError {
+                name,
+                bit: mask.trailing_zeros() as u8,
+                severity: PciAerSeverity::Correctable,
+                masked: self.correctable_mask.0 & mask != 0,
+                first: false,
+            });
+
+        uncorrectable.chain(correctable).collect()
+    }
+}
+
+pci_register_bits! {
+    /// The Root Error Command register of the AER capability.
+    pub struct PciAerRootErrorCommand(u32) {
+        /// Correctable errors are reported to the system.
+        correctable_error_reporting_enable, set_correctable_error_reporting_enable: 0 => "CERptEn";
+        /// Non-fatal errors are reported to the system.
+        non_fatal_error_reporting_enable, set_non_fatal_error_reporting_enable: 1 => "NFERptEn";
+        /// Fatal errors are reported to the system.
+        fatal_error_reporting_enable, set_fatal_error_reporting_enable: 2 => "FERptEn";
+    }
+}
+
+pci_register_bits! {
+    /// The Root Error Status register of the AER capability.
+    pub struct PciAerRootErrorStatus(u32) {
+        /// An ERR_COR message was received.
+        err_cor_received, set_err_cor_received: 0 => "CERcvd";
+        /// Multiple ERR_COR messages were received.
+        multiple_err_cor_received, set_multiple_err_cor_received: 1 => "MultCERcvd";
+        /// An ERR_FATAL or ERR_NONFATAL message was received.
+        err_fatal_nonfatal_received, set_err_fatal_nonfatal_received: 2 => "UERcvd";
+        /// Multiple ERR_FATAL or ERR_NONFATAL messages were received.
+        multiple_err_fatal_nonfatal_received, set_multiple_err_fatal_nonfatal_received: 3 => "MultUERcvd";
+        /// The first uncorrectable error message received was fatal.
+        first_uncorrectable_fatal, set_first_uncorrectable_fatal: 4 => "FirstFatal";
+        /// A non-fatal error message was received.
+        non_fatal_error_messages_received, set_non_fatal_error_messages_received: 5 => "NonFatalMsg";
+        /// A fatal error message was received.
+        fatal_error_messages_received, set_fatal_error_messages_received: 6 => "FatalMsg";
+    }
+}
+
+impl PciAerRootErrorStatus {
+    /// The MSI/MSI-X vector used for the interrupts generated by AER.
+    pub fn interrupt_message_number(&self) -> u8 {
+        self.field(27, 0x1F) as u8
+    }
+}
+
+/// The registers of the AER capability that are only implemented by root
+/// ports and root complex event collectors.
+#[derive(Clone, Debug, Default)]
+pub struct PciAerRootErrorRegisters {
+    pub root_error_command: PciAerRootErrorCommand,
+    pub root_error_status: PciAerRootErrorStatus,
+    pub error_source_identification: u32,
+}
+
+impl PciAerRootErrorRegisters {
+    /// The length of the AER capability of root ports and root complex
+    /// event collectors.
+    pub const LENGTH: usize = 0x38;
+
+    /// Decodes the root port registers of an AER capability found in the
+    /// extended capability list. This must be called only on the AER
+    /// capability of root ports and root complex event collectors.
+    pub fn with_capability<A: PciConfigAccess + ?Sized>(
+        cap: &PciExtendedCapability<'_, A>,
+    ) -> Result<Self, PciInfoError> {
+        cap.assert_id(PciAerCapability::ID)?;
+
+        Ok(Self {
+            root_error_command: PciAerRootErrorCommand(cap.read_u32(0x2C)?),
+            root_error_status: PciAerRootErrorStatus(cap.read_u32(0x30)?),
+            error_source_identification: cap.read_u32(0x34)?,
+        })
+    }
+
+    /// Encodes the AER capability `aer` of a root port, followed by these
+    /// root port registers, as it appears in configuration space.
+    pub fn to_bytes(&self, aer: &PciAerCapability) -> Vec<u8> {
+        let mut bytes = aer.to_bytes();
+        bytes.resize(Self::LENGTH, 0);
+        write_u32_at(&mut bytes, 0x2C, self.root_error_command.bits());
+        write_u32_at(&mut bytes, 0x30, self.root_error_status.bits());
+        write_u32_at(&mut bytes, 0x34, self.error_source_identification);
+        bytes
+    }
+
+    /// The location of the function that sent the first ERR_COR message.
+    pub fn err_cor_source(&self) -> PciLocation {
+        PciLocation::with_bdf_u16(self.error_source_identification as u16)
+    }
+
+    /// The location of the function that sent the first ERR_FATAL or
+    /// ERR_NONFATAL message.
+    pub fn err_fatal_nonfatal_source(&self) -> PciLocation {
+        PciLocation::with_bdf_u16((self.error_source_identification >> 16) as u16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_headers::PciExtendedCapabilityIterator;
+
+    #[test]
+    fn lists_asserted_errors() {
+        let mut config = vec![0u8; 4096];
+        config[0x100..0x104].copy_from_slice(&0x0002_0001u32.to_le_bytes());
+        // Completion timeout (fatal, first) and unsupported request (masked)
+        config[0x104..0x108].copy_from_slice(&0x0010_4000u32.to_le_bytes());
+        config[0x108..0x10C].copy_from_slice(&0x0010_0000u32.to_le_bytes());
+        config[0x10C..0x110].copy_from_slice(&0x0000_4000u32.to_le_bytes());
+        // Bad TLP
+        config[0x110..0x114].copy_from_slice(&0x0000_0040u32.to_le_bytes());
+        config[0x118..0x11C].copy_from_slice(&14u32.to_le_bytes());
+
+        let cap = PciExtendedCapabilityIterator::with_bytes(&config)
+            .find_capability(PciAerCapability::ID)
+            .unwrap()
+            .unwrap();
+        let aer = PciAerCapability::with_capability(&cap).unwrap();
+
+        assert!(aer.uncorrectable_status.completion_timeout());
+        assert!(aer.correctable_status.bad_tlp());
+        assert_eq!(
+            aer.asserted_errors(),
+            vec![
+                PciAerAssertedError {
+                    name: "CmpltTO",
+                    bit: 14,
+                    severity: PciAerSeverity::Fatal,
+                    masked: false,
+                    first: true,
+                },
+                PciAerAssertedError {
+                    name: "UnsupReq",
+                    bit: 20,
+                    severity: PciAerSeverity::NonFatal,
+                    masked: true,
+                    first: false,
+                },
+                PciAerAssertedError {
+                    name: "BadTLP",
+                    bit: 6,
+                    severity: PciAerSeverity::Correctable,
+                    masked: false,
+                    first: false,
+                },
+            ]
+        );
+    }
+}
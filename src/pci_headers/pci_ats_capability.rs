@@ -0,0 +1,104 @@
+use crate::PciInfoError;
+
+use super::{PciConfigAccess, PciExtendedCapability, PciExtendedCapabilityId};
+
+pci_register_bits! {
+    /// The ATS Capability register of the Address Translation Services
+    /// capability.
+    pub struct PciAtsCapabilities(u16) {
+        /// Untranslated address requests are always aligned to a 4096 byte
+        /// boundary.
+        page_aligned_request, set_page_aligned_request: 5 => "PageAlignedReq";
+        /// Global invalidate requests are supported.
+        global_invalidate_supported, set_global_invalidate_supported: 6 => "GlobalInvalidate";
+        /// Relaxed ordering of translation requests is supported.
+        relaxed_ordering_supported, set_relaxed_ordering_supported: 7 => "RelaxedOrdering";
+    }
+}
+
+impl PciAtsCapabilities {
+    /// The number of invalidate requests that the function can accept
+    /// before throttling.
+    pub fn invalidate_queue_depth(&self) -> u16 {
+        match self.field(0, 0x1F) {
+            0 => 32,
+            n => n,
+        }
+    }
+}
+
+pci_register_bits! {
+    /// The ATS Control register of the Address Translation Services
+    /// capability.
+    pub struct PciAtsControl(u16) {
+        /// ATS is enabled.
+        enable, set_enable: 15 => "Enable";
+    }
+}
+
+impl PciAtsControl {
+    /// The minimum number of 4096 byte blocks in a translation completion,
+    /// as a power of two.
+    pub fn smallest_translation_unit(&self) -> u16 {
+        self.field(0, 0x1F)
+    }
+}
+
+/// The Address Translation Services extended capability (extended
+/// capability ID 000Fh) of a PCI Express function, which allows the
+/// function to request address translations from the IOMMU and cache them
+/// in its own translation cache.
+///
+/// The format of the capability in PCI configuration space is the following.
+///
+///  ```text
+/// +---------+-------------+--------------+----------------+----------------+
+/// | Offset  | Bits 31-24  | Bits 23-16   | Bits 15-8      | Bits 7-0       |
+/// +---------+-------------+--------------+----------------+----------------+
+/// |   0x00  | Next offset | Version      |       Extended capability ID    |
+/// |   0x04  |        ATS Control         |         ATS Capability          |
+/// +---------+-------------+--------------+----------------+----------------+
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PciAtsCapability {
+    pub capabilities: PciAtsCapabilities,
+    pub control: PciAtsControl,
+}
+
+impl PciAtsCapability {
+    pub const ID: PciExtendedCapabilityId = PciExtendedCapabilityId::AddressTranslationServices;
+    pub const LENGTH: usize = 0x08;
+
+    /// Decodes an ATS capability found in the extended capability list.
+    pub fn with_capability<A: PciConfigAccess + ?Sized>(
+        cap: &PciExtendedCapability<'_, A>,
+    ) -> Result<Self, PciInfoError> {
+        cap.assert_id(Self::ID)?;
+
+        Ok(Self {
+            capabilities: PciAtsCapabilities(cap.read_u16(0x04)?),
+            control: PciAtsControl(cap.read_u16(0x06)?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_headers::test_support::decode_extended_capability;
+
+    #[test]
+    fn decodes_queue_depth_and_control() {
+        let ats = decode_extended_capability(
+            PciAtsCapability::ID,
+            &[0x60, 0x00, 0x00, 0x80],
+            PciAtsCapability::with_capability,
+        );
+
+        assert_eq!(ats.capabilities.invalidate_queue_depth(), 32);
+        assert!(ats.capabilities.page_aligned_request());
+        assert!(ats.capabilities.global_invalidate_supported());
+        assert!(ats.control.enable());
+        assert_eq!(ats.control.smallest_translation_unit(), 0);
+    }
+}
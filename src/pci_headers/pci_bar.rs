@@ -0,0 +1,280 @@
+use std::ops::RangeInclusive;
+
+/// A decoded Base Address Register of a PCI device or bridge.
+///
+/// 64-bit memory BARs occupy two consecutive registers; they are decoded as
+/// a single `PciBar::Memory64` whose `index` is the index of the lower
+/// register. The size of a BAR cannot be determined from the configuration
+/// space alone (as it requires writing to the register), so it is only
+/// available when provided by the enumerator in use.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PciBar {
+    /// A BAR mapping a region in I/O space.
+    Io {
+        index: u8,
+        base: u32,
+        size: Option<u64>,
+    },
+    /// A BAR mapping a region anywhere in the 32-bit memory space.
+    Memory32 {
+        index: u8,
+        base: u32,
+        prefetchable: bool,
+        size: Option<u64>,
+    },
+    /// A BAR mapping a region anywhere in the 64-bit memory space,
+    /// occupying two registers.
+    Memory64 {
+        index: u8,
+        base: u64,
+        prefetchable: bool,
+        size: Option<u64>,
+    },
+    /// A legacy BAR mapping a region below 1 MiB in memory space.
+    Memory32Below1M {
+        index: u8,
+        base: u32,
+        prefetchable: bool,
+        size: Option<u64>,
+    },
+}
+
+impl PciBar {
+    /// Decodes the raw values of a sequence of Base Address Registers,
+    /// merging the two halves of 64-bit BARs. Registers with a raw value of
+    /// zero are considered unused and skipped, as is a 64-bit BAR in the
+    /// last register, which has no upper half.
+    pub fn from_registers(registers: &[u32]) -> Vec<PciBar> {
+        Self::from_registers_with_sizes(registers, &[])
+    }
+
+    /// Decodes the raw values of a sequence of Base Address Registers like
+    /// [`PciBar::from_registers`], attaching the sizes reported by an
+    /// enumerator. `sizes` is indexed by register; a missing size or a size
+    /// of zero is considered unknown. Registers with a raw value of zero are
+    /// not skipped if they have a size, as they are implemented but
+    /// unassigned.
+    pub fn from_registers_with_sizes(registers: &[u32], sizes: &[u64]) -> Vec<PciBar> {
+        let mut bars = Vec::new();
+        let mut index = 0;
+
+        while index < registers.len() {
+            let raw = registers[index];
+            let size = sizes.get(index).copied().filter(|s| *s != 0);
+            let prefetchable = raw & 0x8 != 0;
+            let bar_index = index as u8;
+
+            index += 1;
+
+            if raw == 0 && size.is_none() {
+                continue;
+            }
+
+            let bar = match (raw & 0x1, (raw >> 1) & 0x3) {
+                (1, _) => PciBar::Io {
+                    index: bar_index,
+                    base: raw & !0x3,
+                    size,
+                },
+                (_, 0b10) => {
+                    let Some(upper) = registers.get(index) else {
+                        continue;
+                    };
+
+                    index += 1;
+
+                    PciBar::Memory64 {
+                        index: bar_index,
+                        base: (raw & !0xF) as u64 | (*upper as u64) << 32,
+                        prefetchable,
+                        size,
+                    }
+                }
+                (_, 0b01) => PciBar::Memory32Below1M {
+                    index: bar_index,
+                    base: raw & !0xF,
+                    prefetchable,
+                    size,
+                },
+                _ => PciBar::Memory32 {
+                    index: bar_index,
+                    base: raw & !0xF,
+                    prefetchable,
+                    size,
+                },
+            };
+
+            bars.push(bar);
+        }
+
+        bars
+    }
+
+    /// Encodes this BAR as the raw values of its registers (two for 64-bit
+    /// BARs, one otherwise). The size of the BAR is not encoded.
+    pub fn to_registers(&self) -> Vec<u32> {
+        let prefetchable = if self.is_prefetchable() { 0x8 } else { 0 };
+
+        match self {
+            Self::Io { base, .. } => vec![base & !0x3 | 0x1],
+            Self::Memory32 { base, .. } => vec![base & !0xF | prefetchable],
+            Self::Memory32Below1M { base, .. } => vec![base & !0xF | 0x2 | prefetchable],
+            Self::Memory64 { base, .. } => vec![
+                (*base as u32) & !0xF | 0x4 | prefetchable,
+                (base >> 32) as u32,
+            ],
+        }
+    }
+
+    /// The index of the (first) register of this BAR.
+    pub fn index(&self) -> u8 {
+        match self {
+            Self::Io { index, .. }
+            | Self::Memory32 { index, .. }
+            | Self::Memory64 { index, .. }
+            | Self::Memory32Below1M { index, .. } => *index,
+        }
+    }
+
+    /// The base address of the region mapped by this BAR.
+    pub fn base_address(&self) -> u64 {
+        match self {
+            Self::Io { base, .. }
+            | Self::Memory32 { base, .. }
+            | Self::Memory32Below1M { base, .. } => *base as u64,
+            Self::Memory64 { base, .. } => *base,
+        }
+    }
+
+    /// The size of the region mapped by this BAR, if known.
+    pub fn size(&self) -> Option<u64> {
+        match self {
+            Self::Io { size, .. }
+            | Self::Memory32 { size, .. }
+            | Self::Memory64 { size, .. }
+            | Self::Memory32Below1M { size, .. } => *size,
+        }
+    }
+
+    /// Sets the size of the region mapped by this BAR.
+    pub fn set_size(&mut self, new_size: Option<u64>) {
+        match self {
+            Self::Io { size, .. }
+            | Self::Memory32 { size, .. }
+            | Self::Memory64 { size, .. }
+            | Self::Memory32Below1M { size, .. } => *size = new_size,
+        }
+    }
+
+    /// Returns true if this BAR maps a region in I/O space.
+    pub fn is_io(&self) -> bool {
+        matches!(self, Self::Io { .. })
+    }
+
+    /// Returns true if this BAR maps a region in memory space.
+    pub fn is_memory(&self) -> bool {
+        !self.is_io()
+    }
+
+    /// Returns true if this BAR maps a prefetchable memory region.
+    pub fn is_prefetchable(&self) -> bool {
+        match self {
+            Self::Io { .. } => false,
+            Self::Memory32 { prefetchable, .. }
+            | Self::Memory64 { prefetchable, .. }
+            | Self::Memory32Below1M { prefetchable, .. } => *prefetchable,
+        }
+    }
+
+    /// The number of registers occupied by this BAR (2 for 64-bit BARs,
+    /// 1 otherwise).
+    pub fn register_count(&self) -> usize {
+        match self {
+            Self::Memory64 { .. } => 2,
+            _ => 1,
+        }
+    }
+
+    /// The range of addresses mapped by this BAR, if its size is known.
+    pub fn address_range(&self) -> Option<RangeInclusive<u64>> {
+        let base = self.base_address();
+        let end = base.checked_add(self.size()?.checked_sub(1)?)?;
+
+        Some(base..=end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairs_64bit_bars_and_skips_unused() {
+        let registers = [
+            0xFE00_000C,
+            0x0000_0001,
+            0,
+            0x0000_E001,
+            0xF000_0000,
+            0x0000_0004,
+        ];
+        let sizes = [0x10_0000, 0, 0x1000, 0x20, 0x4000, 0];
+
+        let bars = PciBar::from_registers_with_sizes(&registers[0..5], &sizes);
+
+        // The 64-bit BAR in the last register has no upper half and is
+        // skipped, but the other BARs are still decoded
+        assert_eq!(
+            PciBar::from_registers(&registers),
+            PciBar::from_registers(&registers[0..5])
+        );
+        assert_eq!(PciBar::from_registers(&registers).len(), 3);
+
+        assert_eq!(
+            bars,
+            vec![
+                PciBar::Memory64 {
+                    index: 0,
+                    base: 0x1_FE00_0000,
+                    prefetchable: true,
+                    size: Some(0x10_0000),
+                },
+                PciBar::Memory32 {
+                    index: 2,
+                    base: 0,
+                    prefetchable: false,
+                    size: Some(0x1000),
+                },
+                PciBar::Io {
+                    index: 3,
+                    base: 0xE000,
+                    size: Some(0x20),
+                },
+                PciBar::Memory32 {
+                    index: 4,
+                    base: 0xF000_0000,
+                    prefetchable: false,
+                    size: Some(0x4000),
+                },
+            ]
+        );
+
+        assert_eq!(bars[0].address_range(), Some(0x1_FE00_0000..=0x1_FE0F_FFFF));
+        assert_eq!(bars[2].address_range(), Some(0xE000..=0xE01F));
+
+        let encoded = bars
+            .iter()
+            .flat_map(|bar| bar.to_registers())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            encoded,
+            [0xFE00_000C, 0x0000_0001, 0, 0x0000_E001, 0xF000_0000]
+        );
+
+        let bars = PciBar::from_registers(&registers[0..3]);
+
+        assert_eq!(bars.len(), 1);
+        assert_eq!(bars[0].register_count(), 2);
+        assert_eq!(bars[0].address_range(), None);
+    }
+}
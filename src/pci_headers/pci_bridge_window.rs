@@ -0,0 +1,140 @@
+use std::ops::RangeInclusive;
+
+/// An address window forwarded by a bridge from its primary interface to
+/// its secondary interface.
+///
+/// Windows are defined by a base and a limit register; a window whose base
+/// is greater than its limit is disabled.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PciBridgeWindow {
+    /// The bridge does not forward any address in this window.
+    Disabled,
+    /// The bridge forwards the addresses in the specified inclusive range.
+    Enabled(RangeInclusive<u64>),
+}
+
+impl PciBridgeWindow {
+    pub(super) fn with_base_and_limit(base: u64, limit: u64) -> Self {
+        if base > limit {
+            Self::Disabled
+        } else {
+            Self::Enabled(base..=limit)
+        }
+    }
+
+    /// Returns true if the window is enabled.
+    pub fn is_enabled(&self) -> bool {
+        matches!(self, Self::Enabled(_))
+    }
+
+    /// Returns the range of addresses forwarded by this window, or `None` if
+    /// the window is disabled.
+    pub fn range(&self) -> Option<&RangeInclusive<u64>> {
+        match self {
+            Self::Disabled => None,
+            Self::Enabled(range) => Some(range),
+        }
+    }
+
+    /// Returns true if `address` is forwarded by this window.
+    pub fn contains(&self, address: u64) -> bool {
+        self.range().is_some_and(|range| range.contains(&address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_headers::{PciSpecializedHeader, PciToCardbusBridgeHeader};
+
+    fn write_u32(config: &mut [u8], offset: usize, value: u32) {
+        config[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    #[test]
+    fn decodes_pci_to_pci_bridge_windows() {
+        let mut config = [0u8; 64];
+        config[0x0E] = 0x01;
+        // 32-bit I/O window 0x1_2000..=0x1_3FFF
+        config[0x1C] = 0x21;
+        config[0x1D] = 0x31;
+        write_u32(&mut config, 0x30, 0x0001_0001);
+        // Memory window 0xFC00_0000..=0xFC2F_FFFF
+        write_u32(&mut config, 0x20, 0xFC20_FC00);
+        // 64-bit prefetchable window 0x38_0000_0000..=0x38_1FFF_FFFF
+        write_u32(&mut config, 0x24, 0x1FF1_0001);
+        write_u32(&mut config, 0x28, 0x38);
+        write_u32(&mut config, 0x2C, 0x38);
+
+        let Ok(PciSpecializedHeader::PciToPciBridge(mut header)) =
+            PciSpecializedHeader::read_subheader(1, &config, true)
+        else {
+            panic!("unexpected header type");
+        };
+
+        assert_eq!(
+            header.io_window(),
+            PciBridgeWindow::Enabled(0x1_2000..=0x1_3FFF)
+        );
+        assert_eq!(
+            header.memory_window(),
+            PciBridgeWindow::Enabled(0xFC00_0000..=0xFC2F_FFFF)
+        );
+        assert_eq!(
+            header.prefetchable_memory_window(),
+            PciBridgeWindow::Enabled(0x38_0000_0000..=0x38_1FFF_FFFF)
+        );
+        assert!(header.forwards_memory_address(0x38_1000_0000));
+        assert!(!header.forwards_memory_address(0xFC30_0000));
+
+        // 16-bit I/O and 32-bit prefetchable windows ignore the upper halves
+        header.io_base = 0x20;
+        header.io_limit = 0x30;
+        header.prefetchable_memory_base = 0x0010;
+        header.prefetchable_memory_limit = 0x01F0;
+
+        assert_eq!(
+            header.io_window(),
+            PciBridgeWindow::Enabled(0x2000..=0x3FFF)
+        );
+        assert_eq!(
+            header.prefetchable_memory_window(),
+            PciBridgeWindow::Enabled(0x0010_0000..=0x01FF_FFFF)
+        );
+
+        header.memory_base = 0xFFF0;
+        header.memory_limit = 0x0000;
+
+        assert_eq!(header.memory_window(), PciBridgeWindow::Disabled);
+        assert!(!header.memory_window().contains(0));
+    }
+
+    #[test]
+    fn decodes_cardbus_bridge_windows() {
+        let mut config = [0u8; PciToCardbusBridgeHeader::LENGTH];
+        config[0x0E] = 0x02;
+        write_u32(&mut config, 0x1C, 0x1000_0000);
+        write_u32(&mut config, 0x20, 0x1000_3000);
+        write_u32(&mut config, 0x24, 0x2000_0000);
+        write_u32(&mut config, 0x28, 0x1000_0000);
+        write_u32(&mut config, 0x2C, 0x0000_4001);
+        write_u32(&mut config, 0x30, 0x0000_40FC);
+
+        let Ok(PciSpecializedHeader::PciToCardbusBridge(header)) =
+            PciSpecializedHeader::read_subheader(2, &config, true)
+        else {
+            panic!("unexpected header type");
+        };
+
+        assert_eq!(
+            header.memory_window_0(),
+            PciBridgeWindow::Enabled(0x1000_0000..=0x1000_3FFF)
+        );
+        assert_eq!(header.memory_window_1(), PciBridgeWindow::Disabled);
+        assert_eq!(
+            header.io_window_0(),
+            PciBridgeWindow::Enabled(0x4000..=0x40FF)
+        );
+        assert_eq!(header.io_window_1(), PciBridgeWindow::Enabled(0..=3));
+    }
+}
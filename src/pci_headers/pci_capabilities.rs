@@ -0,0 +1,381 @@
+use crate::PciInfoError;
+
+use super::{
+    PciCapabilityId, PciCommonHeader, PciConfigAccess, PciGenericDeviceHeader,
+    PciToCardbusBridgeHeader, PciToPciBridgeHeader,
+};
+
+/// A capability found in the standard capability list of a PCI device.
+///
+/// The bytes of the capability start at the capability header (the
+/// capability ID and the pointer to the next capability) and extend up to
+/// the end of the standard configuration space (or of the configuration
+/// space access, if shorter), as the length of a capability depends on its
+/// type. The capability is read through the [`PciConfigAccess`] it was
+/// found in, which is a byte slice unless specified otherwise.
+pub struct PciCapability<'a, A: ?Sized = [u8]> {
+    id: u8,
+    offset: u8,
+    next: u8,
+    config: &'a A,
+    end: usize,
+}
+
+impl<A: ?Sized> Clone for PciCapability<'_, A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<A: ?Sized> Copy for PciCapability<'_, A> {}
+
+impl<'a> PciCapability<'a> {
+    /// Returns the raw bytes of this capability, starting at the
+    /// capability header.
+    pub fn bytes(&self) -> &'a [u8] {
+        &self.config[self.offset as usize..self.end]
+    }
+}
+
+impl<'a, A: PciConfigAccess + ?Sized> PciCapability<'a, A> {
+    /// Returns the raw ID of this capability.
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// Returns the ID of this capability as a `PciCapabilityId`.
+    pub fn capability_id(&self) -> PciCapabilityId {
+        PciCapabilityId::from_code(self.id)
+    }
+
+    /// Returns the offset of this capability in the configuration space.
+    pub fn offset(&self) -> u8 {
+        self.offset
+    }
+
+    /// Returns the raw pointer to the next capability in the list.
+    pub fn next_pointer(&self) -> u8 {
+        self.next
+    }
+
+    /// Returns the configuration space this capability was found in.
+    pub fn config(&self) -> &'a A {
+        self.config
+    }
+
+    /// Reads a byte at `offset` bytes from the start of the capability.
+    pub fn read_u8(&self, offset: usize) -> Result<u8, PciInfoError> {
+        self.config.read_u8(self.absolute_offset(offset, 1)?)
+    }
+
+    /// Reads a `u16` at `offset` bytes from the start of the capability.
+    pub fn read_u16(&self, offset: usize) -> Result<u16, PciInfoError> {
+        self.config.read_u16(self.absolute_offset(offset, 2)?)
+    }
+
+    /// Reads a `u32` at `offset` bytes from the start of the capability.
+    pub fn read_u32(&self, offset: usize) -> Result<u32, PciInfoError> {
+        self.config.read_u32(self.absolute_offset(offset, 4)?)
+    }
+
+    fn absolute_offset(&self, offset: usize, len: usize) -> Result<usize, PciInfoError> {
+        let absolute = (self.offset as usize)
+            .checked_add(offset)
+            .ok_or(PciInfoError::UnexpectedEof)?;
+
+        match absolute.checked_add(len) {
+            Some(end) if end <= self.end => Ok(absolute),
+            _ => Err(PciInfoError::UnexpectedEof),
+        }
+    }
+
+    pub(super) fn assert_id(&self, id: PciCapabilityId) -> Result<(), PciInfoError> {
+        if self.capability_id() != id {
+            return Err(PciInfoError::ParseError(
+                format!(
+                    "expected capability {id:?}, found {:?}",
+                    self.capability_id()
+                )
+                .into(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl<A: PciConfigAccess + ?Sized> std::fmt::Debug for PciCapability<'_, A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{:?} (0x{:02X}) at 0x{:02X}, next: 0x{:02X}]",
+            self.capability_id(),
+            self.id,
+            self.offset,
+            self.next
+        )
+    }
+}
+
+/// An iterator over the standard capability list of a PCI device, located
+/// in the `0x40..0x100` region of the configuration space.
+///
+/// The iterator yields an error, and then stops, when a capability pointer
+/// is out of range, when a loop is detected in the list or when the buffer
+/// ends before the capability is complete.
+///
+/// The iterator reads the configuration space through [`PciConfigAccess`],
+/// so that only the capability headers are read while walking the list; use
+/// [`PciCapabilityIterator::with_access`] for sources other than a byte
+/// slice.
+///
+/// # Example
+/// ```rust
+/// use pci_info::pci_headers::{PciCapabilityId, PciCapabilityIterator};
+///
+/// # fn main() -> Result<(), pci_info::PciInfoError> {
+/// let mut config = [0u8; 256];
+/// config[0x06] = 0x10; // Status: capabilities list
+/// config[0x34] = 0x40; // Capabilities pointer
+/// config[0x40] = 0x01; // Power management, next at 0x50
+/// config[0x41] = 0x50;
+/// config[0x50] = 0x05; // MSI, end of list
+///
+/// for cap in PciCapabilityIterator::with_bytes(&config)? {
+///     let cap = cap?;
+///     println!("{:?} at 0x{:02X}", cap.capability_id(), cap.offset());
+/// }
+///
+/// let msi = PciCapabilityIterator::with_bytes(&config)?.find_capability(PciCapabilityId::Msi)?;
+/// assert_eq!(msi.map(|c| c.offset()), Some(0x50));
+/// # Ok(())
+/// # }
+/// ```
+pub struct PciCapabilityIterator<'a, A: ?Sized = [u8]> {
+    config: &'a A,
+    next: u8,
+    visited: u64,
+}
+
+impl<A: ?Sized> Clone for PciCapabilityIterator<'_, A> {
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config,
+            next: self.next,
+            visited: self.visited,
+        }
+    }
+}
+
+impl<'a> PciCapabilityIterator<'a> {
+    /// The length of the standard (non extended) PCI configuration space.
+    pub const STANDARD_CONFIG_SPACE_LEN: usize = 0x100;
+    /// The lowest offset at which a capability can be located.
+    pub const FIRST_CAPABILITY_OFFSET: usize = 0x40;
+
+    /// Creates an iterator over the capabilities of the configuration space
+    /// contained in `bytes`, which must start at the beginning of the common
+    /// header.
+    ///
+    /// The "capabilities list" bit of the Status register is checked first;
+    /// if it is clear, the iterator is empty. The capabilities pointer is then
+    /// read from the right location for the header type of the device.
+    pub fn with_bytes(bytes: &'a [u8]) -> Result<Self, PciInfoError> {
+        Self::with_access(bytes)
+    }
+
+    /// Creates an iterator over the capabilities of the configuration space
+    /// contained in `bytes`, starting at the capability pointed by `pointer`
+    /// (e.g. the value of [`PciGenericDeviceHeader::capabilities_ptr`]).
+    /// The Status register is not checked. A `pointer` of zero produces an
+    /// empty iterator.
+    pub fn with_pointer(bytes: &'a [u8], pointer: u8) -> Self {
+        Self::with_access_and_pointer(bytes, pointer)
+    }
+}
+
+impl<'a, A: PciConfigAccess + ?Sized> PciCapabilityIterator<'a, A> {
+    /// Creates an iterator over the capabilities of the configuration space
+    /// accessed through `config`, like [`PciCapabilityIterator::with_bytes`].
+    pub fn with_access(config: &'a A) -> Result<Self, PciInfoError> {
+        let header = PciCommonHeader::with_access(config)?;
+
+        if !header.status_register().capabilities_list() {
+            return Ok(Self::with_access_and_pointer(config, 0));
+        }
+
+        let pointer_offset = match header.header_type & 0x7F {
+            PciGenericDeviceHeader::ID | PciToPciBridgeHeader::ID => 0x34,
+            PciToCardbusBridgeHeader::ID => 0x14,
+            _ => return Err(PciInfoError::UnknownPciHeaderType(header.header_type)),
+        };
+
+        Ok(Self::with_access_and_pointer(
+            config,
+            config.read_u8(pointer_offset)?,
+        ))
+    }
+
+    /// Creates an iterator over the capabilities of the configuration space
+    /// accessed through `config`, starting at the capability pointed by
+    /// `pointer`, like [`PciCapabilityIterator::with_pointer`].
+    pub fn with_access_and_pointer(config: &'a A, pointer: u8) -> Self {
+        Self {
+            config,
+            next: pointer,
+            visited: 0,
+        }
+    }
+
+    /// Walks the capability list looking for the first capability with the
+    /// specified id.
+    pub fn find_capability(
+        self,
+        id: PciCapabilityId,
+    ) -> Result<Option<PciCapability<'a, A>>, PciInfoError> {
+        for cap in self {
+            let cap = cap?;
+
+            if cap.capability_id() == id {
+                return Ok(Some(cap));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl<'a, A: PciConfigAccess + ?Sized> Iterator for PciCapabilityIterator<'a, A> {
+    type Item = Result<PciCapability<'a, A>, PciInfoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pointer = std::mem::take(&mut self.next);
+
+        if pointer == 0 {
+            return None;
+        }
+
+        // The two lowest bits of capability pointers are reserved
+        let offset = pointer & 0xFC;
+
+        if (offset as usize) < PciCapabilityIterator::FIRST_CAPABILITY_OFFSET {
+            return Some(Err(PciInfoError::PciCapabilityPointerOutOfRange(
+                pointer as u16,
+            )));
+        }
+
+        let visited_bit = 1u64 << (offset / 4);
+
+        if self.visited & visited_bit != 0 {
+            return Some(Err(PciInfoError::PciCapabilityLoop(offset as u16)));
+        }
+
+        self.visited |= visited_bit;
+
+        let header = match self.config.read_u16(offset as usize) {
+            Ok(h) => h,
+            Err(e) => return Some(Err(e)),
+        };
+
+        let id = (header & 0xFF) as u8;
+        let next = (header >> 8) as u8;
+        let end = self
+            .config
+            .size()
+            .min(PciCapabilityIterator::STANDARD_CONFIG_SPACE_LEN);
+
+        self.next = next;
+
+        Some(Ok(PciCapability {
+            id,
+            offset,
+            next,
+            config: self.config,
+            end,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with_caps(caps: &[(u8, u8, u8)]) -> [u8; 256] {
+        let mut config = [0u8; 256];
+        config[0x06] = 0x10;
+
+        for (i, (offset, id, next)) in caps.iter().enumerate() {
+            if i == 0 {
+                config[0x34] = *offset;
+            }
+            config[*offset as usize] = *id;
+            config[*offset as usize + 1] = *next;
+        }
+
+        config
+    }
+
+    #[test]
+    fn walks_capability_list() {
+        let config = config_with_caps(&[(0x40, 0x01, 0x50), (0x50, 0x05, 0x70), (0x70, 0x10, 0)]);
+        let caps = PciCapabilityIterator::with_bytes(&config)
+            .unwrap()
+            .map(|c| c.map(|c| (c.offset(), c.capability_id())))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            caps,
+            vec![
+                (0x40, PciCapabilityId::PowerManagement),
+                (0x50, PciCapabilityId::Msi),
+                (0x70, PciCapabilityId::PciExpress),
+            ]
+        );
+    }
+
+    #[test]
+    fn status_bit_clear_yields_nothing() {
+        let mut config = config_with_caps(&[(0x40, 0x01, 0)]);
+        config[0x06] = 0;
+
+        assert_eq!(
+            PciCapabilityIterator::with_bytes(&config).unwrap().count(),
+            0
+        );
+    }
+
+    #[test]
+    fn detects_loops() {
+        let config = config_with_caps(&[(0x40, 0x01, 0x50), (0x50, 0x05, 0x40)]);
+        let res = PciCapabilityIterator::with_bytes(&config)
+            .unwrap()
+            .collect::<Vec<_>>();
+
+        assert_eq!(res.len(), 3);
+        assert!(matches!(res[2], Err(PciInfoError::PciCapabilityLoop(0x40))));
+    }
+
+    #[test]
+    fn detects_out_of_range_pointers() {
+        let config = config_with_caps(&[(0x40, 0x01, 0x20)]);
+        let res = PciCapabilityIterator::with_bytes(&config)
+            .unwrap()
+            .collect::<Vec<_>>();
+
+        assert_eq!(res.len(), 2);
+        assert!(matches!(
+            res[1],
+            Err(PciInfoError::PciCapabilityPointerOutOfRange(0x20))
+        ));
+    }
+
+    #[test]
+    fn truncated_buffer_is_eof() {
+        let config = config_with_caps(&[(0x40, 0x01, 0)]);
+        let mut res = PciCapabilityIterator::with_bytes(&config[0..64]).unwrap();
+
+        assert!(matches!(res.next(), Some(Err(PciInfoError::UnexpectedEof))));
+        assert!(res.next().is_none());
+    }
+}
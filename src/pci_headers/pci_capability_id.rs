@@ -0,0 +1,126 @@
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+/// Represent the ID of a standard PCI capability as an enumeration,
+/// for easier matching with known valid values.
+///
+/// See the PCI Code and ID Assignment Specification for possible values.
+pub enum PciCapabilityId {
+    /// Enumeration matching capability ID 00h (null capability).
+    Null,
+    /// Enumeration matching capability ID 01h.
+    PowerManagement,
+    /// Enumeration matching capability ID 02h.
+    Agp,
+    /// Enumeration matching capability ID 03h.
+    VitalProductData,
+    /// Enumeration matching capability ID 04h.
+    SlotIdentification,
+    /// Enumeration matching capability ID 05h.
+    Msi,
+    /// Enumeration matching capability ID 06h.
+    CompactPciHotSwap,
+    /// Enumeration matching capability ID 07h.
+    PciX,
+    /// Enumeration matching capability ID 08h.
+    HyperTransport,
+    /// Enumeration matching capability ID 09h.
+    VendorSpecific,
+    /// Enumeration matching capability ID 0Ah.
+    DebugPort,
+    /// Enumeration matching capability ID 0Bh.
+    CompactPciResourceControl,
+    /// Enumeration matching capability ID 0Ch.
+    PciHotPlug,
+    /// Enumeration matching capability ID 0Dh.
+    BridgeSubsystemVendorId,
+    /// Enumeration matching capability ID 0Eh.
+    Agp8x,
+    /// Enumeration matching capability ID 0Fh.
+    SecureDevice,
+    /// Enumeration matching capability ID 10h.
+    PciExpress,
+    /// Enumeration matching capability ID 11h.
+    MsiX,
+    /// Enumeration matching capability ID 12h.
+    SataDataIndexConfiguration,
+    /// Enumeration matching capability ID 13h.
+    AdvancedFeatures,
+    /// Enumeration matching capability ID 14h.
+    EnhancedAllocation,
+    /// Enumeration matching capability ID 15h.
+    FlatteningPortalBridge,
+    /// Enumeration matching unknown capability IDs.
+    Unknown(u8),
+}
+
+impl PciCapabilityId {
+    /// Create a `PciCapabilityId` from the `u8` value that it
+    /// represents
+    pub fn from_code(id: u8) -> Self {
+        match id {
+            0x00 => Self::Null,
+            0x01 => Self::PowerManagement,
+            0x02 => Self::Agp,
+            0x03 => Self::VitalProductData,
+            0x04 => Self::SlotIdentification,
+            0x05 => Self::Msi,
+            0x06 => Self::CompactPciHotSwap,
+            0x07 => Self::PciX,
+            0x08 => Self::HyperTransport,
+            0x09 => Self::VendorSpecific,
+            0x0a => Self::DebugPort,
+            0x0b => Self::CompactPciResourceControl,
+            0x0c => Self::PciHotPlug,
+            0x0d => Self::BridgeSubsystemVendorId,
+            0x0e => Self::Agp8x,
+            0x0f => Self::SecureDevice,
+            0x10 => Self::PciExpress,
+            0x11 => Self::MsiX,
+            0x12 => Self::SataDataIndexConfiguration,
+            0x13 => Self::AdvancedFeatures,
+            0x14 => Self::EnhancedAllocation,
+            0x15 => Self::FlatteningPortalBridge,
+            unk => Self::Unknown(unk),
+        }
+    }
+
+    /// Gets the `u8` value that this `PciCapabilityId` represents
+    pub fn as_code(&self) -> u8 {
+        match self {
+            Self::Null => 0x00,
+            Self::PowerManagement => 0x01,
+            Self::Agp => 0x02,
+            Self::VitalProductData => 0x03,
+            Self::SlotIdentification => 0x04,
+            Self::Msi => 0x05,
+            Self::CompactPciHotSwap => 0x06,
+            Self::PciX => 0x07,
+            Self::HyperTransport => 0x08,
+            Self::VendorSpecific => 0x09,
+            Self::DebugPort => 0x0a,
+            Self::CompactPciResourceControl => 0x0b,
+            Self::PciHotPlug => 0x0c,
+            Self::BridgeSubsystemVendorId => 0x0d,
+            Self::Agp8x => 0x0e,
+            Self::SecureDevice => 0x0f,
+            Self::PciExpress => 0x10,
+            Self::MsiX => 0x11,
+            Self::SataDataIndexConfiguration => 0x12,
+            Self::AdvancedFeatures => 0x13,
+            Self::EnhancedAllocation => 0x14,
+            Self::FlatteningPortalBridge => 0x15,
+            Self::Unknown(unk) => *unk,
+        }
+    }
+}
+
+impl From<u8> for PciCapabilityId {
+    fn from(value: u8) -> Self {
+        Self::from_code(value)
+    }
+}
+
+impl From<PciCapabilityId> for u8 {
+    fn from(value: PciCapabilityId) -> Self {
+        value.as_code()
+    }
+}
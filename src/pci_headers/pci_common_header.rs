@@ -1,11 +1,114 @@
 use crate::PciInfoError;
 
-use super::pci_config_buffer::PciConfigBuffer;
+use super::pci_config_buffer::{PciConfigBuffer, PciConfigBufferMut};
+use super::PciConfigAccess;
+
+pci_register_bits! {
+    /// The Command register of the common header.
+    pub struct PciCommand(u16) {
+        /// The device responds to I/O space accesses.
+        io_space, set_io_space: 0 => "I/O";
+        /// The device responds to memory space accesses.
+        memory_space, set_memory_space: 1 => "Mem";
+        /// The device can act as a bus master.
+        bus_master, set_bus_master: 2 => "BusMaster";
+        /// The device monitors special cycle operations.
+        special_cycles, set_special_cycles: 3 => "SpecCycle";
+        /// The device can generate Memory Write and Invalidate commands.
+        memory_write_and_invalidate, set_memory_write_and_invalidate: 4 => "MemWINV";
+        /// The device snoops VGA palette writes.
+        vga_palette_snoop, set_vga_palette_snoop: 5 => "VGASnoop";
+        /// The device responds to parity errors.
+        parity_error_response, set_parity_error_response: 6 => "ParErr";
+        /// The SERR# driver is enabled.
+        serr_enable, set_serr_enable: 8 => "SERR";
+        /// The device can generate fast back-to-back transactions.
+        fast_back_to_back, set_fast_back_to_back: 9 => "FastB2B";
+        /// The assertion of INTx# signals is disabled.
+        interrupt_disable, set_interrupt_disable: 10 => "DisINTx";
+    }
+}
+
+/// The DEVSEL timing reported in the Status register of the common header.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PciDevselTiming {
+    /// Fast DEVSEL# timing.
+    Fast,
+    /// Medium DEVSEL# timing.
+    Medium,
+    /// Slow DEVSEL# timing.
+    Slow,
+    /// Reserved values.
+    Unknown(u8),
+}
+
+impl PciDevselTiming {
+    /// Create a `PciDevselTiming` from the encoded value of the DEVSEL
+    /// timing field of the Status register.
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            0 => Self::Fast,
+            1 => Self::Medium,
+            2 => Self::Slow,
+            unk => Self::Unknown(unk),
+        }
+    }
+
+    /// Gets the encoded value that this `PciDevselTiming` represents
+    pub fn as_code(&self) -> u8 {
+        match self {
+            Self::Fast => 0,
+            Self::Medium => 1,
+            Self::Slow => 2,
+            Self::Unknown(unk) => *unk,
+        }
+    }
+}
+
+pci_register_bits! {
+    /// The Status register of the common header.
+    pub struct PciStatus(u16) {
+        /// An INTx# interrupt is pending.
+        interrupt_status, set_interrupt_status: 3 => "INTx";
+        /// The device implements a capability list.
+        capabilities_list, set_capabilities_list: 4 => "Cap";
+        /// The device is capable of running at 66 MHz.
+        capable_66mhz, set_capable_66mhz: 5 => "66MHz";
+        /// The device can accept fast back-to-back transactions.
+        fast_back_to_back_capable, set_fast_back_to_back_capable: 7 => "FastB2B";
+        /// A data parity error was detected by the device as a bus master.
+        master_data_parity_error, set_master_data_parity_error: 8 => "ParErr";
+        /// The device terminated a transaction with a target abort.
+        signaled_target_abort, set_signaled_target_abort: 11 => ">TAbort";
+        /// A transaction of the device was terminated with a target abort.
+        received_target_abort, set_received_target_abort: 12 => "<TAbort";
+        /// A transaction of the device was terminated with a master abort.
+        received_master_abort, set_received_master_abort: 13 => "<MAbort";
+        /// The device asserted SERR#.
+        signaled_system_error, set_signaled_system_error: 14 => ">SERR";
+        /// The device detected a parity error.
+        detected_parity_error, set_detected_parity_error: 15 => "<PERR";
+    }
+}
+
+impl PciStatus {
+    /// The DEVSEL# timing of the device.
+    pub fn devsel_timing(&self) -> PciDevselTiming {
+        PciDevselTiming::from_code(self.field(9, 0x3) as u8)
+    }
+
+    /// Sets the DEVSEL# timing of the device.
+    pub fn set_devsel_timing(&mut self, timing: PciDevselTiming) {
+        self.set_field(9, 0x3, timing.as_code() as u16);
+    }
+}
 
 /// The header that is common for all PCI devices.
 ///
 /// All fields are the raw values of every non-reserved register part in
-/// the configuration space.
+/// the configuration space. Typed views of the Command and Status registers
+/// are available through [`PciCommonHeader::command_register`] and
+/// [`PciCommonHeader::status_register`].
 ///
 /// The format of the header in PCI configuration space is the following.
 ///
@@ -19,7 +122,7 @@ use super::pci_config_buffer::PciConfigBuffer;
 /// |    0x3   |   0xC   | BIST        | Header type  | Latency Timer  | Cache Line Size|
 /// +----------+---------+-------------+--------------+----------------+----------------+
 /// ```
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct PciCommonHeader {
     pub device_id: u16,
     pub vendor_id: u16,
@@ -48,6 +151,56 @@ impl PciCommonHeader {
         Self::with_pci_cfg(&PciConfigBuffer::new(bytes, 0))
     }
 
+    /// Reads the common header from the configuration space accessed through
+    /// `config`, reading only its first 16 bytes.
+    pub fn with_access<A: PciConfigAccess + ?Sized>(config: &A) -> Result<Self, PciInfoError> {
+        let mut bytes = [0u8; Self::COMMON_HEADER_LEN];
+        config.read_bytes(0, &mut bytes)?;
+        Self::with_bytes(&bytes)
+    }
+
+    /// Writes the common header to the first 16 bytes of a slice, at the
+    /// same offsets used by [`PciCommonHeader::with_bytes`].
+    pub fn write_bytes(&self, bytes: &mut [u8]) -> Result<(), PciInfoError> {
+        let mut pci_cfg = PciConfigBufferMut::new(bytes, 0);
+        pci_cfg.assert_registers_available(0, 3)?;
+
+        pci_cfg.write_u16_lo(0, self.vendor_id);
+        pci_cfg.write_u16_hi(0, self.device_id);
+        pci_cfg.write_u16_lo(1, self.command);
+        pci_cfg.write_u16_hi(1, self.status);
+        pci_cfg.write_u8(2, 0, self.revision_id);
+        pci_cfg.write_u8(2, 1, self.prog_iface_code);
+        pci_cfg.write_u8(2, 2, self.subclass_code);
+        pci_cfg.write_u8(2, 3, self.class_code);
+        pci_cfg.write_u8(3, 0, self.cache_line_size);
+        pci_cfg.write_u8(3, 1, self.latency_timer);
+        pci_cfg.write_u8(3, 2, self.header_type);
+        pci_cfg.write_u8(3, 3, self.bist);
+
+        Ok(())
+    }
+
+    /// Returns a typed view of the Command register.
+    pub fn command_register(&self) -> PciCommand {
+        PciCommand(self.command)
+    }
+
+    /// Sets the Command register from a typed value.
+    pub fn set_command_register(&mut self, command: PciCommand) {
+        self.command = command.bits();
+    }
+
+    /// Returns a typed view of the Status register.
+    pub fn status_register(&self) -> PciStatus {
+        PciStatus(self.status)
+    }
+
+    /// Sets the Status register from a typed value.
+    pub fn set_status_register(&mut self, status: PciStatus) {
+        self.status = status.bits();
+    }
+
     fn with_pci_cfg(pci_cfg: &PciConfigBuffer<'_>) -> Result<Self, PciInfoError> {
         pci_cfg.assert_registers_available(0, 3)?;
 
@@ -67,3 +220,41 @@ impl PciCommonHeader {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_command_and_status() {
+        let mut bytes = [0u8; 16];
+        bytes[0x04..0x06].copy_from_slice(&0x0407u16.to_le_bytes());
+        bytes[0x06..0x08].copy_from_slice(&0x2210u16.to_le_bytes());
+
+        let mut header = PciCommonHeader::with_bytes(&bytes).unwrap();
+        let command = header.command_register();
+        let status = header.status_register();
+
+        assert!(command.io_space() && command.memory_space() && command.bus_master());
+        assert!(command.interrupt_disable() && !command.serr_enable());
+        assert!(status.capabilities_list() && status.received_master_abort());
+        assert_eq!(status.devsel_timing(), PciDevselTiming::Medium);
+        assert_eq!(
+            format!("{command:?}"),
+            "PciCommand(0x407: I/O | Mem | BusMaster | DisINTx)"
+        );
+
+        let mut command = PciCommand::default();
+        command.set_memory_space(true);
+        command.set_bus_master(true);
+        header.set_command_register(command);
+
+        let mut status = header.status_register();
+        status.set_received_master_abort(false);
+        status.set_devsel_timing(PciDevselTiming::Slow);
+        header.set_status_register(status);
+
+        assert_eq!(header.command, 0x0006);
+        assert_eq!(header.status, 0x0410);
+    }
+}
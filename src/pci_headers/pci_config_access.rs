@@ -0,0 +1,426 @@
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::PciInfoError;
+
+use super::pci_config_buffer::{read_u16_at, read_u32_at, read_u8_at};
+use super::{PciConfigSpace, PciExtendedCapabilityIterator};
+
+/// Offset-based read access to the configuration space of a PCI device.
+///
+/// Headers, capability iterators and capability decoders read through this
+/// trait, so that the configuration space does not have to be loaded in
+/// memory in advance: when decoding directly from a lazy backend such as
+/// [`PciConfigFile`] only the registers that are actually decoded are read.
+/// Enumerators read the whole configuration space by default, as they keep
+/// a copy of it in each [`PciDevice`](crate::PciDevice); on Linux,
+/// `LinuxProcFsPciEnumerator::with_lazy_config_space` reads only the headers
+/// and leaves the rest to be read on demand.
+///
+/// All offsets are relative to the beginning of the common header, and
+/// multi-byte values are little endian. Reads at or beyond [`size`] fail
+/// with [`PciInfoError::UnexpectedEof`].
+///
+/// [`size`]: PciConfigAccess::size
+pub trait PciConfigAccess {
+    /// The number of bytes of the configuration space that can be read.
+    fn size(&self) -> usize;
+
+    /// Reads a byte at an arbitrary offset.
+    fn read_u8(&self, offset: usize) -> Result<u8, PciInfoError>;
+
+    /// Reads a little endian `u16` at an arbitrary offset.
+    fn read_u16(&self, offset: usize) -> Result<u16, PciInfoError> {
+        let mut bytes = [0u8; 2];
+        self.read_bytes(offset, &mut bytes)?;
+        Ok(u16::from_le_bytes(bytes))
+    }
+
+    /// Reads a little endian `u32` at an arbitrary offset.
+    fn read_u32(&self, offset: usize) -> Result<u32, PciInfoError> {
+        let mut bytes = [0u8; 4];
+        self.read_bytes(offset, &mut bytes)?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    /// Fills `buffer` with the bytes starting at an arbitrary offset. The
+    /// default implementation reads one byte at a time.
+    fn read_bytes(&self, offset: usize, buffer: &mut [u8]) -> Result<(), PciInfoError> {
+        for (i, byte) in buffer.iter_mut().enumerate() {
+            let offset = offset.checked_add(i).ok_or(PciInfoError::UnexpectedEof)?;
+            *byte = self.read_u8(offset)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl PciConfigAccess for [u8] {
+    fn size(&self) -> usize {
+        self.len()
+    }
+
+    fn read_u8(&self, offset: usize) -> Result<u8, PciInfoError> {
+        read_u8_at(self, offset)
+    }
+
+    fn read_u16(&self, offset: usize) -> Result<u16, PciInfoError> {
+        read_u16_at(self, offset)
+    }
+
+    fn read_u32(&self, offset: usize) -> Result<u32, PciInfoError> {
+        read_u32_at(self, offset)
+    }
+
+    fn read_bytes(&self, offset: usize, buffer: &mut [u8]) -> Result<(), PciInfoError> {
+        let end = offset
+            .checked_add(buffer.len())
+            .ok_or(PciInfoError::UnexpectedEof)?;
+
+        match self.get(offset..end) {
+            Some(bytes) => {
+                buffer.copy_from_slice(bytes);
+                Ok(())
+            }
+            None => Err(PciInfoError::UnexpectedEof),
+        }
+    }
+}
+
+impl PciConfigAccess for PciConfigSpace {
+    fn size(&self) -> usize {
+        self.len()
+    }
+
+    fn read_u8(&self, offset: usize) -> Result<u8, PciInfoError> {
+        self.as_bytes().read_u8(offset)
+    }
+
+    fn read_u16(&self, offset: usize) -> Result<u16, PciInfoError> {
+        self.as_bytes().read_u16(offset)
+    }
+
+    fn read_u32(&self, offset: usize) -> Result<u32, PciInfoError> {
+        self.as_bytes().read_u32(offset)
+    }
+
+    fn read_bytes(&self, offset: usize, buffer: &mut [u8]) -> Result<(), PciInfoError> {
+        self.as_bytes().read_bytes(offset, buffer)
+    }
+}
+
+/// Lazy access to a configuration space exposed as a seekable file, such as
+/// `/proc/bus/pci/<bus>/<device>.<function>` or
+/// `/sys/bus/pci/devices/<location>/config` on Linux.
+///
+/// Every read seeks to the requested offset and reads only the requested
+/// bytes. The reader is kept in a `RefCell`, so a `PciConfigFile` can be
+/// shared by reference but is not `Sync`.
+///
+/// Note that the size of the file usually reflects the size of the whole
+/// configuration space, even when the operating system only allows
+/// unprivileged users to read its first 64 bytes; reads of the bytes that
+/// are not accessible fail with [`PciInfoError::UnexpectedEof`].
+pub struct PciConfigFile<F> {
+    reader: RefCell<F>,
+    size: usize,
+}
+
+impl PciConfigFile<File> {
+    /// Opens the configuration space file at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, PciInfoError> {
+        Self::with_reader(File::open(path)?)
+    }
+}
+
+impl<F: Read + Seek> PciConfigFile<F> {
+    /// Creates a configuration space backed by `reader`, using the length of
+    /// the stream (up to 4096 bytes) as its size.
+    pub fn with_reader(mut reader: F) -> Result<Self, PciInfoError> {
+        let len = reader.seek(SeekFrom::End(0))?;
+        let size = len.min(PciExtendedCapabilityIterator::EXTENDED_CONFIG_SPACE_LEN as u64);
+
+        Ok(Self::with_reader_and_size(reader, size as usize))
+    }
+
+    /// Creates a configuration space backed by `reader`, with an explicit
+    /// size. This is useful for streams that do not report a meaningful
+    /// length.
+    pub fn with_reader_and_size(reader: F, size: usize) -> Self {
+        Self {
+            reader: RefCell::new(reader),
+            size,
+        }
+    }
+
+    /// Consumes this configuration space, returning the underlying reader.
+    pub fn into_inner(self) -> F {
+        self.reader.into_inner()
+    }
+}
+
+impl<F: Read + Seek> PciConfigAccess for PciConfigFile<F> {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn read_u8(&self, offset: usize) -> Result<u8, PciInfoError> {
+        let mut bytes = [0u8; 1];
+        self.read_bytes(offset, &mut bytes)?;
+        Ok(bytes[0])
+    }
+
+    fn read_bytes(&self, offset: usize, buffer: &mut [u8]) -> Result<(), PciInfoError> {
+        if offset
+            .checked_add(buffer.len())
+            .map_or(true, |end| end > self.size)
+        {
+            return Err(PciInfoError::UnexpectedEof);
+        }
+
+        let mut reader = self.reader.borrow_mut();
+        reader.seek(SeekFrom::Start(offset as u64))?;
+
+        match reader.read_exact(buffer) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                Err(PciInfoError::UnexpectedEof)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl<F> std::fmt::Debug for PciConfigFile<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[PciConfigFile of {} bytes]", self.size)
+    }
+}
+
+/// An in-memory configuration space which records the reads performed on
+/// it, meant for testing code built on [`PciConfigAccess`].
+///
+/// # Example
+/// ```rust
+/// use pci_info::pci_headers::{PciCommonHeader, PciConfigMock};
+///
+/// # fn main() -> Result<(), pci_info::PciInfoError> {
+/// let mut config = PciConfigMock::new(256);
+/// config.set_u16(0x00, 0x8086);
+/// config.set_u16(0x02, 0x1234);
+///
+/// let header = PciCommonHeader::with_access(&config)?;
+///
+/// assert_eq!(header.vendor_id, 0x8086);
+/// assert_eq!(config.reads(), vec![(0x00, 16)]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PciConfigMock {
+    bytes: Vec<u8>,
+    reads: RefCell<Vec<(usize, usize)>>,
+}
+
+impl PciConfigMock {
+    /// Creates a configuration space of `size` bytes, all set to zero.
+    pub fn new(size: usize) -> Self {
+        Self::with_bytes(vec![0; size])
+    }
+
+    /// Creates a configuration space with the specified content.
+    pub fn with_bytes(bytes: Vec<u8>) -> Self {
+        Self {
+            bytes,
+            reads: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Returns the content of the configuration space.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Sets the byte at `offset`. Panics if `offset` is out of range.
+    pub fn set_u8(&mut self, offset: usize, value: u8) {
+        self.bytes[offset] = value;
+    }
+
+    /// Sets the `u16` at `offset`. Panics if `offset` is out of range.
+    pub fn set_u16(&mut self, offset: usize, value: u16) {
+        self.bytes[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Sets the `u32` at `offset`. Panics if `offset` is out of range.
+    pub fn set_u32(&mut self, offset: usize, value: u32) {
+        self.bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Returns the offset and length of every read performed so far, in
+    /// order, including reads that failed.
+    pub fn reads(&self) -> Vec<(usize, usize)> {
+        self.reads.borrow().clone()
+    }
+
+    /// Returns the number of bytes read so far.
+    pub fn bytes_read(&self) -> usize {
+        self.reads.borrow().iter().map(|(_, len)| len).sum()
+    }
+
+    /// Forgets the reads performed so far.
+    pub fn clear_reads(&self) {
+        self.reads.borrow_mut().clear();
+    }
+
+    fn record(&self, offset: usize, len: usize) {
+        self.reads.borrow_mut().push((offset, len));
+    }
+}
+
+impl PciConfigAccess for PciConfigMock {
+    fn size(&self) -> usize {
+        self.bytes.len()
+    }
+
+    fn read_u8(&self, offset: usize) -> Result<u8, PciInfoError> {
+        self.record(offset, 1);
+        self.bytes.read_u8(offset)
+    }
+
+    fn read_u16(&self, offset: usize) -> Result<u16, PciInfoError> {
+        self.record(offset, 2);
+        self.bytes.read_u16(offset)
+    }
+
+    fn read_u32(&self, offset: usize) -> Result<u32, PciInfoError> {
+        self.record(offset, 4);
+        self.bytes.read_u32(offset)
+    }
+
+    fn read_bytes(&self, offset: usize, buffer: &mut [u8]) -> Result<(), PciInfoError> {
+        self.record(offset, buffer.len());
+        self.bytes.as_slice().read_bytes(offset, buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_headers::{
+        PciCapabilityId, PciCapabilityIterator, PciCommonHeader, PciConfigSpaceBuilder,
+        PciExtendedCapabilityId, PciExtendedCapabilityIterator, PciPowerManagementCapability,
+        PciSpecializedHeader,
+    };
+
+    fn sample_config() -> Vec<u8> {
+        PciConfigSpaceBuilder::new(0x8086, 0x1234)
+            .with_power_management(&PciPowerManagementCapability::default())
+            .with_capability(PciCapabilityId::VendorSpecific, &[0x04, 0x00])
+            .with_extended_capability(PciExtendedCapabilityId::DeviceSerialNumber, 1, &[0; 8])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn capabilities_are_read_lazily() {
+        let config = PciConfigMock::with_bytes(sample_config());
+
+        let cap = PciCapabilityIterator::with_access(&config)
+            .unwrap()
+            .find_capability(PciCapabilityId::PowerManagement)
+            .unwrap()
+            .unwrap();
+        PciPowerManagementCapability::with_capability(&cap).unwrap();
+
+        assert!(config.bytes_read() < 32);
+        assert!(config.reads().iter().all(|(offset, _)| *offset < 0x100));
+
+        config.clear_reads();
+
+        let dsn = PciExtendedCapabilityIterator::with_access(&config)
+            .find_capability(PciExtendedCapabilityId::DeviceSerialNumber)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(dsn.offset(), 0x100);
+        assert_eq!(config.reads(), vec![(0x100, 4), (0x100, 4)]);
+        assert!(dsn.read_u32(0x04).is_ok());
+        assert!(dsn.read_u32(0xF00).is_err());
+    }
+
+    #[test]
+    fn offsets_near_usize_max_are_out_of_range() {
+        let bytes = sample_config();
+        let bytes = bytes.as_slice();
+        let eof = |res: Result<u32, PciInfoError>| matches!(res, Err(PciInfoError::UnexpectedEof));
+
+        assert!(eof(bytes.read_u32(usize::MAX)));
+        assert!(eof(bytes.read_u32(usize::MAX - 2)));
+        assert!(eof(bytes.read_u16(usize::MAX).map(u32::from)));
+        assert!(eof(bytes.read_u8(usize::MAX).map(u32::from)));
+
+        let cap = PciCapabilityIterator::with_bytes(bytes)
+            .unwrap()
+            .find_capability(PciCapabilityId::PowerManagement)
+            .unwrap()
+            .unwrap();
+
+        assert!(eof(cap.read_u32(usize::MAX - 2)));
+        assert!(eof(cap.read_u16(usize::MAX).map(u32::from)));
+
+        let dsn = PciExtendedCapabilityIterator::with_bytes(bytes)
+            .find_capability(PciExtendedCapabilityId::DeviceSerialNumber)
+            .unwrap()
+            .unwrap();
+
+        assert!(eof(dsn.read_u32(usize::MAX - 0x100)));
+        assert!(eof(dsn.read_u32(usize::MAX)));
+    }
+
+    #[test]
+    fn file_access_matches_slice_access() {
+        let bytes = sample_config();
+        let file = PciConfigFile::with_reader(std::io::Cursor::new(bytes.clone())).unwrap();
+
+        assert_eq!(file.size(), bytes.len());
+        assert_eq!(
+            file.read_u32(0x00).unwrap(),
+            bytes.as_slice().read_u32(0x00).unwrap()
+        );
+        assert!(matches!(
+            file.read_u16(bytes.len() - 1),
+            Err(PciInfoError::UnexpectedEof)
+        ));
+        assert!(matches!(
+            file.read_u32(usize::MAX - 1),
+            Err(PciInfoError::UnexpectedEof)
+        ));
+        assert!(matches!(
+            bytes.as_slice().read_bytes(usize::MAX, &mut [0u8; 2]),
+            Err(PciInfoError::UnexpectedEof)
+        ));
+
+        let header = PciCommonHeader::with_access(&file).unwrap();
+        assert_eq!(header.device_id, 0x1234);
+        assert!(matches!(
+            PciSpecializedHeader::with_access(header.header_type, &file),
+            Ok(PciSpecializedHeader::GenericDevice(_))
+        ));
+
+        let from_file = PciCapabilityIterator::with_access(&file)
+            .unwrap()
+            .map(|c| c.map(|c| (c.offset(), c.id())))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let from_slice = PciCapabilityIterator::with_bytes(&bytes)
+            .unwrap()
+            .map(|c| c.map(|c| (c.offset(), c.id())))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(from_file.len(), 2);
+        assert_eq!(from_file, from_slice);
+    }
+}
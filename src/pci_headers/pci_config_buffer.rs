@@ -73,6 +73,103 @@ impl<'a> PciConfigBuffer<'a> {
     }
 
     pub fn last_register(&self) -> usize {
-        self.offset + self.bytes.len() / Self::REGISTER_SIZE
+        (self.offset + self.bytes.len()) / Self::REGISTER_SIZE
     }
 }
+
+/// The writable counterpart of `PciConfigBuffer`, used to serialize
+/// headers with the same register layout used to parse them.
+pub(super) struct PciConfigBufferMut<'a> {
+    bytes: &'a mut [u8],
+    offset: usize,
+}
+
+impl<'a> PciConfigBufferMut<'a> {
+    pub fn new(bytes: &'a mut [u8], offset: usize) -> Self {
+        assert!(offset % PciConfigBuffer::REGISTER_SIZE == 0);
+
+        Self { bytes, offset }
+    }
+
+    #[inline(always)]
+    fn calc_base(&self, register: usize) -> usize {
+        register * PciConfigBuffer::REGISTER_SIZE - self.offset
+    }
+
+    #[inline(always)]
+    pub fn write_u32(&mut self, register: usize, value: u32) {
+        let base = self.calc_base(register);
+        self.bytes[base..base + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    #[inline(always)]
+    pub fn write_u16_lo(&mut self, register: usize, value: u16) {
+        let base = self.calc_base(register);
+        self.bytes[base..base + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    #[inline(always)]
+    pub fn write_u16_hi(&mut self, register: usize, value: u16) {
+        let base = self.calc_base(register) + 2;
+        self.bytes[base..base + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    #[inline(always)]
+    pub fn write_u8(&mut self, register: usize, offset_in_register: usize, value: u8) {
+        assert!(offset_in_register <= 3);
+        let base = self.calc_base(register);
+        self.bytes[base + offset_in_register] = value;
+    }
+
+    pub fn assert_registers_available(
+        &self,
+        first_register: usize,
+        last_register_including: usize,
+    ) -> Result<(), PciInfoError> {
+        PciConfigBuffer::new(self.bytes, self.offset)
+            .assert_registers_available(first_register, last_register_including)
+    }
+}
+
+/// Reads a byte at an arbitrary offset of a buffer, failing with
+/// `PciInfoError::UnexpectedEof` if the buffer is too short.
+pub(super) fn read_u8_at(bytes: &[u8], offset: usize) -> Result<u8, PciInfoError> {
+    bytes
+        .get(offset)
+        .copied()
+        .ok_or(PciInfoError::UnexpectedEof)
+}
+
+/// Reads a little endian `u16` at an arbitrary offset of a buffer,
+/// failing with `PciInfoError::UnexpectedEof` if the buffer is too short.
+pub(super) fn read_u16_at(bytes: &[u8], offset: usize) -> Result<u16, PciInfoError> {
+    let end = offset.checked_add(2).ok_or(PciInfoError::UnexpectedEof)?;
+
+    match bytes.get(offset..end) {
+        Some(b) => Ok(u16::from_le_bytes([b[0], b[1]])),
+        None => Err(PciInfoError::UnexpectedEof),
+    }
+}
+
+/// Reads a little endian `u32` at an arbitrary offset of a buffer,
+/// failing with `PciInfoError::UnexpectedEof` if the buffer is too short.
+pub(super) fn read_u32_at(bytes: &[u8], offset: usize) -> Result<u32, PciInfoError> {
+    let end = offset.checked_add(4).ok_or(PciInfoError::UnexpectedEof)?;
+
+    match bytes.get(offset..end) {
+        Some(b) => Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+        None => Err(PciInfoError::UnexpectedEof),
+    }
+}
+
+/// Writes a little endian `u16` at an arbitrary offset of a buffer.
+/// Panics if the buffer is too short.
+pub(super) fn write_u16_at(bytes: &mut [u8], offset: usize, value: u16) {
+    bytes[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+/// Writes a little endian `u32` at an arbitrary offset of a buffer.
+/// Panics if the buffer is too short.
+pub(super) fn write_u32_at(bytes: &mut [u8], offset: usize, value: u32) {
+    bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
@@ -0,0 +1,681 @@
+use super::{
+    PciAcsControl, PciAerCapabilitiesControl, PciAerCorrectableErrors, PciAerUncorrectableErrors,
+    PciBridgeControl, PciCapabilityId, PciCapabilityIterator, PciCardbusBridgeControl, PciCommand,
+    PciConfigAccess, PciDevselTiming, PciExpressDeviceControl, PciExpressDeviceStatus,
+    PciExpressLinkControl, PciExpressLinkSpeed, PciExpressLinkStatus, PciExpressLinkWidth,
+    PciExpressSlotControl, PciExpressSlotStatus, PciExtendedCapabilityId,
+    PciExtendedCapabilityIterator, PciGenericDeviceHeader, PciMsiMessageControl,
+    PciMsixMessageControl, PciPowerManagementControlStatus, PciPowerState, PciSecondaryStatus,
+    PciSrIovControl, PciSrIovStatus, PciStatus, PciToCardbusBridgeHeader, PciToPciBridgeHeader,
+};
+
+/// A register (or a field of a register) whose value differs between two
+/// snapshots of a configuration space, as reported by [`diff_config_spaces`].
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PciRegisterChange {
+    /// The offset of the register in the configuration space.
+    pub offset: usize,
+    /// The width of the register, in bytes.
+    pub width: usize,
+    /// The symbolic name of the register, followed by the name of the field
+    /// that changed if known (e.g. `Command.BusMaster`), or `None` if the
+    /// register is not known.
+    pub name: Option<String>,
+    /// The value before the change, formatted according to the field.
+    pub before: String,
+    /// The value after the change, formatted according to the field.
+    pub after: String,
+}
+
+impl std::fmt::Display for PciRegisterChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{name}")?,
+            None => write!(f, "0x{:03X}[{}]", self.offset, self.width)?,
+        }
+
+        write!(f, " {} -> {}", self.before, self.after)
+    }
+}
+
+/// Compares two snapshots of the configuration space of the same device,
+/// such as the ones taken before and after a driver is loaded, and reports
+/// the registers that changed.
+///
+/// Registers of the headers and of the most common capabilities are
+/// reported with their symbolic names, one change per flag or field (e.g.
+/// `Command.BusMaster 0 -> 1` or `PCIe LinkStatus.Speed 8GT/s -> 2.5GT/s`).
+/// Other changes are reported as raw dwords. The layout of the configuration
+/// space (header type and capability offsets) is taken from `before`, and
+/// only the bytes available in both snapshots are compared.
+///
+/// # Example
+/// ```rust
+/// use pci_info::pci_headers::{diff_config_spaces, PciConfigSpaceBuilder};
+///
+/// # fn main() -> Result<(), pci_info::PciInfoError> {
+/// let before = PciConfigSpaceBuilder::new(0x8086, 0x1234).build()?;
+/// let mut after = before.clone();
+/// after[0x04] |= 0x04; // Bus master enable
+/// after[0x3C] = 0x0B; // Interrupt line
+///
+/// let changes = diff_config_spaces(before.as_slice(), after.as_slice())
+///     .iter()
+///     .map(|c| c.to_string())
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(changes, ["Command.BusMaster 0 -> 1", "InterruptLine 0x00 -> 0x0B"]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn diff_config_spaces<A, B>(before: &A, after: &B) -> Vec<PciRegisterChange>
+where
+    A: PciConfigAccess + ?Sized,
+    B: PciConfigAccess + ?Sized,
+{
+    let len = before.size().min(after.size());
+    let mut old = vec![0u8; len];
+    let mut new = vec![0u8; len];
+
+    if before.read_bytes(0, &mut old).is_err() || after.read_bytes(0, &mut new).is_err() {
+        return Vec::new();
+    }
+
+    let mut registers = header_registers(&old);
+    registers.extend(capability_registers(&old));
+    registers.retain(|r| r.offset + r.width <= len);
+    registers.sort_by_key(|r| r.offset);
+
+    let mut known = vec![false; len];
+    let mut changes = Vec::new();
+
+    for register in registers.iter() {
+        known[register.offset..register.offset + register.width].fill(true);
+        register.diff(&old, &new, &mut changes);
+    }
+
+    for dword in (0..len / 4).map(|i| i * 4) {
+        let unknown_change = (dword..dword + 4).any(|i| !known[i] && old[i] != new[i]);
+
+        if unknown_change {
+            changes.push(PciRegisterChange {
+                offset: dword,
+                width: 4,
+                name: None,
+                before: format!("0x{:08X}", read(&old, dword, 4)),
+                after: format!("0x{:08X}", read(&new, dword, 4)),
+            });
+        }
+    }
+
+    changes.sort_by_key(|c| c.offset);
+    changes
+}
+
+/// A multi-bit field of a register.
+struct Field {
+    name: &'static str,
+    shift: u32,
+    mask: u32,
+    format: fn(u32) -> String,
+}
+
+/// A register with a symbolic name, and optionally the single-bit flags
+/// and multi-bit fields to report separately.
+struct Register {
+    offset: usize,
+    width: usize,
+    name: String,
+    flags: Vec<(&'static str, u32)>,
+    fields: &'static [Field],
+}
+
+impl Register {
+    fn plain(offset: usize, width: usize, name: impl Into<String>) -> Self {
+        Self {
+            offset,
+            width,
+            name: name.into(),
+            flags: Vec::new(),
+            fields: &[],
+        }
+    }
+
+    fn with_flags<T: Copy + Into<u32>>(
+        offset: usize,
+        width: usize,
+        name: impl Into<String>,
+        flags: &[(&'static str, T)],
+        fields: &'static [Field],
+    ) -> Self {
+        Self {
+            offset,
+            width,
+            name: name.into(),
+            flags: flags.iter().map(|(l, m)| (*l, (*m).into())).collect(),
+            fields,
+        }
+    }
+
+    fn diff(&self, old: &[u8], new: &[u8], changes: &mut Vec<PciRegisterChange>) {
+        let old = read(old, self.offset, self.width);
+        let new = read(new, self.offset, self.width);
+
+        if old == new {
+            return;
+        }
+
+        let mut change = |name: String, before: String, after: String| {
+            changes.push(PciRegisterChange {
+                offset: self.offset,
+                width: self.width,
+                name: Some(name),
+                before,
+                after,
+            })
+        };
+
+        if self.flags.is_empty() && self.fields.is_empty() {
+            let digits = self.width * 2;
+            change(
+                self.name.clone(),
+                format!("0x{old:0digits$X}"),
+                format!("0x{new:0digits$X}"),
+            );
+            return;
+        }
+
+        let mut reported = 0u32;
+
+        for (label, mask) in self.flags.iter() {
+            reported |= mask;
+
+            if (old ^ new) & mask != 0 {
+                change(
+                    format!("{}.{label}", self.name),
+                    ((old & mask != 0) as u8).to_string(),
+                    ((new & mask != 0) as u8).to_string(),
+                );
+            }
+        }
+
+        for field in self.fields.iter() {
+            let old = (old >> field.shift) & field.mask;
+            let new = (new >> field.shift) & field.mask;
+            reported |= field.mask << field.shift;
+
+            if old != new {
+                change(
+                    format!("{}.{}", self.name, field.name),
+                    (field.format)(old),
+                    (field.format)(new),
+                );
+            }
+        }
+
+        if (old ^ new) & !reported != 0 {
+            let digits = self.width * 2;
+            change(
+                self.name.clone(),
+                format!("0x{old:0digits$X}"),
+                format!("0x{new:0digits$X}"),
+            );
+        }
+    }
+}
+
+fn read(bytes: &[u8], offset: usize, width: usize) -> u32 {
+    bytes[offset..offset + width]
+        .iter()
+        .rev()
+        .fold(0, |acc, b| acc << 8 | *b as u32)
+}
+
+fn format_decimal(value: u32) -> String {
+    value.to_string()
+}
+
+fn format_devsel(value: u32) -> String {
+    format!("{:?}", PciDevselTiming::from_code(value as u8))
+}
+
+fn format_power_state(value: u32) -> String {
+    format!("{:?}", PciPowerState::from_code(value as u8))
+}
+
+fn format_size(value: u32) -> String {
+    match value {
+        0..=5 => format!("{}B", 128 << value),
+        reserved => format!("unknown size ({reserved})"),
+    }
+}
+
+fn format_link_speed(value: u32) -> String {
+    PciExpressLinkSpeed::from_code(value as u8).to_string()
+}
+
+fn format_link_width(value: u32) -> String {
+    PciExpressLinkWidth::from_code(value as u8).to_string()
+}
+
+const DEVSEL_FIELDS: &[Field] = &[Field {
+    name: "DEVSEL",
+    shift: 9,
+    mask: 0x3,
+    format: format_devsel,
+}];
+
+const PMCSR_FIELDS: &[Field] = &[
+    Field {
+        name: "PowerState",
+        shift: 0,
+        mask: 0x3,
+        format: format_power_state,
+    },
+    Field {
+        name: "DataSelect",
+        shift: 9,
+        mask: 0xF,
+        format: format_decimal,
+    },
+    Field {
+        name: "DataScale",
+        shift: 13,
+        mask: 0x3,
+        format: format_decimal,
+    },
+];
+
+const MSI_CONTROL_FIELDS: &[Field] = &[Field {
+    name: "MultipleMessageEnable",
+    shift: 4,
+    mask: 0x7,
+    format: format_decimal,
+}];
+
+const DEVICE_CONTROL_FIELDS: &[Field] = &[
+    Field {
+        name: "MaxPayload",
+        shift: 5,
+        mask: 0x7,
+        format: format_size,
+    },
+    Field {
+        name: "MaxReadReq",
+        shift: 12,
+        mask: 0x7,
+        format: format_size,
+    },
+];
+
+const LINK_CONTROL_FIELDS: &[Field] = &[Field {
+    name: "ASPM",
+    shift: 0,
+    mask: 0x3,
+    format: format_decimal,
+}];
+
+const LINK_STATUS_FIELDS: &[Field] = &[
+    Field {
+        name: "Speed",
+        shift: 0,
+        mask: 0xF,
+        format: format_link_speed,
+    },
+    Field {
+        name: "Width",
+        shift: 4,
+        mask: 0x3F,
+        format: format_link_width,
+    },
+];
+
+const SLOT_CONTROL_FIELDS: &[Field] = &[
+    Field {
+        name: "AttnInd",
+        shift: 6,
+        mask: 0x3,
+        format: format_decimal,
+    },
+    Field {
+        name: "PwrInd",
+        shift: 8,
+        mask: 0x3,
+        format: format_decimal,
+    },
+];
+
+const AER_CONTROL_FIELDS: &[Field] = &[Field {
+    name: "FirstErrPtr",
+    shift: 0,
+    mask: 0x1F,
+    format: format_decimal,
+}];
+
+fn header_registers(bytes: &[u8]) -> Vec<Register> {
+    let mut registers = vec![
+        Register::plain(0x00, 2, "VendorID"),
+        Register::plain(0x02, 2, "DeviceID"),
+        Register::with_flags(0x04, 2, "Command", PciCommand::FLAGS, &[]),
+        Register::with_flags(0x06, 2, "Status", PciStatus::FLAGS, DEVSEL_FIELDS),
+        Register::plain(0x08, 1, "Revision"),
+        Register::plain(0x09, 1, "ProgIf"),
+        Register::plain(0x0A, 1, "Subclass"),
+        Register::plain(0x0B, 1, "Class"),
+        Register::plain(0x0C, 1, "CacheLineSize"),
+        Register::plain(0x0D, 1, "LatencyTimer"),
+        Register::plain(0x0E, 1, "HeaderType"),
+        Register::plain(0x0F, 1, "BIST"),
+    ];
+
+    let Some(header_type) = bytes.get(0x0E) else {
+        return registers;
+    };
+
+    match header_type & 0x7F {
+        PciGenericDeviceHeader::ID => {
+            registers.extend((0..6).map(|i| Register::plain(0x10 + i * 4, 4, format!("BAR{i}"))));
+            registers.extend([
+                Register::plain(0x28, 4, "CardbusCIS"),
+                Register::plain(0x2C, 2, "SubsystemVendorID"),
+                Register::plain(0x2E, 2, "SubsystemID"),
+                Register::plain(0x30, 4, "ExpansionROM"),
+                Register::plain(0x34, 1, "CapabilitiesPointer"),
+                Register::plain(0x3C, 1, "InterruptLine"),
+                Register::plain(0x3D, 1, "InterruptPin"),
+                Register::plain(0x3E, 1, "MinGnt"),
+                Register::plain(0x3F, 1, "MaxLat"),
+            ]);
+        }
+        PciToPciBridgeHeader::ID => {
+            registers.extend([
+                Register::plain(0x10, 4, "BAR0"),
+                Register::plain(0x14, 4, "BAR1"),
+                Register::plain(0x18, 1, "PrimaryBus"),
+                Register::plain(0x19, 1, "SecondaryBus"),
+                Register::plain(0x1A, 1, "SubordinateBus"),
+                Register::plain(0x1B, 1, "SecondaryLatencyTimer"),
+                Register::plain(0x1C, 1, "IOBase"),
+                Register::plain(0x1D, 1, "IOLimit"),
+                Register::with_flags(
+                    0x1E,
+                    2,
+                    "SecondaryStatus",
+                    PciSecondaryStatus::FLAGS,
+                    DEVSEL_FIELDS,
+                ),
+                Register::plain(0x20, 2, "MemoryBase"),
+                Register::plain(0x22, 2, "MemoryLimit"),
+                Register::plain(0x24, 2, "PrefetchableMemoryBase"),
+                Register::plain(0x26, 2, "PrefetchableMemoryLimit"),
+                Register::plain(0x28, 4, "PrefetchableBaseUpper32"),
+                Register::plain(0x2C, 4, "PrefetchableLimitUpper32"),
+                Register::plain(0x30, 2, "IOBaseUpper16"),
+                Register::plain(0x32, 2, "IOLimitUpper16"),
+                Register::plain(0x34, 1, "CapabilitiesPointer"),
+                Register::plain(0x38, 4, "ExpansionROM"),
+                Register::plain(0x3C, 1, "InterruptLine"),
+                Register::plain(0x3D, 1, "InterruptPin"),
+                Register::with_flags(0x3E, 2, "BridgeControl", PciBridgeControl::FLAGS, &[]),
+            ]);
+        }
+        PciToCardbusBridgeHeader::ID => {
+            registers.extend([
+                Register::plain(0x10, 4, "SocketBase"),
+                Register::plain(0x14, 1, "CapabilitiesPointer"),
+                Register::with_flags(
+                    0x16,
+                    2,
+                    "SecondaryStatus",
+                    PciSecondaryStatus::FLAGS,
+                    DEVSEL_FIELDS,
+                ),
+                Register::plain(0x18, 1, "PrimaryBus"),
+                Register::plain(0x19, 1, "CardbusBus"),
+                Register::plain(0x1A, 1, "SubordinateBus"),
+                Register::plain(0x1B, 1, "CardbusLatencyTimer"),
+                Register::plain(0x3C, 1, "InterruptLine"),
+                Register::plain(0x3D, 1, "InterruptPin"),
+                Register::with_flags(
+                    0x3E,
+                    2,
+                    "BridgeControl",
+                    PciCardbusBridgeControl::FLAGS,
+                    &[],
+                ),
+            ]);
+        }
+        _ => (),
+    }
+
+    registers
+}
+
+fn capability_registers(bytes: &[u8]) -> Vec<Register> {
+    let mut registers = Vec::new();
+
+    for cap in PciCapabilityIterator::with_bytes(bytes)
+        .into_iter()
+        .flatten()
+        .map_while(Result::ok)
+    {
+        let base = cap.offset() as usize;
+
+        match cap.capability_id() {
+            PciCapabilityId::PowerManagement => registers.push(Register::with_flags(
+                base + 0x04,
+                2,
+                "PM ControlStatus",
+                PciPowerManagementControlStatus::FLAGS,
+                PMCSR_FIELDS,
+            )),
+            PciCapabilityId::Msi => registers.push(Register::with_flags(
+                base + 0x02,
+                2,
+                "MSI MessageControl",
+                PciMsiMessageControl::FLAGS,
+                MSI_CONTROL_FIELDS,
+            )),
+            PciCapabilityId::MsiX => registers.push(Register::with_flags(
+                base + 0x02,
+                2,
+                "MSI-X MessageControl",
+                PciMsixMessageControl::FLAGS,
+                &[],
+            )),
+            PciCapabilityId::PciExpress => registers.extend([
+                Register::with_flags(
+                    base + 0x08,
+                    2,
+                    "PCIe DeviceControl",
+                    PciExpressDeviceControl::FLAGS,
+                    DEVICE_CONTROL_FIELDS,
+                ),
+                Register::with_flags(
+                    base + 0x0A,
+                    2,
+                    "PCIe DeviceStatus",
+                    PciExpressDeviceStatus::FLAGS,
+                    &[],
+                ),
+                Register::with_flags(
+                    base + 0x10,
+                    2,
+                    "PCIe LinkControl",
+                    PciExpressLinkControl::FLAGS,
+                    LINK_CONTROL_FIELDS,
+                ),
+                Register::with_flags(
+                    base + 0x12,
+                    2,
+                    "PCIe LinkStatus",
+                    PciExpressLinkStatus::FLAGS,
+                    LINK_STATUS_FIELDS,
+                ),
+                Register::with_flags(
+                    base + 0x18,
+                    2,
+                    "PCIe SlotControl",
+                    PciExpressSlotControl::FLAGS,
+                    SLOT_CONTROL_FIELDS,
+                ),
+                Register::with_flags(
+                    base + 0x1A,
+                    2,
+                    "PCIe SlotStatus",
+                    PciExpressSlotStatus::FLAGS,
+                    &[],
+                ),
+            ]),
+            _ => (),
+        }
+    }
+
+    for cap in PciExtendedCapabilityIterator::with_bytes(bytes).map_while(Result::ok) {
+        let base = cap.offset() as usize;
+
+        match cap.capability_id() {
+            PciExtendedCapabilityId::AdvancedErrorReporting => registers.extend([
+                Register::with_flags(
+                    base + 0x04,
+                    4,
+                    "AER UncorrectableStatus",
+                    PciAerUncorrectableErrors::FLAGS,
+                    &[],
+                ),
+                Register::with_flags(
+                    base + 0x08,
+                    4,
+                    "AER UncorrectableMask",
+                    PciAerUncorrectableErrors::FLAGS,
+                    &[],
+                ),
+                Register::with_flags(
+                    base + 0x0C,
+                    4,
+                    "AER UncorrectableSeverity",
+                    PciAerUncorrectableErrors::FLAGS,
+                    &[],
+                ),
+                Register::with_flags(
+                    base + 0x10,
+                    4,
+                    "AER CorrectableStatus",
+                    PciAerCorrectableErrors::FLAGS,
+                    &[],
+                ),
+                Register::with_flags(
+                    base + 0x14,
+                    4,
+                    "AER CorrectableMask",
+                    PciAerCorrectableErrors::FLAGS,
+                    &[],
+                ),
+                Register::with_flags(
+                    base + 0x18,
+                    4,
+                    "AER CapabilitiesControl",
+                    PciAerCapabilitiesControl::FLAGS,
+                    AER_CONTROL_FIELDS,
+                ),
+            ]),
+            PciExtendedCapabilityId::AccessControlServices => registers.push(Register::with_flags(
+                base + 0x06,
+                2,
+                "ACS Control",
+                PciAcsControl::FLAGS,
+                &[],
+            )),
+            PciExtendedCapabilityId::SingleRootIoVirtualization => registers.extend([
+                Register::with_flags(
+                    base + 0x08,
+                    2,
+                    "SR-IOV Control",
+                    PciSrIovControl::FLAGS,
+                    &[],
+                ),
+                Register::with_flags(base + 0x0A, 2, "SR-IOV Status", PciSrIovStatus::FLAGS, &[]),
+                Register::plain(base + 0x10, 2, "SR-IOV NumVFs"),
+            ]),
+            _ => (),
+        }
+    }
+
+    registers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_headers::{PciConfigSpaceBuilder, PciExpressCapability};
+
+    #[test]
+    fn reports_named_fields_and_raw_dwords() {
+        let mut pcie = PciExpressCapability::default();
+        pcie.link_status
+            .set_current_link_speed(PciExpressLinkSpeed::Gt8);
+        pcie.link_status
+            .set_negotiated_link_width(PciExpressLinkWidth::X4);
+
+        let before = PciConfigSpaceBuilder::new(0x8086, 0x1234)
+            .with_pci_express(&pcie)
+            .build()
+            .unwrap();
+        let pcie_offset = before[0x34] as usize;
+
+        let mut after = before.clone();
+        // Command: memory space and bus master enable
+        after[0x04] = 0x06;
+        // Link speed 8GT/s -> 2.5GT/s, width unchanged
+        after[pcie_offset + 0x12] = 0x41;
+        // Vendor-specific area
+        after[0xF0] = 0xAA;
+
+        let changes = diff_config_spaces(before.as_slice(), after.as_slice());
+        let changes = changes.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            changes,
+            vec![
+                "Command.Mem 0 -> 1".to_string(),
+                "Command.BusMaster 0 -> 1".to_string(),
+                "PCIe LinkStatus.Speed 8GT/s -> 2.5GT/s".to_string(),
+                "0x0F0[4] 0x00000000 -> 0x000000AA".to_string(),
+            ]
+        );
+
+        assert!(diff_config_spaces(before.as_slice(), before.as_slice()).is_empty());
+
+        // Max_Read_Request_Size 512B -> reserved encoding 7
+        let mut reserved = before.clone();
+        reserved[pcie_offset + 0x09] = 0x20;
+        let mut reserved_after = reserved.clone();
+        reserved_after[pcie_offset + 0x09] = 0x70;
+
+        let changes = diff_config_spaces(reserved.as_slice(), reserved_after.as_slice());
+
+        assert_eq!(
+            changes[0].to_string(),
+            "PCIe DeviceControl.MaxReadReq 512B -> unknown size (7)"
+        );
+
+        // Devices without a raw configuration space compare their headers
+        let device = |bytes: &[u8]| {
+            let common = crate::pci_headers::PciCommonHeader::with_bytes(bytes).unwrap();
+            let specialized = crate::pci_headers::PciSpecializedHeader::read_subheader(
+                common.header_type,
+                bytes,
+                true,
+            )
+            .unwrap();
+            crate::PciDevice::from_pci_header_set(common, Some(specialized))
+        };
+
+        let changes = device(&before)
+            .config_space_changes(&device(&after))
+            .unwrap();
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[1].name.as_deref(), Some("Command.BusMaster"));
+    }
+}
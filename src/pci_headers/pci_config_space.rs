@@ -0,0 +1,267 @@
+use crate::PciInfoError;
+
+use super::pci_config_buffer::{read_u16_at, read_u32_at, read_u8_at};
+use super::{
+    validate_config_space, PciCapability, PciCapabilityId, PciCapabilityIterator, PciCommonHeader,
+    PciConfigFinding, PciExtendedCapability, PciExtendedCapabilityId,
+    PciExtendedCapabilityIterator, PciSpecializedHeader,
+};
+
+/// An owned copy of the configuration space of a PCI device.
+///
+/// The storage is either 256 bytes (standard configuration space) or 4096
+/// bytes (PCI Express extended configuration space), but only the first
+/// [`PciConfigSpace::len`] bytes are valid, as enumerators usually cannot
+/// read the whole configuration space without elevated privileges. Reads
+/// beyond the valid length fail with [`PciInfoError::UnexpectedEof`].
+///
+/// The common and specialized headers are parsed once, when the
+/// configuration space is created.
+///
+/// # Example
+/// ```rust
+/// use pci_info::pci_headers::{PciCapabilityId, PciConfigSpace, PciConfigSpaceBuilder};
+///
+/// # fn main() -> Result<(), pci_info::PciInfoError> {
+/// let bytes = PciConfigSpaceBuilder::new(0x8086, 0x1234)
+///     .with_capability(PciCapabilityId::VendorSpecific, &[0x04, 0x00])
+///     .build()?;
+/// let config = PciConfigSpace::with_bytes(&bytes)?;
+///
+/// assert_eq!(config.read_u16(0x00)?, 0x8086);
+/// assert_eq!(config.common_header().device_id, 0x1234);
+/// assert!(config.find_capability(PciCapabilityId::VendorSpecific)?.is_some());
+/// assert!(config.read_u32(0x100).is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct PciConfigSpace {
+    bytes: Box<[u8]>,
+    len: usize,
+    common_header: PciCommonHeader,
+    specialized_header: Result<PciSpecializedHeader, PciInfoError>,
+}
+
+impl PciConfigSpace {
+    /// The length of the standard configuration space.
+    pub const STANDARD_LEN: usize = PciCapabilityIterator::STANDARD_CONFIG_SPACE_LEN;
+    /// The length of the PCI Express extended configuration space.
+    pub const EXTENDED_LEN: usize = PciExtendedCapabilityIterator::EXTENDED_CONFIG_SPACE_LEN;
+
+    /// Creates a configuration space by copying `bytes`, which must start at
+    /// the beginning of the common header and contain at least the common
+    /// header and at most 4096 bytes.
+    pub fn with_bytes(bytes: &[u8]) -> Result<Self, PciInfoError> {
+        if bytes.len() < PciCommonHeader::COMMON_HEADER_LEN {
+            return Err(PciInfoError::UnexpectedEof);
+        }
+
+        if bytes.len() > Self::EXTENDED_LEN {
+            return Err(PciInfoError::ParseError(
+                format!(
+                    "configuration space of {} bytes exceeds {} bytes",
+                    bytes.len(),
+                    Self::EXTENDED_LEN
+                )
+                .into(),
+            ));
+        }
+
+        let storage_len = match bytes.len() {
+            len if len <= Self::STANDARD_LEN => Self::STANDARD_LEN,
+            _ => Self::EXTENDED_LEN,
+        };
+
+        let mut storage = vec![0u8; storage_len].into_boxed_slice();
+        storage[..bytes.len()].copy_from_slice(bytes);
+
+        let header_type = bytes[0x0E];
+        let specialized_header = match PciSpecializedHeader::length_of_subheader(header_type) {
+            Some(len) if bytes.len() < len => Err(PciInfoError::UnexpectedEof),
+            _ => PciSpecializedHeader::read_subheader(header_type, bytes, true),
+        };
+
+        Ok(Self {
+            common_header: PciCommonHeader::with_bytes(bytes)?,
+            specialized_header,
+            bytes: storage,
+            len: bytes.len(),
+        })
+    }
+
+    /// The number of valid bytes in the configuration space.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if there are no valid bytes in the configuration space.
+    /// This is never the case, as at least the common header is valid.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns true if the storage is large enough for the PCI Express
+    /// extended configuration space.
+    pub fn is_extended(&self) -> bool {
+        self.bytes.len() == Self::EXTENDED_LEN
+    }
+
+    /// Returns the valid bytes of the configuration space.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+
+    /// Reads a byte at an arbitrary offset.
+    pub fn read_u8(&self, offset: usize) -> Result<u8, PciInfoError> {
+        read_u8_at(self.as_bytes(), offset)
+    }
+
+    /// Reads a little endian `u16` at an arbitrary offset.
+    pub fn read_u16(&self, offset: usize) -> Result<u16, PciInfoError> {
+        read_u16_at(self.as_bytes(), offset)
+    }
+
+    /// Reads a little endian `u32` at an arbitrary offset.
+    pub fn read_u32(&self, offset: usize) -> Result<u32, PciInfoError> {
+        read_u32_at(self.as_bytes(), offset)
+    }
+
+    /// Returns the common header.
+    pub fn common_header(&self) -> &PciCommonHeader {
+        &self.common_header
+    }
+
+    /// Returns the specialized header, of the type found in the common
+    /// header, or the error that occurred parsing it.
+    pub fn specialized_header(&self) -> Result<&PciSpecializedHeader, &PciInfoError> {
+        self.specialized_header.as_ref()
+    }
+
+    /// Returns the bytes of the device-specific area of the standard
+    /// configuration space (`0x40..0x100`), where the standard capabilities
+    /// are located. The slice is truncated to the valid length.
+    pub fn capability_area(&self) -> &[u8] {
+        self.valid_range(
+            PciCapabilityIterator::FIRST_CAPABILITY_OFFSET,
+            PciCapabilityIterator::STANDARD_CONFIG_SPACE_LEN,
+        )
+    }
+
+    /// Returns the bytes of the extended configuration space
+    /// (`0x100..0x1000`), where the extended capabilities are located. The
+    /// slice is truncated to the valid length, and is empty if the extended
+    /// configuration space is not available.
+    pub fn extended_capability_area(&self) -> &[u8] {
+        self.valid_range(
+            PciExtendedCapabilityIterator::FIRST_EXTENDED_CAPABILITY_OFFSET,
+            PciExtendedCapabilityIterator::EXTENDED_CONFIG_SPACE_LEN,
+        )
+    }
+
+    /// Returns an iterator over the standard capabilities.
+    pub fn capabilities(&self) -> Result<PciCapabilityIterator<'_>, PciInfoError> {
+        PciCapabilityIterator::with_bytes(self.as_bytes())
+    }
+
+    /// Returns an iterator over the extended capabilities.
+    pub fn extended_capabilities(&self) -> PciExtendedCapabilityIterator<'_> {
+        PciExtendedCapabilityIterator::with_bytes(self.as_bytes())
+    }
+
+    /// Looks for the first standard capability with the specified id.
+    pub fn find_capability(
+        &self,
+        id: PciCapabilityId,
+    ) -> Result<Option<PciCapability<'_>>, PciInfoError> {
+        self.capabilities()?.find_capability(id)
+    }
+
+    /// Looks for the first extended capability with the specified id.
+    pub fn find_extended_capability(
+        &self,
+        id: PciExtendedCapabilityId,
+    ) -> Result<Option<PciExtendedCapability<'_>>, PciInfoError> {
+        self.extended_capabilities().find_capability(id)
+    }
+
+    /// Checks the configuration space for structural problems; see
+    /// [`validate_config_space`].
+    pub fn validate(&self) -> Vec<PciConfigFinding> {
+        validate_config_space(self.as_bytes())
+    }
+
+    fn valid_range(&self, start: usize, end: usize) -> &[u8] {
+        let end = end.min(self.len);
+        let start = start.min(end);
+
+        &self.bytes[start..end]
+    }
+}
+
+impl std::fmt::Debug for PciConfigSpace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[PciConfigSpace {} of {} bytes valid]",
+            self.len,
+            self.bytes.len()
+        )
+    }
+}
+
+impl TryFrom<&[u8]> for PciConfigSpace {
+    type Error = PciInfoError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::with_bytes(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_are_bounded_by_valid_length() {
+        let mut bytes = vec![0u8; 64];
+        bytes[0x00..0x04].copy_from_slice(&0x1234_8086u32.to_le_bytes());
+        bytes[0x3E] = 0x5A;
+
+        let config = PciConfigSpace::with_bytes(&bytes).unwrap();
+
+        assert_eq!(config.len(), 64);
+        assert!(!config.is_extended());
+        assert_eq!(config.read_u32(0x00).unwrap(), 0x1234_8086);
+        assert_eq!(config.read_u16(0x3E).unwrap(), 0x005A);
+        assert!(matches!(
+            config.read_u16(0x3F),
+            Err(PciInfoError::UnexpectedEof)
+        ));
+        assert!(config.capability_area().is_empty());
+        assert!(config.extended_capability_area().is_empty());
+        assert_eq!(config.extended_capabilities().count(), 0);
+        assert!(matches!(
+            config.specialized_header(),
+            Ok(PciSpecializedHeader::GenericDevice(_))
+        ));
+
+        bytes[0x0E] = 0x7F;
+        let config = PciConfigSpace::with_bytes(&bytes).unwrap();
+
+        assert_eq!(config.common_header().header_type, 0x7F);
+        assert!(matches!(
+            config.specialized_header(),
+            Err(PciInfoError::UnknownPciHeaderType(0x7F))
+        ));
+
+        let config = PciConfigSpace::with_bytes(&vec![0u8; 300]).unwrap();
+
+        assert!(config.is_extended());
+        assert_eq!(config.capability_area().len(), 0xC0);
+        assert_eq!(config.extended_capability_area().len(), 300 - 0x100);
+
+        assert!(PciConfigSpace::with_bytes(&[0u8; 8]).is_err());
+        assert!(PciConfigSpace::with_bytes(&vec![0u8; 4097]).is_err());
+    }
+}
@@ -0,0 +1,510 @@
+use crate::PciInfoError;
+
+use super::pci_config_buffer::write_u32_at;
+use super::{
+    PciAerCapability, PciAerRootErrorRegisters, PciBar, PciCapabilityId, PciCapabilityIterator,
+    PciCommonHeader, PciExpressCapability, PciExtendedCapabilityId, PciExtendedCapabilityIterator,
+    PciGenericDeviceHeader, PciMsiCapability, PciMsixCapability, PciPowerManagementCapability,
+    PciSpecializedHeader, PciToCardbusBridgeHeader, PciToPciBridgeHeader,
+};
+
+/// A builder of synthetic PCI configuration spaces, e.g. to be used as
+/// fixtures in tests.
+///
+/// The builder starts from an empty generic device header; the header type,
+/// the identification registers and the BARs can then be set, and standard
+/// and extended capabilities appended. Capabilities are laid out in order
+/// starting at offset `0x40` (standard) and `0x100` (extended), and linked
+/// together when the image is built. Errors are reported by
+/// [`PciConfigSpaceBuilder::build`].
+///
+/// # Example
+/// ```rust
+/// use pci_info::pci_headers::*;
+///
+/// # fn main() -> Result<(), pci_info::PciInfoError> {
+/// let mut msi_control = PciMsiMessageControl::default();
+/// msi_control.set_address_64bit_capable(true);
+///
+/// let config = PciConfigSpaceBuilder::new(0x8086, 0x1234)
+///     .with_class(0x02, 0x00, 0x00)
+///     .with_bar(PciBar::Memory64 { index: 0, base: 0xF_E000_0000, prefetchable: false, size: None })
+///     .with_msi(&PciMsiCapability {
+///         message_control: msi_control,
+///         message_address: 0xFEE0_0000,
+///         message_data: 0x4021,
+///         mask_bits: None,
+///         pending_bits: None,
+///     })
+///     .with_capability(PciCapabilityId::VendorSpecific, &[0x04, 0x00])
+///     .build()?;
+///
+/// assert_eq!(config.len(), 256);
+/// assert_eq!(PciCapabilityIterator::with_bytes(&config)?.count(), 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct PciConfigSpaceBuilder {
+    common: PciCommonHeader,
+    specialized: PciSpecializedHeader,
+    capabilities: Vec<Vec<u8>>,
+    extended_capabilities: Vec<Vec<u8>>,
+    extended_config_space: bool,
+    error: Option<PciInfoError>,
+}
+
+impl PciConfigSpaceBuilder {
+    /// Creates a builder for a generic device (header type 0) with the
+    /// specified vendor and device ids.
+    pub fn new(vendor_id: u16, device_id: u16) -> Self {
+        Self {
+            common: PciCommonHeader {
+                vendor_id,
+                device_id,
+                ..Default::default()
+            },
+            specialized: PciSpecializedHeader::GenericDevice(PciGenericDeviceHeader::default()),
+            capabilities: Vec::new(),
+            extended_capabilities: Vec::new(),
+            extended_config_space: false,
+            error: None,
+        }
+    }
+
+    /// Sets the class, subclass and programming interface codes.
+    pub fn with_class(mut self, class_code: u8, subclass_code: u8, prog_iface_code: u8) -> Self {
+        self.common.class_code = class_code;
+        self.common.subclass_code = subclass_code;
+        self.common.prog_iface_code = prog_iface_code;
+        self
+    }
+
+    /// Sets the revision id.
+    pub fn with_revision(mut self, revision_id: u8) -> Self {
+        self.common.revision_id = revision_id;
+        self
+    }
+
+    /// Sets the header type, replacing the specialized header with an empty
+    /// one of the new type. The multi-function bit (bit 7) of `header_type`
+    /// is kept in the common header.
+    pub fn with_header_type(mut self, header_type: u8) -> Self {
+        self.common.header_type = header_type;
+        self.specialized = match header_type & 0x7F {
+            PciGenericDeviceHeader::ID => {
+                PciSpecializedHeader::GenericDevice(PciGenericDeviceHeader::default())
+            }
+            PciToPciBridgeHeader::ID => {
+                PciSpecializedHeader::PciToPciBridge(PciToPciBridgeHeader::default())
+            }
+            PciToCardbusBridgeHeader::ID => {
+                PciSpecializedHeader::PciToCardbusBridge(PciToCardbusBridgeHeader::default())
+            }
+            _ => return self.fail(PciInfoError::UnknownPciHeaderType(header_type)),
+        };
+        self
+    }
+
+    /// Replaces the whole common header. The capabilities list bit of the
+    /// Status register is set when the image is built if capabilities are
+    /// present.
+    pub fn with_common_header(mut self, header: PciCommonHeader) -> Self {
+        self.common = header;
+        self
+    }
+
+    /// Replaces the whole specialized header, updating the header type of
+    /// the common header. The capabilities pointer is overwritten when the
+    /// image is built.
+    pub fn with_specialized_header(mut self, header: PciSpecializedHeader) -> Self {
+        self.common.header_type = (self.common.header_type & 0x80) | header.header_type();
+        self.specialized = header;
+        self
+    }
+
+    /// Sets the subsystem vendor and device ids. Fails for PCI to PCI
+    /// bridges, whose header has no subsystem ids.
+    pub fn with_subsystem(mut self, subsystem_vendor_id: u16, subsystem_device_id: u16) -> Self {
+        match &mut self.specialized {
+            PciSpecializedHeader::GenericDevice(h) => {
+                h.subsystem_vendor_id = subsystem_vendor_id;
+                h.subsystem_device_id = subsystem_device_id;
+            }
+            PciSpecializedHeader::PciToCardbusBridge(h) => {
+                h.subsystem_vendor_id = subsystem_vendor_id;
+                h.subsystem_device_id = subsystem_device_id;
+            }
+            PciSpecializedHeader::PciToPciBridge(_) => {
+                return self.fail(PciInfoError::InvalidBuildRequest(
+                    "PCI to PCI bridge headers have no subsystem ids".into(),
+                ))
+            }
+        }
+        self
+    }
+
+    /// Sets a Base Address Register (both registers, for 64-bit BARs). Fails
+    /// if the header does not have enough BARs.
+    pub fn with_bar(mut self, bar: PciBar) -> Self {
+        let base_addr: &mut [u32] = match &mut self.specialized {
+            PciSpecializedHeader::GenericDevice(h) => &mut h.base_addr,
+            PciSpecializedHeader::PciToPciBridge(h) => &mut h.base_addr,
+            PciSpecializedHeader::PciToCardbusBridge(_) => &mut [],
+        };
+
+        let index = bar.index() as usize;
+        let registers = bar.to_registers();
+
+        match base_addr.get_mut(index..index + registers.len()) {
+            Some(slots) => slots.copy_from_slice(&registers),
+            None => {
+                return self.fail(PciInfoError::InvalidBuildRequest(
+                    format!("BAR {index} is not available in this header type").into(),
+                ))
+            }
+        }
+
+        self
+    }
+
+    /// Appends a standard capability with the specified id; `body` contains
+    /// the bytes that follow the capability id and next pointer.
+    pub fn with_capability(self, id: PciCapabilityId, body: &[u8]) -> Self {
+        let mut bytes = vec![id.as_code(), 0];
+        bytes.extend_from_slice(body);
+        self.with_capability_bytes(bytes)
+    }
+
+    /// Appends a Power Management capability.
+    pub fn with_power_management(self, cap: &PciPowerManagementCapability) -> Self {
+        self.with_capability_bytes(cap.to_bytes())
+    }
+
+    /// Appends an MSI capability.
+    pub fn with_msi(self, cap: &PciMsiCapability) -> Self {
+        self.with_capability_bytes(cap.to_bytes())
+    }
+
+    /// Appends an MSI-X capability.
+    pub fn with_msix(self, cap: &PciMsixCapability) -> Self {
+        self.with_capability_bytes(cap.to_bytes())
+    }
+
+    /// Appends a PCI Express capability.
+    pub fn with_pci_express(self, cap: &PciExpressCapability) -> Self {
+        self.with_capability_bytes(cap.to_bytes())
+    }
+
+    /// Appends an extended capability with the specified id and version;
+    /// `body` contains the bytes that follow the extended capability header.
+    /// This implies an extended configuration space.
+    pub fn with_extended_capability(
+        self,
+        id: PciExtendedCapabilityId,
+        version: u8,
+        body: &[u8],
+    ) -> Self {
+        let header = id.as_code() as u32 | ((version & 0xF) as u32) << 16;
+        let mut bytes = header.to_le_bytes().to_vec();
+        bytes.extend_from_slice(body);
+        self.with_extended_capability_bytes(bytes)
+    }
+
+    /// Appends an Advanced Error Reporting extended capability, including
+    /// the root port registers if `root` is specified. This implies an
+    /// extended configuration space.
+    pub fn with_aer(self, cap: &PciAerCapability, root: Option<&PciAerRootErrorRegisters>) -> Self {
+        let bytes = match root {
+            Some(root) => root.to_bytes(cap),
+            None => cap.to_bytes(),
+        };
+
+        self.with_extended_capability_bytes(bytes)
+    }
+
+    /// Selects whether a 4096 bytes (extended) or a 256 bytes configuration
+    /// space is built. The extended configuration space is always built if
+    /// extended capabilities are present.
+    pub fn with_extended_config_space(mut self, extended: bool) -> Self {
+        self.extended_config_space = extended;
+        self
+    }
+
+    /// Builds the configuration space image, linking the capabilities
+    /// together.
+    pub fn build(self) -> Result<Vec<u8>, PciInfoError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+
+        let extended = self.extended_config_space || !self.extended_capabilities.is_empty();
+        let length = match extended {
+            true => PciExtendedCapabilityIterator::EXTENDED_CONFIG_SPACE_LEN,
+            false => PciCapabilityIterator::STANDARD_CONFIG_SPACE_LEN,
+        };
+
+        let mut bytes = vec![0u8; length];
+        let mut common = self.common;
+        let mut specialized = self.specialized;
+
+        let first_capability = Self::layout(
+            &mut bytes,
+            &self.capabilities,
+            PciCapabilityIterator::FIRST_CAPABILITY_OFFSET,
+            PciCapabilityIterator::STANDARD_CONFIG_SPACE_LEN,
+            |cap, next| cap[1] = next as u8,
+        )?;
+
+        Self::layout(
+            &mut bytes,
+            &self.extended_capabilities,
+            PciExtendedCapabilityIterator::FIRST_EXTENDED_CAPABILITY_OFFSET,
+            PciExtendedCapabilityIterator::EXTENDED_CONFIG_SPACE_LEN,
+            |cap, next| {
+                let header = u32::from_le_bytes([cap[0], cap[1], cap[2], cap[3]]);
+                write_u32_at(cap, 0, header & 0xF_FFFF | (next as u32) << 20);
+            },
+        )?;
+
+        let mut status = common.status_register();
+        status.set_capabilities_list(first_capability != 0);
+        common.set_status_register(status);
+
+        match &mut specialized {
+            PciSpecializedHeader::GenericDevice(h) => h.capabilities_ptr = first_capability as u16,
+            PciSpecializedHeader::PciToPciBridge(h) => {
+                h.capability_pointer = first_capability as u16
+            }
+            PciSpecializedHeader::PciToCardbusBridge(h) => {
+                h.offset_of_capabilities_list = first_capability as u8
+            }
+        }
+
+        common.write_bytes(&mut bytes)?;
+        specialized.write_subheader(&mut bytes, true)?;
+
+        Ok(bytes)
+    }
+
+    fn with_capability_bytes(mut self, bytes: Vec<u8>) -> Self {
+        self.capabilities.push(bytes);
+        self
+    }
+
+    fn with_extended_capability_bytes(mut self, bytes: Vec<u8>) -> Self {
+        self.extended_capabilities.push(bytes);
+        self
+    }
+
+    fn fail(mut self, error: PciInfoError) -> Self {
+        self.error.get_or_insert(error);
+        self
+    }
+
+    // Copies the capabilities in `bytes`, starting at `first_offset` and
+    // aligning each one to 4 bytes, then links each capability to the next
+    // using `set_next`. Returns the offset of the first capability, or 0 if
+    // there are none.
+    fn layout(
+        bytes: &mut [u8],
+        capabilities: &[Vec<u8>],
+        first_offset: usize,
+        end: usize,
+        set_next: impl Fn(&mut [u8], usize),
+    ) -> Result<usize, PciInfoError> {
+        let mut offsets = Vec::with_capacity(capabilities.len());
+        let mut offset = first_offset;
+
+        for cap in capabilities {
+            if offset + cap.len() > end {
+                return Err(PciInfoError::InvalidBuildRequest(
+                    format!("capabilities do not fit before offset 0x{end:X}").into(),
+                ));
+            }
+
+            bytes[offset..offset + cap.len()].copy_from_slice(cap);
+            offsets.push(offset);
+            offset = (offset + cap.len() + 3) & !3;
+        }
+
+        for (i, cap_offset) in offsets.iter().enumerate() {
+            let next = offsets.get(i + 1).copied().unwrap_or(0);
+            set_next(&mut bytes[*cap_offset..], next);
+        }
+
+        Ok(offsets.first().copied().unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_headers::{
+        PciExpressDevicePortType, PciExpressLinkSpeed, PciExpressLinkWidth, PciExtendedCapability,
+        PciMsixMessageControl, PciPowerState,
+    };
+
+    #[test]
+    fn builds_linked_capability_chains() {
+        let mut pm = PciPowerManagementCapability::default();
+        pm.control_status.set_power_state(PciPowerState::D3Hot);
+
+        let mut pcie = PciExpressCapability::default();
+        pcie.capabilities.set_version(2);
+        pcie.capabilities
+            .set_device_port_type(PciExpressDevicePortType::RootPort);
+        pcie.link_status
+            .set_current_link_speed(PciExpressLinkSpeed::Gt8);
+        pcie.link_status
+            .set_negotiated_link_width(PciExpressLinkWidth::X4);
+        pcie.link_control_2 = 0x0003;
+
+        let mut msix_control = PciMsixMessageControl::default();
+        msix_control.set_table_size(16);
+
+        let msix = PciMsixCapability {
+            message_control: msix_control,
+            table_bir: 0,
+            table_offset: 0x2000,
+            pba_bir: 0,
+            pba_offset: 0x3000,
+        };
+
+        let mut aer = PciAerCapability::default();
+        aer.uncorrectable_severity.0 = 0x0046_2030;
+
+        let config = PciConfigSpaceBuilder::new(0x1022, 0x1483)
+            .with_header_type(0x81)
+            .with_class(0x06, 0x04, 0x00)
+            .with_bar(PciBar::Memory64 {
+                index: 0,
+                base: 0x38_0000_0000,
+                prefetchable: true,
+                size: None,
+            })
+            .with_power_management(&pm)
+            .with_pci_express(&pcie)
+            .with_msix(&msix)
+            .with_aer(&aer, None)
+            .with_extended_capability(
+                PciExtendedCapabilityId::AccessControlServices,
+                1,
+                &[0x1F, 0, 0x1D, 0],
+            )
+            .build()
+            .unwrap();
+
+        assert_eq!(config.len(), 4096);
+
+        let common = PciCommonHeader::with_bytes(&config).unwrap();
+        assert_eq!((common.vendor_id, common.device_id), (0x1022, 0x1483));
+        assert_eq!(common.header_type, 0x81);
+        assert!(common.status_register().capabilities_list());
+
+        let Ok(PciSpecializedHeader::PciToPciBridge(bridge)) =
+            PciSpecializedHeader::read_subheader(common.header_type, &config, true)
+        else {
+            panic!("unexpected header type");
+        };
+        assert_eq!(bridge.capability_pointer, 0x40);
+        assert_eq!(
+            bridge.bars(),
+            vec![PciBar::Memory64 {
+                index: 0,
+                base: 0x38_0000_0000,
+                prefetchable: true,
+                size: None
+            }]
+        );
+
+        let caps = PciCapabilityIterator::with_bytes(&config)
+            .unwrap()
+            .map(|c| c.map(|c| (c.offset(), c.capability_id())))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            caps,
+            vec![
+                (0x40, PciCapabilityId::PowerManagement),
+                (0x48, PciCapabilityId::PciExpress),
+                (0x84, PciCapabilityId::MsiX),
+            ]
+        );
+
+        let found = |id| {
+            PciCapabilityIterator::with_bytes(&config)
+                .unwrap()
+                .find_capability(id)
+                .unwrap()
+                .unwrap()
+        };
+
+        let decoded =
+            PciPowerManagementCapability::with_capability(&found(PciCapabilityId::PowerManagement))
+                .unwrap();
+        assert_eq!(decoded.power_state(), PciPowerState::D3Hot);
+
+        let decoded =
+            PciExpressCapability::with_capability(&found(PciCapabilityId::PciExpress)).unwrap();
+        assert_eq!(
+            decoded.device_port_type(),
+            PciExpressDevicePortType::RootPort
+        );
+        assert_eq!(decoded.negotiated_link_speed(), PciExpressLinkSpeed::Gt8);
+        assert_eq!(decoded.negotiated_link_width(), PciExpressLinkWidth::X4);
+        assert_eq!(decoded.link_control_2, 0x0003);
+
+        let decoded = PciMsixCapability::with_capability(&found(PciCapabilityId::MsiX)).unwrap();
+        assert_eq!(decoded.message_control.table_size(), 16);
+        assert_eq!(decoded.pba_offset, 0x3000);
+
+        let ext_caps = PciExtendedCapabilityIterator::with_bytes(&config)
+            .collect::<Result<Vec<PciExtendedCapability>, _>>()
+            .unwrap();
+        assert_eq!(ext_caps.len(), 2);
+        assert_eq!(ext_caps[0].offset(), 0x100);
+        assert_eq!(ext_caps[0].next_offset(), 0x12C);
+        assert_eq!(
+            ext_caps[1].capability_id(),
+            PciExtendedCapabilityId::AccessControlServices
+        );
+        assert_eq!(ext_caps[1].next_offset(), 0);
+
+        let decoded = PciAerCapability::with_capability(&ext_caps[0]).unwrap();
+        assert_eq!(decoded.uncorrectable_severity.0, 0x0046_2030);
+    }
+
+    #[test]
+    fn reports_errors_on_build() {
+        assert!(PciConfigSpaceBuilder::new(0, 0)
+            .with_header_type(0x05)
+            .build()
+            .is_err_and(|e| matches!(e, PciInfoError::UnknownPciHeaderType(0x05))));
+        assert!(PciConfigSpaceBuilder::new(0, 0)
+            .with_header_type(0x01)
+            .with_subsystem(0x8086, 0x0001)
+            .build()
+            .is_err_and(|e| matches!(e, PciInfoError::InvalidBuildRequest(_))));
+        assert!(PciConfigSpaceBuilder::new(0, 0)
+            .with_header_type(0x01)
+            .with_bar(PciBar::Io {
+                index: 2,
+                base: 0xE000,
+                size: None
+            })
+            .build()
+            .is_err_and(|e| matches!(e, PciInfoError::InvalidBuildRequest(_))));
+        assert!(PciConfigSpaceBuilder::new(0, 0)
+            .with_capability(PciCapabilityId::VendorSpecific, &[0; 0xC0])
+            .build()
+            .is_err_and(|e| matches!(e, PciInfoError::InvalidBuildRequest(_))));
+
+        let config = PciConfigSpaceBuilder::new(0, 0).build().unwrap();
+        assert_eq!(config.len(), 256);
+        assert!(!PciCommonHeader::with_bytes(&config)
+            .unwrap()
+            .status_register()
+            .capabilities_list());
+    }
+}
@@ -0,0 +1,521 @@
+use super::{
+    PciCapabilityIterator, PciCommonHeader, PciConfigAccess, PciExtendedCapabilityIterator,
+    PciGenericDeviceHeader, PciSpecializedHeader, PciToCardbusBridgeHeader, PciToPciBridgeHeader,
+};
+
+/// The severity of a [`PciConfigFinding`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum PciConfigSeverity {
+    /// The configuration space is valid, but some of it could not be checked.
+    Info,
+    /// The configuration space violates the specification in a way that
+    /// does not prevent it from being parsed.
+    Warning,
+    /// The configuration space is malformed, and parsing part of it fails
+    /// or produces meaningless results.
+    Error,
+}
+
+impl std::fmt::Display for PciConfigSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Info => write!(f, "info"),
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A structural problem found in a configuration space by
+/// [`validate_config_space`].
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum PciConfigIssue {
+    /// The configuration space is shorter than the header of the device.
+    TruncatedHeader { length: usize, expected: usize },
+    /// The header type is not one of the types defined by the specification.
+    UnknownHeaderType(u8),
+    /// A capability pointer has its reserved low bits set.
+    MisalignedCapabilityPointer(u16),
+    /// A capability pointer points outside of the area reserved to the
+    /// capabilities of its list.
+    CapabilityPointerOutOfRange(u16),
+    /// A capability pointer points to a capability already visited.
+    CapabilityLoop(u16),
+    /// A capability pointer points beyond the end of the configuration
+    /// space, which is usually the case for dumps taken without elevated
+    /// privileges.
+    CapabilityBeyondEnd(u16),
+    /// A 64-bit memory BAR is in the last BAR slot, so its upper half is
+    /// missing.
+    UnpairedBar64 { index: u8 },
+    /// The subordinate bus number of a bridge is lower than its secondary
+    /// bus number.
+    SubordinateBelowSecondaryBus { secondary: u8, subordinate: u8 },
+    /// Bits reserved by the specification are set in a register.
+    ReservedBitsSet { register: &'static str, bits: u32 },
+}
+
+impl std::fmt::Display for PciConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TruncatedHeader { length, expected } => {
+                write!(f, "header truncated at {length} bytes, expected {expected}")
+            }
+            Self::UnknownHeaderType(t) => write!(f, "unknown header type 0x{t:02X}"),
+            Self::MisalignedCapabilityPointer(p) => {
+                write!(f, "misaligned capability pointer 0x{p:03X}")
+            }
+            Self::CapabilityPointerOutOfRange(p) => {
+                write!(f, "capability pointer 0x{p:03X} out of range")
+            }
+            Self::CapabilityLoop(p) => write!(f, "capability loop at 0x{p:03X}"),
+            Self::CapabilityBeyondEnd(p) => {
+                write!(f, "capability at 0x{p:03X} is beyond the end of the data")
+            }
+            Self::UnpairedBar64 { index } => {
+                write!(f, "64-bit BAR {index} has no upper half")
+            }
+            Self::SubordinateBelowSecondaryBus {
+                secondary,
+                subordinate,
+            } => write!(
+                f,
+                "subordinate bus {subordinate:02x} is below secondary bus {secondary:02x}"
+            ),
+            Self::ReservedBitsSet { register, bits } => {
+                write!(f, "reserved bits 0x{bits:X} set in {register}")
+            }
+        }
+    }
+}
+
+/// A problem found in a configuration space, with the offset of the
+/// register it was found in.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PciConfigFinding {
+    pub offset: usize,
+    pub severity: PciConfigSeverity,
+    pub issue: PciConfigIssue,
+}
+
+impl std::fmt::Display for PciConfigFinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "0x{:03X}: {}: {}",
+            self.offset, self.severity, self.issue
+        )
+    }
+}
+
+/// Checks a configuration space for structural problems, such as malformed
+/// capability lists, inconsistent bridge bus numbers or reserved bits set.
+///
+/// Findings are returned in the order they are found, which roughly follows
+/// the layout of the configuration space. An empty result means that no
+/// problem was found in the bytes available; configuration spaces that only
+/// contain the first 64 bytes (as read by unprivileged users) are checked as
+/// far as possible.
+///
+/// # Example
+/// ```rust
+/// use pci_info::pci_headers::{validate_config_space, PciConfigSeverity};
+///
+/// let mut config = [0u8; 256];
+/// config[0x06] = 0x10; // Status: capabilities list
+/// config[0x34] = 0x42; // Misaligned capabilities pointer
+/// config[0x40] = 0x01; // Power management, pointing back to itself
+/// config[0x41] = 0x40;
+///
+/// for finding in validate_config_space(config.as_slice()) {
+///     // Prints "0x034: warning: misaligned capability pointer 0x042"
+///     // and "0x041: error: capability loop at 0x040"
+///     println!("{finding}");
+/// }
+///
+/// assert!(validate_config_space(config.as_slice())
+///     .iter()
+///     .any(|f| f.severity == PciConfigSeverity::Error));
+/// ```
+pub fn validate_config_space<A: PciConfigAccess + ?Sized>(config: &A) -> Vec<PciConfigFinding> {
+    let mut validator = Validator {
+        config,
+        findings: Vec::new(),
+    };
+
+    validator.validate();
+    validator.findings
+}
+
+struct Validator<'a, A: ?Sized> {
+    config: &'a A,
+    findings: Vec<PciConfigFinding>,
+}
+
+impl<A: PciConfigAccess + ?Sized> Validator<'_, A> {
+    // Reserved bits of the Command and Status registers
+    const COMMAND_RESERVED: u32 = 0xF800;
+    const STATUS_RESERVED: u32 = 0x0046;
+
+    fn validate(&mut self) {
+        let header = match PciCommonHeader::with_access(self.config) {
+            Ok(header) => header,
+            Err(_) => {
+                self.push(
+                    0,
+                    PciConfigSeverity::Error,
+                    PciConfigIssue::TruncatedHeader {
+                        length: self.config.size(),
+                        expected: PciCommonHeader::COMMON_HEADER_LEN,
+                    },
+                );
+                return;
+            }
+        };
+
+        self.check_reserved(0x04, 2, Self::COMMAND_RESERVED, "Command");
+        self.check_reserved(0x06, 2, Self::STATUS_RESERVED, "Status");
+
+        let Some(len) = PciSpecializedHeader::length_of_subheader(header.header_type) else {
+            self.push(
+                0x0E,
+                PciConfigSeverity::Error,
+                PciConfigIssue::UnknownHeaderType(header.header_type),
+            );
+            return;
+        };
+
+        if self.config.size() < len {
+            self.push(
+                0,
+                PciConfigSeverity::Error,
+                PciConfigIssue::TruncatedHeader {
+                    length: self.config.size(),
+                    expected: len,
+                },
+            );
+            return;
+        }
+
+        match header.header_type & 0x7F {
+            PciGenericDeviceHeader::ID => {
+                self.check_last_bar(5);
+                self.check_reserved(0x34, 4, 0xFFFF_FF00, "Capabilities Pointer");
+                self.check_reserved(0x38, 4, 0xFFFF_FFFF, "Reserved");
+            }
+            PciToPciBridgeHeader::ID => {
+                self.check_last_bar(1);
+                self.check_bus_numbers();
+                // Address decode types other than 16 and 32 bits are reserved
+                self.check_reserved(0x1C, 1, 0x0E, "I/O Base");
+                self.check_reserved(0x1D, 1, 0x0E, "I/O Limit");
+                self.check_reserved(0x1E, 2, 0x001F, "Secondary Status");
+                self.check_reserved(0x20, 2, 0x000F, "Memory Base");
+                self.check_reserved(0x22, 2, 0x000F, "Memory Limit");
+                self.check_reserved(0x24, 2, 0x000E, "Prefetchable Memory Base");
+                self.check_reserved(0x26, 2, 0x000E, "Prefetchable Memory Limit");
+                self.check_reserved(0x34, 4, 0xFFFF_FF00, "Capabilities Pointer");
+                self.check_reserved(0x3E, 2, 0xF000, "Bridge Control");
+            }
+            PciToCardbusBridgeHeader::ID => {
+                self.check_bus_numbers();
+                self.check_reserved(0x15, 1, 0xFF, "Reserved");
+                self.check_reserved(0x16, 2, 0x001F, "Secondary Status");
+                self.check_reserved(0x3E, 2, 0xF810, "Bridge Control");
+            }
+            _ => unreachable!(),
+        }
+
+        if header.status_register().capabilities_list() {
+            self.check_capabilities(header.header_type);
+        }
+
+        self.check_extended_capabilities();
+    }
+
+    fn check_last_bar(&mut self, index: u8) {
+        let offset = 0x10 + index as usize * 4;
+
+        if let Ok(raw) = self.config.read_u32(offset) {
+            if raw & 0x7 == 0x4 {
+                self.push(
+                    offset,
+                    PciConfigSeverity::Error,
+                    PciConfigIssue::UnpairedBar64 { index },
+                );
+            }
+        }
+    }
+
+    fn check_bus_numbers(&mut self) {
+        let (Ok(secondary), Ok(subordinate)) =
+            (self.config.read_u8(0x19), self.config.read_u8(0x1A))
+        else {
+            return;
+        };
+
+        if subordinate < secondary {
+            self.push(
+                0x1A,
+                PciConfigSeverity::Error,
+                PciConfigIssue::SubordinateBelowSecondaryBus {
+                    secondary,
+                    subordinate,
+                },
+            );
+        }
+    }
+
+    fn check_capabilities(&mut self, header_type: u8) {
+        let mut pointer_offset = match header_type & 0x7F {
+            PciToCardbusBridgeHeader::ID => 0x14,
+            _ => 0x34,
+        };
+        let mut visited = 0u64;
+
+        while let Ok(pointer) = self.config.read_u8(pointer_offset) {
+            if pointer == 0 {
+                return;
+            }
+
+            if pointer & 0x3 != 0 {
+                self.push(
+                    pointer_offset,
+                    PciConfigSeverity::Warning,
+                    PciConfigIssue::MisalignedCapabilityPointer(pointer as u16),
+                );
+            }
+
+            let offset = pointer & 0xFC;
+
+            if (offset as usize) < PciCapabilityIterator::FIRST_CAPABILITY_OFFSET {
+                self.push(
+                    pointer_offset,
+                    PciConfigSeverity::Error,
+                    PciConfigIssue::CapabilityPointerOutOfRange(pointer as u16),
+                );
+                return;
+            }
+
+            let visited_bit = 1u64 << (offset / 4);
+
+            if visited & visited_bit != 0 {
+                self.push(
+                    pointer_offset,
+                    PciConfigSeverity::Error,
+                    PciConfigIssue::CapabilityLoop(offset as u16),
+                );
+                return;
+            }
+
+            visited |= visited_bit;
+
+            if self.config.size() < offset as usize + 2 {
+                self.push(
+                    pointer_offset,
+                    PciConfigSeverity::Info,
+                    PciConfigIssue::CapabilityBeyondEnd(offset as u16),
+                );
+                return;
+            }
+
+            pointer_offset = offset as usize + 1;
+        }
+    }
+
+    fn check_extended_capabilities(&mut self) {
+        let first = PciExtendedCapabilityIterator::FIRST_EXTENDED_CAPABILITY_OFFSET;
+
+        // Absent extended configuration spaces read either as
+        // all zeros or all ones.
+        match self.config.read_u32(first) {
+            Ok(0) | Ok(0xFFFF_FFFF) | Err(_) => return,
+            Ok(_) => (),
+        }
+
+        let mut offset = first;
+        let mut visited = [0u64; 16];
+
+        while let Ok(header) = self.config.read_u32(offset) {
+            let next = (header >> 20) as u16;
+
+            if next == 0 {
+                return;
+            }
+
+            if next & 0x3 != 0 {
+                self.push(
+                    offset,
+                    PciConfigSeverity::Warning,
+                    PciConfigIssue::MisalignedCapabilityPointer(next),
+                );
+            }
+
+            let next_offset = (next & 0xFFC) as usize;
+
+            if next_offset < first {
+                self.push(
+                    offset,
+                    PciConfigSeverity::Error,
+                    PciConfigIssue::CapabilityPointerOutOfRange(next),
+                );
+                return;
+            }
+
+            let dword = next_offset / 4;
+            let visited_bit = 1u64 << (dword % 64);
+
+            if visited[dword / 64] & visited_bit != 0 {
+                self.push(
+                    offset,
+                    PciConfigSeverity::Error,
+                    PciConfigIssue::CapabilityLoop(next_offset as u16),
+                );
+                return;
+            }
+
+            visited[dword / 64] |= visited_bit;
+
+            if self.config.size() < next_offset + 4 {
+                self.push(
+                    offset,
+                    PciConfigSeverity::Info,
+                    PciConfigIssue::CapabilityBeyondEnd(next_offset as u16),
+                );
+                return;
+            }
+
+            offset = next_offset;
+        }
+    }
+
+    fn check_reserved(&mut self, offset: usize, width: usize, mask: u32, register: &'static str) {
+        let value = match width {
+            1 => self.config.read_u8(offset).map(|v| v as u32),
+            2 => self.config.read_u16(offset).map(|v| v as u32),
+            _ => self.config.read_u32(offset),
+        };
+
+        if let Ok(value) = value {
+            if value & mask != 0 {
+                self.push(
+                    offset,
+                    PciConfigSeverity::Warning,
+                    PciConfigIssue::ReservedBitsSet {
+                        register,
+                        bits: value & mask,
+                    },
+                );
+            }
+        }
+    }
+
+    fn push(&mut self, offset: usize, severity: PciConfigSeverity, issue: PciConfigIssue) {
+        self.findings.push(PciConfigFinding {
+            offset,
+            severity,
+            issue,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixtures_are_sane() {
+        for arch in ["amd64", "aarch64"] {
+            let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("test-data/linux")
+                .join(arch)
+                .join("pci");
+
+            for bus in std::fs::read_dir(root).unwrap() {
+                let bus = bus.unwrap();
+
+                if !bus.file_type().unwrap().is_dir() {
+                    continue;
+                }
+
+                for dev in std::fs::read_dir(bus.path()).unwrap() {
+                    let bytes = std::fs::read(dev.unwrap().path()).unwrap();
+                    let findings = validate_config_space(bytes.as_slice());
+
+                    assert!(
+                        findings
+                            .iter()
+                            .all(|f| f.severity == PciConfigSeverity::Info),
+                        "{findings:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reports_broken_bridges_and_bars() {
+        let mut config = [0u8; 256];
+        config[0x0E] = 0x01;
+        // 64-bit BAR in the last slot of a bridge
+        config[0x14] = 0x04;
+        // Secondary bus 5, subordinate bus 3
+        config[0x19] = 0x05;
+        config[0x1A] = 0x03;
+        // Reserved bits in Bridge Control
+        config[0x3F] = 0x80;
+        // Capability list leaving the standard configuration space
+        config[0x06] = 0x10;
+        config[0x34] = 0x40;
+        config[0x41] = 0x04;
+
+        let findings = validate_config_space(config.as_slice());
+        let issues = findings
+            .iter()
+            .map(|f| (f.offset, f.severity, f.issue.clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            issues,
+            vec![
+                (
+                    0x14,
+                    PciConfigSeverity::Error,
+                    PciConfigIssue::UnpairedBar64 { index: 1 }
+                ),
+                (
+                    0x1A,
+                    PciConfigSeverity::Error,
+                    PciConfigIssue::SubordinateBelowSecondaryBus {
+                        secondary: 5,
+                        subordinate: 3
+                    }
+                ),
+                (
+                    0x3E,
+                    PciConfigSeverity::Warning,
+                    PciConfigIssue::ReservedBitsSet {
+                        register: "Bridge Control",
+                        bits: 0x8000
+                    }
+                ),
+                (
+                    0x41,
+                    PciConfigSeverity::Error,
+                    PciConfigIssue::CapabilityPointerOutOfRange(0x04)
+                ),
+            ]
+        );
+
+        config[0x0E] = 0x05;
+        let findings = validate_config_space(config.as_slice());
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].issue, PciConfigIssue::UnknownHeaderType(0x05));
+        assert_eq!(
+            findings[0].to_string(),
+            "0x00E: error: unknown header type 0x05"
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use crate::{PciInfoError, PciLocation};
+
+use super::{PciConfigAccess, PciExtendedCapability, PciExtendedCapabilityId};
+
+pci_register_bits! {
+    /// The DPC Capability register of the Downstream Port Containment
+    /// capability.
+    pub struct PciDpcCapabilities(u16) {
+        /// The root port implements the RP extensions for DPC, including
+        /// the RP PIO registers.
+        rp_extensions, set_rp_extensions: 5 => "RPExt";
+        /// Poisoned TLP egress blocking is supported.
+        poisoned_tlp_egress_blocking_supported, set_poisoned_tlp_egress_blocking_supported: 6 => "PoisonedTLP";
+        /// DPC can be triggered by software.
+        software_triggering_supported, set_software_triggering_supported: 7 => "SwTrigger";
+        /// ERR_COR signaling on DL_Active is supported.
+        dl_active_err_cor_supported, set_dl_active_err_cor_supported: 12 => "DL_ActiveErr";
+    }
+}
+
+impl PciDpcCapabilities {
+    /// The MSI/MSI-X vector used for the interrupts generated by DPC.
+    pub fn interrupt_message_number(&self) -> u8 {
+        self.field(0, 0x1F) as u8
+    }
+
+    /// The number of dwords of the RP PIO log registers (header, ImpSpec
+    /// and TLP prefix logs).
+    pub fn rp_pio_log_size(&self) -> u8 {
+        self.field(8, 0xF) as u8
+    }
+}
+
+pci_register_bits! {
+    /// The DPC Control register of the Downstream Port Containment
+    /// capability.
+    pub struct PciDpcControl(u16) {
+        /// Completions of requests are completed with Completer Abort
+        /// rather than Unsupported Request while containment is active.
+        completion_control, set_completion_control: 2 => "CompletionCtl";
+        /// An interrupt is generated when DPC is triggered.
+        interrupt_enable, set_interrupt_enable: 3 => "IntEn";
+        /// ERR_COR is signaled when DPC is triggered.
+        err_cor_enable, set_err_cor_enable: 4 => "ErrCor";
+        /// Poisoned TLP egress blocking is enabled.
+        poisoned_tlp_egress_blocking_enable, set_poisoned_tlp_egress_blocking_enable: 5 => "PoisonedTLP";
+        /// Triggers DPC from software (write only).
+        software_trigger, set_software_trigger: 6 => "SwTrigger";
+        /// ERR_COR is signaled when the link becomes DL_Active.
+        dl_active_err_cor_enable, set_dl_active_err_cor_enable: 7 => "DL_ActiveErr";
+        /// DPC signals the system firmware when triggered.
+        sig_sfw_enable, set_sig_sfw_enable: 8 => "SIG_SFW";
+    }
+}
+
+impl PciDpcControl {
+    /// The condition that triggers DPC: 0 if DPC is disabled, 1 for
+    /// ERR_FATAL errors, 2 for ERR_NONFATAL and ERR_FATAL errors.
+    pub fn trigger_enable(&self) -> u8 {
+        self.field(0, 0x3) as u8
+    }
+}
+
+/// The reason that triggered Downstream Port Containment.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PciDpcTriggerReason {
+    /// An unmasked uncorrectable error was detected by the port.
+    UnmaskedUncorrectableError,
+    /// The port received an ERR_NONFATAL message.
+    ErrNonFatal,
+    /// The port received an ERR_FATAL message.
+    ErrFatal,
+    /// The root port detected an RP PIO error.
+    RpPioError,
+    /// DPC was triggered by software.
+    SoftwareTrigger,
+    /// A reserved value of the DPC Trigger Reason Extension field.
+    Unknown(u8),
+}
+
+impl PciDpcTriggerReason {
+    /// Create a `PciDpcTriggerReason` from the values of the DPC Trigger
+    /// Reason and DPC Trigger Reason Extension fields of the DPC Status
+    /// register.
+    pub fn from_codes(reason: u8, extension: u8) -> Self {
+        match (reason, extension) {
+            (0, _) => Self::UnmaskedUncorrectableError,
+            (1, _) => Self::ErrNonFatal,
+            (2, _) => Self::ErrFatal,
+            (_, 0) => Self::RpPioError,
+            (_, 1) => Self::SoftwareTrigger,
+            (_, unk) => Self::Unknown(unk),
+        }
+    }
+}
+
+pci_register_bits! {
+    /// The DPC Status register of the Downstream Port Containment
+    /// capability.
+    pub struct PciDpcStatus(u16) {
+        /// DPC has been triggered and the link is contained.
+        trigger_status, set_trigger_status: 0 => "Trigger";
+        /// A DPC interrupt is pending.
+        interrupt_status, set_interrupt_status: 3 => "IntStatus";
+        /// The root port is still busy with internal activity after DPC
+        /// was triggered.
+        rp_busy, set_rp_busy: 4 => "RPBusy";
+        /// DPC signaled the system firmware.
+        sig_sfw_status, set_sig_sfw_status: 13 => "SIG_SFW";
+    }
+}
+
+impl PciDpcStatus {
+    /// The reason that triggered DPC, from the DPC Trigger Reason (bits
+    /// 2:1) and DPC Trigger Reason Extension (bits 6:5) fields. This is
+    /// meaningful only if [`PciDpcStatus::trigger_status`] is set.
+    pub fn trigger_reason(&self) -> PciDpcTriggerReason {
+        PciDpcTriggerReason::from_codes(self.field(1, 0x3) as u8, self.field(5, 0x3) as u8)
+    }
+
+    /// The bit of the RP PIO Status register corresponding to the first
+    /// RP PIO error that was logged.
+    pub fn rp_pio_first_error_pointer(&self) -> u8 {
+        self.field(8, 0x1F) as u8
+    }
+}
+
+pci_register_bits! {
+    /// An RP PIO error register (status, mask, severity, system error or
+    /// exception) of the Downstream Port Containment capability.
+    pub struct PciDpcRpPioErrors(u32) {
+        /// A configuration request received an Unsupported Request
+        /// completion.
+        cfg_ur_completion, set_cfg_ur_completion: 0 => "CfgUR";
+        /// A configuration request received a Completer Abort completion.
+        cfg_ca_completion, set_cfg_ca_completion: 1 => "CfgCA";
+        /// A configuration request timed out.
+        cfg_completion_timeout, set_cfg_completion_timeout: 2 => "CfgCTO";
+        /// An I/O request received an Unsupported Request completion.
+        io_ur_completion, set_io_ur_completion: 8 => "IoUR";
+        /// An I/O request received a Completer Abort completion.
+        io_ca_completion, set_io_ca_completion: 9 => "IoCA";
+        /// An I/O request timed out.
+        io_completion_timeout, set_io_completion_timeout: 10 => "IoCTO";
+        /// A memory request received an Unsupported Request completion.
+        mem_ur_completion, set_mem_ur_completion: 16 => "MemUR";
+        /// A memory request received a Completer Abort completion.
+        mem_ca_completion, set_mem_ca_completion: 17 => "MemCA";
+        /// A memory request timed out.
+        mem_completion_timeout, set_mem_completion_timeout: 18 => "MemCTO";
+    }
+}
+
+/// The RP PIO registers of the DPC capability, implemented only by root
+/// ports supporting the RP extensions for DPC. They log the errors of the
+/// non-posted requests (PIO) issued by the root port.
+#[derive(Clone, Debug, Default)]
+pub struct PciDpcRpPioRegisters {
+    pub status: PciDpcRpPioErrors,
+    pub mask: PciDpcRpPioErrors,
+    pub severity: PciDpcRpPioErrors,
+    pub system_error: PciDpcRpPioErrors,
+    pub exception: PciDpcRpPioErrors,
+    /// The header of the request that caused the first RP PIO error.
+    pub header_log: [u32; 4],
+}
+
+/// The Downstream Port Containment extended capability (extended capability
+/// ID 001Dh) of a PCI Express root port or switch downstream port. When DPC
+/// is triggered, the port disables its link, containing the error to the
+/// devices below it, until software releases it.
+///
+/// The format of the capability in PCI configuration space is the following.
+///
+///  ```text
+/// +---------+-------------+--------------+----------------+----------------+
+/// | Offset  | Bits 31-24  | Bits 23-16   | Bits 15-8      | Bits 7-0       |
+/// +---------+-------------+--------------+----------------+----------------+
+/// |   0x00  | Next offset | Version      |       Extended capability ID    |
+/// |   0x04  |        DPC Control         |         DPC Capability          |
+/// |   0x08  |    DPC Error Source ID     |           DPC Status            |
+/// |   0x0C  |             RP PIO Status (RP extensions only)               |
+/// |   0x10  |             RP PIO Mask (RP extensions only)                 |
+/// |   0x14  |             RP PIO Severity (RP extensions only)             |
+/// |   0x18  |             RP PIO SysError (RP extensions only)             |
+/// |   0x1C  |             RP PIO Exception (RP extensions only)            |
+/// |   0x20  |       RP PIO Header Log (4 registers, RP extensions only)    |
+/// |   0x30  |       RP PIO ImpSpec Log and TLP Prefix Log (optional)       |
+/// +---------+-------------+--------------+----------------+----------------+
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PciDpcCapability {
+    pub capabilities: PciDpcCapabilities,
+    pub control: PciDpcControl,
+    pub status: PciDpcStatus,
+    /// The raw DPC Error Source ID register; see
+    /// [`PciDpcCapability::error_source`].
+    pub error_source_id: u16,
+    /// The RP PIO registers, present only if the port implements the RP
+    /// extensions for DPC.
+    pub rp_pio: Option<PciDpcRpPioRegisters>,
+}
+
+impl PciDpcCapability {
+    pub const ID: PciExtendedCapabilityId = PciExtendedCapabilityId::DownstreamPortContainment;
+
+    /// Decodes a DPC capability found in the extended capability list.
+    pub fn with_capability<A: PciConfigAccess + ?Sized>(
+        cap: &PciExtendedCapability<'_, A>,
+    ) -> Result<Self, PciInfoError> {
+        cap.assert_id(Self::ID)?;
+
+        let capabilities = PciDpcCapabilities(cap.read_u16(0x04)?);
+
+        let rp_pio = if capabilities.rp_extensions() {
+            Some(PciDpcRpPioRegisters {
+                status: PciDpcRpPioErrors(cap.read_u32(0x0C)?),
+                mask: PciDpcRpPioErrors(cap.read_u32(0x10)?),
+                severity: PciDpcRpPioErrors(cap.read_u32(0x14)?),
+                system_error: PciDpcRpPioErrors(cap.read_u32(0x18)?),
+                exception: PciDpcRpPioErrors(cap.read_u32(0x1C)?),
+                header_log: [
+                    cap.read_u32(0x20)?,
+                    cap.read_u32(0x24)?,
+                    cap.read_u32(0x28)?,
+                    cap.read_u32(0x2C)?,
+                ],
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            capabilities,
+            control: PciDpcControl(cap.read_u16(0x06)?),
+            status: PciDpcStatus(cap.read_u16(0x08)?),
+            error_source_id: cap.read_u16(0x0A)?,
+            rp_pio,
+        })
+    }
+
+    /// The length of the capability in configuration space, which depends
+    /// on the RP extensions and on the size of the RP PIO logs.
+    pub fn length(&self) -> usize {
+        match self.rp_pio {
+            Some(_) => 0x20 + self.capabilities.rp_pio_log_size().max(4) as usize * 4,
+            None => 0x0C,
+        }
+    }
+
+    /// Returns the reason that triggered DPC, or `None` if DPC is not
+    /// currently triggered.
+    pub fn trigger_reason(&self) -> Option<PciDpcTriggerReason> {
+        self.status
+            .trigger_status()
+            .then(|| self.status.trigger_reason())
+    }
+
+    /// Returns the location of the device that sent the ERR_FATAL or
+    /// ERR_NONFATAL message that triggered DPC, or `None` if DPC was not
+    /// triggered by an error message. The location does not include the
+    /// PCI segment, which is the same of the port.
+    pub fn error_source(&self) -> Option<PciLocation> {
+        match self.trigger_reason()? {
+            PciDpcTriggerReason::ErrNonFatal | PciDpcTriggerReason::ErrFatal => {
+                Some(PciLocation::with_bdf_u16(self.error_source_id))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_headers::test_support::decode_extended_capability;
+
+    fn decode(regs: &[u32]) -> PciDpcCapability {
+        let body = regs
+            .iter()
+            .flat_map(|r| r.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        decode_extended_capability(
+            PciDpcCapability::ID,
+            &body,
+            PciDpcCapability::with_capability,
+        )
+    }
+
+    #[test]
+    fn decodes_err_fatal_trigger_and_source() {
+        // Switch downstream port triggered by an ERR_FATAL from 05:00.0
+        let dpc = decode(&[0x000A_0080, 0x0500_0005]);
+
+        assert!(dpc.rp_pio.is_none());
+        assert_eq!(dpc.length(), 0x0C);
+        assert_eq!(dpc.control.trigger_enable(), 2);
+        assert!(dpc.control.interrupt_enable());
+        assert_eq!(dpc.trigger_reason(), Some(PciDpcTriggerReason::ErrFatal));
+        assert_eq!(
+            dpc.error_source(),
+            Some(PciLocation::with_bdf(5, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn decodes_rp_pio_registers() {
+        // Root port with RP extensions, triggered by an RP PIO error
+        // (reason 3, extension 0) caused by a memory completion timeout.
+        let dpc = decode(&[
+            0x0001_04A0,
+            0x0000_1207,
+            0x0004_0000,
+            0x0000_0000,
+            0x0007_0707,
+            0x0000_0000,
+            0x0000_0000,
+            0x2000_0001,
+            0x0000_010F,
+            0xFE00_0000,
+            0x0000_0000,
+        ]);
+
+        let rp_pio = dpc.rp_pio.as_ref().unwrap();
+        assert!(dpc.capabilities.rp_extensions());
+        assert_eq!(dpc.trigger_reason(), Some(PciDpcTriggerReason::RpPioError));
+        assert_eq!(dpc.status.rp_pio_first_error_pointer(), 18);
+        assert_eq!(dpc.error_source(), None);
+        assert!(rp_pio.status.mem_completion_timeout());
+        assert!(rp_pio.severity.mem_completion_timeout());
+        assert_eq!(
+            rp_pio.header_log,
+            [0x2000_0001, 0x0000_010F, 0xFE00_0000, 0]
+        );
+        assert_eq!(dpc.length(), 0x30);
+    }
+}
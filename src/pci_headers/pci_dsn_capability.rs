@@ -0,0 +1,78 @@
+use crate::PciInfoError;
+
+use super::{PciConfigAccess, PciExtendedCapability, PciExtendedCapabilityId};
+
+/// The Device Serial Number extended capability (extended capability ID
+/// 0003h), containing a 64-bit serial number that uniquely identifies a
+/// device, usually derived from an IEEE EUI-64 identifier.
+///
+/// Unlike the location of a device, the serial number does not change when
+/// the device is moved to a different slot. All the functions of a
+/// multi-function device report the same serial number.
+///
+/// The format of the capability in PCI configuration space is the following.
+///
+///  ```text
+/// +---------+-------------+--------------+----------------+----------------+
+/// | Offset  | Bits 31-24  | Bits 23-16   | Bits 15-8      | Bits 7-0       |
+/// +---------+-------------+--------------+----------------+----------------+
+/// |   0x00  | Next offset | Version      |       Extended capability ID    |
+/// |   0x04  |              Serial Number Register (Lower DW)               |
+/// |   0x08  |              Serial Number Register (Upper DW)               |
+/// +---------+-------------+--------------+----------------+----------------+
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PciDeviceSerialNumberCapability {
+    /// The 64-bit serial number of the device.
+    pub serial_number: u64,
+}
+
+impl PciDeviceSerialNumberCapability {
+    pub const ID: PciExtendedCapabilityId = PciExtendedCapabilityId::DeviceSerialNumber;
+    pub const LENGTH: usize = 0x0C;
+
+    /// Decodes a Device Serial Number capability found in the extended
+    /// capability list.
+    pub fn with_capability<A: PciConfigAccess + ?Sized>(
+        cap: &PciExtendedCapability<'_, A>,
+    ) -> Result<Self, PciInfoError> {
+        cap.assert_id(Self::ID)?;
+
+        let lower = cap.read_u32(0x04)? as u64;
+        let upper = cap.read_u32(0x08)? as u64;
+
+        Ok(Self {
+            serial_number: (upper << 32) | lower,
+        })
+    }
+
+    /// Formats the serial number as an EUI-64 string, most significant byte
+    /// first, in the same format used by Linux (e.g.
+    /// `00-1b-21-ff-ff-a1-b2-c3`).
+    pub fn eui64_string(&self) -> String {
+        self.serial_number
+            .to_be_bytes()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_headers::test_support::decode_extended_capability;
+
+    #[test]
+    fn decodes_serial_number() {
+        let dsn = decode_extended_capability(
+            PciDeviceSerialNumberCapability::ID,
+            &[0xc3, 0xb2, 0xa1, 0xff, 0xff, 0x21, 0x1b, 0x00],
+            PciDeviceSerialNumberCapability::with_capability,
+        );
+
+        assert_eq!(dsn.serial_number, 0x001B_21FF_FFA1_B2C3);
+        assert_eq!(dsn.eui64_string(), "00-1b-21-ff-ff-a1-b2-c3");
+    }
+}
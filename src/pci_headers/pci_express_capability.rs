@@ -0,0 +1,845 @@
+use crate::PciInfoError;
+
+use super::pci_config_buffer::{write_u16_at, write_u32_at};
+use super::{PciCapability, PciCapabilityId, PciConfigAccess};
+
+/// The type of a PCI Express function, as reported in the
+/// PCI Express Capabilities register.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PciExpressDevicePortType {
+    /// PCI Express endpoint (0h).
+    Endpoint,
+    /// Legacy PCI Express endpoint (1h).
+    LegacyEndpoint,
+    /// Root port of a root complex (4h).
+    RootPort,
+    /// Upstream port of a switch (5h).
+    UpstreamSwitchPort,
+    /// Downstream port of a switch (6h).
+    DownstreamSwitchPort,
+    /// PCI Express to PCI/PCI-X bridge (7h).
+    PcieToPciBridge,
+    /// PCI/PCI-X to PCI Express bridge (8h).
+    PciToPcieBridge,
+    /// Root complex integrated endpoint, also known as RCiEP (9h).
+    RootComplexIntegratedEndpoint,
+    /// Root complex event collector (Ah).
+    RootComplexEventCollector,
+    /// Reserved values.
+    Unknown(u8),
+}
+
+impl PciExpressDevicePortType {
+    /// Create a `PciExpressDevicePortType` from the `u8` value that it
+    /// represents
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            0x0 => Self::Endpoint,
+            0x1 => Self::LegacyEndpoint,
+            0x4 => Self::RootPort,
+            0x5 => Self::UpstreamSwitchPort,
+            0x6 => Self::DownstreamSwitchPort,
+            0x7 => Self::PcieToPciBridge,
+            0x8 => Self::PciToPcieBridge,
+            0x9 => Self::RootComplexIntegratedEndpoint,
+            0xa => Self::RootComplexEventCollector,
+            unk => Self::Unknown(unk),
+        }
+    }
+
+    /// Gets the `u8` value that this `PciExpressDevicePortType` represents
+    pub fn as_code(&self) -> u8 {
+        match self {
+            Self::Endpoint => 0x0,
+            Self::LegacyEndpoint => 0x1,
+            Self::RootPort => 0x4,
+            Self::UpstreamSwitchPort => 0x5,
+            Self::DownstreamSwitchPort => 0x6,
+            Self::PcieToPciBridge => 0x7,
+            Self::PciToPcieBridge => 0x8,
+            Self::RootComplexIntegratedEndpoint => 0x9,
+            Self::RootComplexEventCollector => 0xa,
+            Self::Unknown(unk) => *unk,
+        }
+    }
+
+    /// Returns true if this function is a downstream port (a root port
+    /// or a switch downstream port).
+    pub fn is_downstream_port(&self) -> bool {
+        matches!(self, Self::RootPort | Self::DownstreamSwitchPort)
+    }
+}
+
+/// The speed of a PCI Express link.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum PciExpressLinkSpeed {
+    /// 2.5 GT/s (PCI Express 1.x)
+    Gt2_5,
+    /// 5 GT/s (PCI Express 2.x)
+    Gt5,
+    /// 8 GT/s (PCI Express 3.x)
+    Gt8,
+    /// 16 GT/s (PCI Express 4.x)
+    Gt16,
+    /// 32 GT/s (PCI Express 5.x)
+    Gt32,
+    /// 64 GT/s (PCI Express 6.x)
+    Gt64,
+    /// Reserved values.
+    Unknown(u8),
+}
+
+impl PciExpressLinkSpeed {
+    /// Create a `PciExpressLinkSpeed` from the encoded value of the link
+    /// speed fields of the link registers.
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            1 => Self::Gt2_5,
+            2 => Self::Gt5,
+            3 => Self::Gt8,
+            4 => Self::Gt16,
+            5 => Self::Gt32,
+            6 => Self::Gt64,
+            unk => Self::Unknown(unk),
+        }
+    }
+
+    /// Gets the encoded value that this `PciExpressLinkSpeed` represents
+    pub fn as_code(&self) -> u8 {
+        match self {
+            Self::Gt2_5 => 1,
+            Self::Gt5 => 2,
+            Self::Gt8 => 3,
+            Self::Gt16 => 4,
+            Self::Gt32 => 5,
+            Self::Gt64 => 6,
+            Self::Unknown(unk) => *unk,
+        }
+    }
+
+    /// Returns the transfer rate of the link, in megatransfers per second.
+    pub fn megatransfers_per_sec(&self) -> Option<u32> {
+        match self {
+            Self::Gt2_5 => Some(2_500),
+            Self::Gt5 => Some(5_000),
+            Self::Gt8 => Some(8_000),
+            Self::Gt16 => Some(16_000),
+            Self::Gt32 => Some(32_000),
+            Self::Gt64 => Some(64_000),
+            Self::Unknown(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for PciExpressLinkSpeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Gt2_5 => write!(f, "2.5GT/s"),
+            Self::Unknown(unk) => write!(f, "unknown speed ({unk})"),
+            speed => write!(
+                f,
+                "{}GT/s",
+                speed.megatransfers_per_sec().unwrap_or_default() / 1000
+            ),
+        }
+    }
+}
+
+/// The width of a PCI Express link.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum PciExpressLinkWidth {
+    /// One lane
+    X1,
+    /// Two lanes
+    X2,
+    /// Four lanes
+    X4,
+    /// Eight lanes
+    X8,
+    /// Twelve lanes
+    X12,
+    /// Sixteen lanes
+    X16,
+    /// Thirty-two lanes
+    X32,
+    /// Reserved values (including zero, reported by links that are down).
+    Unknown(u8),
+}
+
+impl PciExpressLinkWidth {
+    /// Create a `PciExpressLinkWidth` from the encoded value of the link
+    /// width fields of the link registers.
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            1 => Self::X1,
+            2 => Self::X2,
+            4 => Self::X4,
+            8 => Self::X8,
+            12 => Self::X12,
+            16 => Self::X16,
+            32 => Self::X32,
+            unk => Self::Unknown(unk),
+        }
+    }
+
+    /// Gets the encoded value that this `PciExpressLinkWidth` represents
+    pub fn as_code(&self) -> u8 {
+        match self {
+            Self::X1 => 1,
+            Self::X2 => 2,
+            Self::X4 => 4,
+            Self::X8 => 8,
+            Self::X12 => 12,
+            Self::X16 => 16,
+            Self::X32 => 32,
+            Self::Unknown(unk) => *unk,
+        }
+    }
+
+    /// Returns the number of lanes of the link, if valid.
+    pub fn lanes(&self) -> Option<u8> {
+        match self {
+            Self::Unknown(_) => None,
+            width => Some(width.as_code()),
+        }
+    }
+}
+
+impl std::fmt::Display for PciExpressLinkWidth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unknown(unk) => write!(f, "unknown width ({unk})"),
+            width => write!(f, "x{}", width.as_code()),
+        }
+    }
+}
+
+pci_register_bits! {
+    /// The PCI Express Capabilities register.
+    pub struct PciExpressCapabilitiesRegister(u16) {
+        /// The link of this port is connected to a slot.
+        slot_implemented, set_slot_implemented: 8 => "SlotImplemented";
+    }
+}
+
+impl PciExpressCapabilitiesRegister {
+    /// The version of the PCI Express capability structure.
+    pub fn version(&self) -> u8 {
+        self.field(0, 0xF) as u8
+    }
+
+    /// Sets the version of the PCI Express capability structure.
+    pub fn set_version(&mut self, version: u8) {
+        self.set_field(0, 0xF, version as u16)
+    }
+
+    /// The type of the PCI Express function.
+    pub fn device_port_type(&self) -> PciExpressDevicePortType {
+        PciExpressDevicePortType::from_code(self.field(4, 0xF) as u8)
+    }
+
+    /// Sets the type of the PCI Express function.
+    pub fn set_device_port_type(&mut self, port_type: PciExpressDevicePortType) {
+        self.set_field(4, 0xF, port_type.as_code() as u16)
+    }
+
+    /// The MSI/MSI-X vector used for interrupts generated by this capability.
+    pub fn interrupt_message_number(&self) -> u8 {
+        self.field(9, 0x1F) as u8
+    }
+}
+
+pci_register_bits! {
+    /// The Device Capabilities register of the PCI Express capability.
+    pub struct PciExpressDeviceCapabilities(u32) {
+        /// 8-bit tags can be used as requester.
+        extended_tag_field_supported, set_extended_tag_field_supported: 5 => "ExtTag";
+        /// Role-based error reporting is implemented.
+        role_based_error_reporting, set_role_based_error_reporting: 15 => "RBE";
+        /// ERR_COR subclass messages are supported.
+        err_cor_subclass_capable, set_err_cor_subclass_capable: 16 => "ErrCorSubclass";
+        /// Function Level Reset is supported.
+        function_level_reset_capable, set_function_level_reset_capable: 28 => "FLReset";
+    }
+}
+
+impl PciExpressDeviceCapabilities {
+    /// The maximum supported TLP payload size, in bytes, or `None` if the
+    /// field contains a reserved encoding.
+    pub fn max_payload_size_supported(&self) -> Option<u16> {
+        size_from_code(self.field(0, 0x7))
+    }
+
+    /// The number of function number bits usable for phantom functions.
+    pub fn phantom_functions_supported(&self) -> u8 {
+        self.field(3, 0x3) as u8
+    }
+
+    /// The encoded acceptable latency of the endpoint for the L0s state.
+    pub fn endpoint_l0s_acceptable_latency(&self) -> u8 {
+        self.field(6, 0x7) as u8
+    }
+
+    /// The encoded acceptable latency of the endpoint for the L1 state.
+    pub fn endpoint_l1_acceptable_latency(&self) -> u8 {
+        self.field(9, 0x7) as u8
+    }
+
+    /// The captured slot power limit, in milliwatts.
+    pub fn captured_slot_power_limit_mw(&self) -> u32 {
+        slot_power_limit_mw(self.field(18, 0xFF), self.field(26, 0x3))
+    }
+}
+
+pci_register_bits! {
+    /// The Device Control register of the PCI Express capability.
+    pub struct PciExpressDeviceControl(u16) {
+        /// Correctable errors are reported.
+        correctable_error_reporting, set_correctable_error_reporting: 0 => "CorrErr";
+        /// Non-fatal errors are reported.
+        non_fatal_error_reporting, set_non_fatal_error_reporting: 1 => "NonFatalErr";
+        /// Fatal errors are reported.
+        fatal_error_reporting, set_fatal_error_reporting: 2 => "FatalErr";
+        /// Unsupported requests are reported.
+        unsupported_request_reporting, set_unsupported_request_reporting: 3 => "UnsupReq";
+        /// Relaxed ordering is enabled.
+        relaxed_ordering, set_relaxed_ordering: 4 => "RlxdOrd";
+        /// 8-bit tags are enabled.
+        extended_tag_field, set_extended_tag_field: 8 => "ExtTag";
+        /// Phantom functions are enabled.
+        phantom_functions, set_phantom_functions: 9 => "PhantFunc";
+        /// Aux power is used for power management.
+        aux_power_pm, set_aux_power_pm: 10 => "AuxPwr";
+        /// No snoop is enabled.
+        no_snoop, set_no_snoop: 11 => "NoSnoop";
+        /// Bridge configuration retry enable (bridges), or initiate
+        /// Function Level Reset (endpoints).
+        bridge_retry_or_flr, set_bridge_retry_or_flr: 15 => "BrConfRtry/FLReset";
+    }
+}
+
+impl PciExpressDeviceControl {
+    /// The maximum TLP payload size in use, in bytes, or `None` if the
+    /// field contains a reserved encoding.
+    pub fn max_payload_size(&self) -> Option<u16> {
+        size_from_code(self.field(5, 0x7) as u32)
+    }
+
+    /// The maximum read request size in use, in bytes, or `None` if the
+    /// field contains a reserved encoding.
+    pub fn max_read_request_size(&self) -> Option<u16> {
+        size_from_code(self.field(12, 0x7) as u32)
+    }
+}
+
+// Decodes a Max_Payload_Size or Max_Read_Request_Size field, which encodes
+// sizes from 128 to 4096 bytes; encodings 6 and 7 are reserved.
+fn size_from_code(code: u32) -> Option<u16> {
+    match code {
+        0..=5 => Some(128 << code),
+        _ => None,
+    }
+}
+
+pci_register_bits! {
+    /// The Device Status register of the PCI Express capability.
+    pub struct PciExpressDeviceStatus(u16) {
+        /// A correctable error was detected.
+        correctable_error_detected, set_correctable_error_detected: 0 => "CorrErr";
+        /// A non-fatal error was detected.
+        non_fatal_error_detected, set_non_fatal_error_detected: 1 => "NonFatalErr";
+        /// A fatal error was detected.
+        fatal_error_detected, set_fatal_error_detected: 2 => "FatalErr";
+        /// An unsupported request was received.
+        unsupported_request_detected, set_unsupported_request_detected: 3 => "UnsupReq";
+        /// Aux power is detected.
+        aux_power_detected, set_aux_power_detected: 4 => "AuxPwr";
+        /// Non-posted requests are still pending completion.
+        transactions_pending, set_transactions_pending: 5 => "TransPend";
+        /// An emergency power reduction request was detected.
+        emergency_power_reduction_detected, set_emergency_power_reduction_detected: 6 => "EmergencyPowerReduction";
+    }
+}
+
+pci_register_bits! {
+    /// The Link Capabilities register of the PCI Express capability.
+    pub struct PciExpressLinkCapabilities(u32) {
+        /// Reference clock removal is supported in L1 and L2/L3 Ready.
+        clock_power_management, set_clock_power_management: 18 => "ClockPM";
+        /// Surprise down errors are reported.
+        surprise_down_error_reporting, set_surprise_down_error_reporting: 19 => "Surprise";
+        /// The Data Link Layer Link Active state is reported.
+        data_link_layer_active_reporting, set_data_link_layer_active_reporting: 20 => "LLActRep";
+        /// Link bandwidth notifications are supported.
+        link_bandwidth_notification, set_link_bandwidth_notification: 21 => "BwNot";
+        /// ASPM optionality compliance.
+        aspm_optionality_compliance, set_aspm_optionality_compliance: 22 => "ASPMOptComp";
+    }
+}
+
+impl PciExpressLinkCapabilities {
+    /// The maximum speed of the link.
+    pub fn max_link_speed(&self) -> PciExpressLinkSpeed {
+        PciExpressLinkSpeed::from_code(self.field(0, 0xF) as u8)
+    }
+
+    /// Sets the maximum speed of the link.
+    pub fn set_max_link_speed(&mut self, speed: PciExpressLinkSpeed) {
+        self.set_field(0, 0xF, speed.as_code() as u32)
+    }
+
+    /// The maximum width of the link.
+    pub fn max_link_width(&self) -> PciExpressLinkWidth {
+        PciExpressLinkWidth::from_code(self.field(4, 0x3F) as u8)
+    }
+
+    /// Sets the maximum width of the link.
+    pub fn set_max_link_width(&mut self, width: PciExpressLinkWidth) {
+        self.set_field(4, 0x3F, width.as_code() as u32)
+    }
+
+    /// The supported Active State Power Management states: bit 0 is L0s,
+    /// bit 1 is L1.
+    pub fn aspm_support(&self) -> u8 {
+        self.field(10, 0x3) as u8
+    }
+
+    /// The encoded exit latency from L0s.
+    pub fn l0s_exit_latency(&self) -> u8 {
+        self.field(12, 0x7) as u8
+    }
+
+    /// The encoded exit latency from L1.
+    pub fn l1_exit_latency(&self) -> u8 {
+        self.field(15, 0x7) as u8
+    }
+
+    /// The port number of the link.
+    pub fn port_number(&self) -> u8 {
+        self.field(24, 0xFF) as u8
+    }
+}
+
+pci_register_bits! {
+    /// The Link Control register of the PCI Express capability.
+    pub struct PciExpressLinkControl(u16) {
+        /// Read completion boundary is 128 bytes (64 bytes if clear).
+        read_completion_boundary_128, set_read_completion_boundary_128: 3 => "RCB";
+        /// The link is disabled.
+        link_disable, set_link_disable: 4 => "LnkDisable";
+        /// Link retraining has been requested.
+        retrain_link, set_retrain_link: 5 => "RetrainLnk";
+        /// A common reference clock is used on both ends of the link.
+        common_clock_configuration, set_common_clock_configuration: 6 => "CommClk";
+        /// Extended synch is enabled.
+        extended_synch, set_extended_synch: 7 => "ExtSynch";
+        /// Clock power management is enabled.
+        enable_clock_power_management, set_enable_clock_power_management: 8 => "ClockPM";
+        /// Hardware autonomous width changes are disabled.
+        hw_autonomous_width_disable, set_hw_autonomous_width_disable: 9 => "AutWidDis";
+        /// Link bandwidth management interrupts are enabled.
+        link_bandwidth_management_interrupt, set_link_bandwidth_management_interrupt: 10 => "BWInt";
+        /// Link autonomous bandwidth interrupts are enabled.
+        link_autonomous_bandwidth_interrupt, set_link_autonomous_bandwidth_interrupt: 11 => "AutBWInt";
+    }
+}
+
+impl PciExpressLinkControl {
+    /// The enabled Active State Power Management states: bit 0 is L0s,
+    /// bit 1 is L1.
+    pub fn aspm_control(&self) -> u8 {
+        self.field(0, 0x3) as u8
+    }
+}
+
+pci_register_bits! {
+    /// The Link Status register of the PCI Express capability.
+    pub struct PciExpressLinkStatus(u16) {
+        /// The link is training.
+        link_training, set_link_training: 11 => "Train";
+        /// The slot uses the reference clock of the platform.
+        slot_clock_configuration, set_slot_clock_configuration: 12 => "SlotClk";
+        /// The Data Link Layer is in the DL_Active state.
+        data_link_layer_link_active, set_data_link_layer_link_active: 13 => "DLActive";
+        /// The link bandwidth changed because of management.
+        link_bandwidth_management_status, set_link_bandwidth_management_status: 14 => "BWMgmt";
+        /// The link bandwidth changed autonomously.
+        link_autonomous_bandwidth_status, set_link_autonomous_bandwidth_status: 15 => "ABWMgmt";
+    }
+}
+
+impl PciExpressLinkStatus {
+    /// The negotiated speed of the link.
+    pub fn current_link_speed(&self) -> PciExpressLinkSpeed {
+        PciExpressLinkSpeed::from_code(self.field(0, 0xF) as u8)
+    }
+
+    /// Sets the negotiated speed of the link.
+    pub fn set_current_link_speed(&mut self, speed: PciExpressLinkSpeed) {
+        self.set_field(0, 0xF, speed.as_code() as u16)
+    }
+
+    /// The negotiated width of the link.
+    pub fn negotiated_link_width(&self) -> PciExpressLinkWidth {
+        PciExpressLinkWidth::from_code(self.field(4, 0x3F) as u8)
+    }
+
+    /// Sets the negotiated width of the link.
+    pub fn set_negotiated_link_width(&mut self, width: PciExpressLinkWidth) {
+        self.set_field(4, 0x3F, width.as_code() as u16)
+    }
+}
+
+pci_register_bits! {
+    /// The Slot Capabilities register of the PCI Express capability.
+    pub struct PciExpressSlotCapabilities(u32) {
+        /// An attention button is present.
+        attention_button_present, set_attention_button_present: 0 => "AttnBtn";
+        /// A power controller is present.
+        power_controller_present, set_power_controller_present: 1 => "PwrCtrl";
+        /// A Manually-operated Retention Latch sensor is present.
+        mrl_sensor_present, set_mrl_sensor_present: 2 => "MRL";
+        /// An attention indicator is present.
+        attention_indicator_present, set_attention_indicator_present: 3 => "AttnInd";
+        /// A power indicator is present.
+        power_indicator_present, set_power_indicator_present: 4 => "PwrInd";
+        /// Adapters can be removed without prior notification.
+        hot_plug_surprise, set_hot_plug_surprise: 5 => "HotPlugSurprise";
+        /// The slot is hot-plug capable.
+        hot_plug_capable, set_hot_plug_capable: 6 => "HotPlug";
+        /// An electromechanical interlock is present.
+        electromechanical_interlock_present, set_electromechanical_interlock_present: 17 => "Interlock";
+        /// The slot does not generate command completed events.
+        no_command_completed_support, set_no_command_completed_support: 18 => "NoCompl";
+    }
+}
+
+impl PciExpressSlotCapabilities {
+    /// The maximum power that can be supplied by the slot, in milliwatts.
+    pub fn slot_power_limit_mw(&self) -> u32 {
+        slot_power_limit_mw(self.field(7, 0xFF), self.field(15, 0x3))
+    }
+
+    /// The physical slot number of the slot.
+    pub fn physical_slot_number(&self) -> u16 {
+        self.field(19, 0x1FFF) as u16
+    }
+}
+
+pci_register_bits! {
+    /// The Slot Control register of the PCI Express capability.
+    pub struct PciExpressSlotControl(u16) {
+        /// Attention button pressed events are enabled.
+        attention_button_pressed_enable, set_attention_button_pressed_enable: 0 => "AttnBtn";
+        /// Power fault detected events are enabled.
+        power_fault_detected_enable, set_power_fault_detected_enable: 1 => "PwrFlt";
+        /// MRL sensor changed events are enabled.
+        mrl_sensor_changed_enable, set_mrl_sensor_changed_enable: 2 => "MRL";
+        /// Presence detect changed events are enabled.
+        presence_detect_changed_enable, set_presence_detect_changed_enable: 3 => "PresDet";
+        /// Command completed interrupts are enabled.
+        command_completed_interrupt_enable, set_command_completed_interrupt_enable: 4 => "CmdCplt";
+        /// Hot-plug interrupts are enabled.
+        hot_plug_interrupt_enable, set_hot_plug_interrupt_enable: 5 => "HPIrq";
+        /// The power controller is off.
+        power_controller_off, set_power_controller_off: 10 => "PwrOff";
+        /// Toggles the electromechanical interlock.
+        electromechanical_interlock_control, set_electromechanical_interlock_control: 11 => "Interlock";
+        /// Data Link Layer state changed events are enabled.
+        data_link_layer_state_changed_enable, set_data_link_layer_state_changed_enable: 12 => "LinkChg";
+    }
+}
+
+impl PciExpressSlotControl {
+    /// The control of the attention indicator: 1 is on, 2 is blink, 3 is off.
+    pub fn attention_indicator_control(&self) -> u8 {
+        self.field(6, 0x3) as u8
+    }
+
+    /// The control of the power indicator: 1 is on, 2 is blink, 3 is off.
+    pub fn power_indicator_control(&self) -> u8 {
+        self.field(8, 0x3) as u8
+    }
+}
+
+pci_register_bits! {
+    /// The Slot Status register of the PCI Express capability.
+    pub struct PciExpressSlotStatus(u16) {
+        /// The attention button was pressed.
+        attention_button_pressed, set_attention_button_pressed: 0 => "AttnBtn";
+        /// A power fault was detected.
+        power_fault_detected, set_power_fault_detected: 1 => "PowerFlt";
+        /// The MRL sensor state changed.
+        mrl_sensor_changed, set_mrl_sensor_changed: 2 => "MRL";
+        /// The presence detect state changed.
+        presence_detect_changed, set_presence_detect_changed: 3 => "PresDet";
+        /// A hot-plug command completed.
+        command_completed, set_command_completed: 4 => "CmdCplt";
+        /// The MRL is open.
+        mrl_sensor_open, set_mrl_sensor_open: 5 => "MRLOpen";
+        /// An adapter is present in the slot.
+        presence_detect_state, set_presence_detect_state: 6 => "PresDetState";
+        /// The electromechanical interlock is engaged.
+        electromechanical_interlock_engaged, set_electromechanical_interlock_engaged: 7 => "Interlock";
+        /// The Data Link Layer state changed.
+        data_link_layer_state_changed, set_data_link_layer_state_changed: 8 => "LinkChg";
+    }
+}
+
+fn slot_power_limit_mw(value: u32, scale: u32) -> u32 {
+    match scale {
+        0 => value * 1000,
+        1 => value * 100,
+        2 => value * 10,
+        _ => value,
+    }
+}
+
+/// The PCI Express capability (capability ID 10h) of a PCI Express function.
+///
+/// Registers that are not implemented by a function (e.g. the slot registers
+/// of an endpoint, or the registers introduced in version 2 of the capability
+/// when parsing a version 1 capability) are read as zero.
+///
+/// The format of the capability in PCI configuration space is the following.
+///
+///  ```text
+/// +---------+-------------+--------------+----------------+----------------+
+/// | Offset  | Bits 31-24  | Bits 23-16   | Bits 15-8      | Bits 7-0       |
+/// +---------+-------------+--------------+----------------+----------------+
+/// |   0x00  | PCI Express Capabilities   | Next pointer   | Capability ID  |
+/// |   0x04  |                  Device Capabilities                         |
+/// |   0x08  |       Device Status        |         Device Control          |
+/// |   0x0C  |                   Link Capabilities                          |
+/// |   0x10  |        Link Status         |          Link Control           |
+/// |   0x14  |                   Slot Capabilities                          |
+/// |   0x18  |        Slot Status         |          Slot Control           |
+/// |   0x1C  |     Root Capabilities      |          Root Control           |
+/// |   0x20  |                      Root Status                             |
+/// |   0x24  |                  Device Capabilities 2                       |
+/// |   0x28  |      Device Status 2       |        Device Control 2         |
+/// |   0x2C  |                   Link Capabilities 2                        |
+/// |   0x30  |       Link Status 2        |         Link Control 2          |
+/// |   0x34  |                   Slot Capabilities 2                        |
+/// |   0x38  |       Slot Status 2        |         Slot Control 2          |
+/// +---------+-------------+--------------+----------------+----------------+
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PciExpressCapability {
+    pub capabilities: PciExpressCapabilitiesRegister,
+    pub device_capabilities: PciExpressDeviceCapabilities,
+    pub device_control: PciExpressDeviceControl,
+    pub device_status: PciExpressDeviceStatus,
+    pub link_capabilities: PciExpressLinkCapabilities,
+    pub link_control: PciExpressLinkControl,
+    pub link_status: PciExpressLinkStatus,
+    pub slot_capabilities: PciExpressSlotCapabilities,
+    pub slot_control: PciExpressSlotControl,
+    pub slot_status: PciExpressSlotStatus,
+    pub root_control: u16,
+    pub root_capabilities: u16,
+    pub root_status: u32,
+    pub device_capabilities_2: u32,
+    pub device_control_2: u16,
+    pub device_status_2: u16,
+    pub link_capabilities_2: u32,
+    pub link_control_2: u16,
+    pub link_status_2: u16,
+    pub slot_capabilities_2: u32,
+    pub slot_control_2: u16,
+    pub slot_status_2: u16,
+}
+
+impl PciExpressCapability {
+    pub const ID: PciCapabilityId = PciCapabilityId::PciExpress;
+    /// The length of a version 1 PCI Express capability.
+    pub const V1_LENGTH: usize = 0x24;
+    /// The length of a version 2 PCI Express capability.
+    pub const V2_LENGTH: usize = 0x3C;
+
+    /// Decodes a PCI Express capability found in the capability list.
+    pub fn with_capability<A: PciConfigAccess + ?Sized>(
+        cap: &PciCapability<'_, A>,
+    ) -> Result<Self, PciInfoError> {
+        cap.assert_id(Self::ID)?;
+
+        let capabilities = PciExpressCapabilitiesRegister(cap.read_u16(0x02)?);
+        let has_v2_registers = capabilities.version() >= 2;
+        let read_v2_u16 = |offset| match has_v2_registers {
+            true => cap.read_u16(offset),
+            false => Ok(0),
+        };
+        let read_v2_u32 = |offset| match has_v2_registers {
+            true => cap.read_u32(offset),
+            false => Ok(0),
+        };
+
+        Ok(Self {
+            capabilities,
+            device_capabilities: PciExpressDeviceCapabilities(cap.read_u32(0x04)?),
+            device_control: PciExpressDeviceControl(cap.read_u16(0x08)?),
+            device_status: PciExpressDeviceStatus(cap.read_u16(0x0A)?),
+            link_capabilities: PciExpressLinkCapabilities(cap.read_u32(0x0C)?),
+            link_control: PciExpressLinkControl(cap.read_u16(0x10)?),
+            link_status: PciExpressLinkStatus(cap.read_u16(0x12)?),
+            slot_capabilities: PciExpressSlotCapabilities(cap.read_u32(0x14)?),
+            slot_control: PciExpressSlotControl(cap.read_u16(0x18)?),
+            slot_status: PciExpressSlotStatus(cap.read_u16(0x1A)?),
+            root_control: cap.read_u16(0x1C)?,
+            root_capabilities: cap.read_u16(0x1E)?,
+            root_status: cap.read_u32(0x20)?,
+            device_capabilities_2: read_v2_u32(0x24)?,
+            device_control_2: read_v2_u16(0x28)?,
+            device_status_2: read_v2_u16(0x2A)?,
+            link_capabilities_2: read_v2_u32(0x2C)?,
+            link_control_2: read_v2_u16(0x30)?,
+            link_status_2: read_v2_u16(0x32)?,
+            slot_capabilities_2: read_v2_u32(0x34)?,
+            slot_control_2: read_v2_u16(0x38)?,
+            slot_status_2: read_v2_u16(0x3A)?,
+        })
+    }
+
+    /// Encodes the capability as it appears in configuration space, with a
+    /// null next capability pointer. The version 2 registers are written
+    /// only if the capability version is 2 or greater.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let has_v2_registers = self.capabilities.version() >= 2;
+        let length = match has_v2_registers {
+            true => Self::V2_LENGTH,
+            false => Self::V1_LENGTH,
+        };
+
+        let mut bytes = vec![0u8; length];
+        bytes[0x00] = Self::ID.as_code();
+        write_u16_at(&mut bytes, 0x02, self.capabilities.bits());
+        write_u32_at(&mut bytes, 0x04, self.device_capabilities.bits());
+        write_u16_at(&mut bytes, 0x08, self.device_control.bits());
+        write_u16_at(&mut bytes, 0x0A, self.device_status.bits());
+        write_u32_at(&mut bytes, 0x0C, self.link_capabilities.bits());
+        write_u16_at(&mut bytes, 0x10, self.link_control.bits());
+        write_u16_at(&mut bytes, 0x12, self.link_status.bits());
+        write_u32_at(&mut bytes, 0x14, self.slot_capabilities.bits());
+        write_u16_at(&mut bytes, 0x18, self.slot_control.bits());
+        write_u16_at(&mut bytes, 0x1A, self.slot_status.bits());
+        write_u16_at(&mut bytes, 0x1C, self.root_control);
+        write_u16_at(&mut bytes, 0x1E, self.root_capabilities);
+        write_u32_at(&mut bytes, 0x20, self.root_status);
+
+        if has_v2_registers {
+            write_u32_at(&mut bytes, 0x24, self.device_capabilities_2);
+            write_u16_at(&mut bytes, 0x28, self.device_control_2);
+            write_u16_at(&mut bytes, 0x2A, self.device_status_2);
+            write_u32_at(&mut bytes, 0x2C, self.link_capabilities_2);
+            write_u16_at(&mut bytes, 0x30, self.link_control_2);
+            write_u16_at(&mut bytes, 0x32, self.link_status_2);
+            write_u32_at(&mut bytes, 0x34, self.slot_capabilities_2);
+            write_u16_at(&mut bytes, 0x38, self.slot_control_2);
+            write_u16_at(&mut bytes, 0x3A, self.slot_status_2);
+        }
+
+        bytes
+    }
+
+    /// The type of the PCI Express function.
+    pub fn device_port_type(&self) -> PciExpressDevicePortType {
+        self.capabilities.device_port_type()
+    }
+
+    /// The maximum speed of the link.
+    pub fn max_link_speed(&self) -> PciExpressLinkSpeed {
+        self.link_capabilities.max_link_speed()
+    }
+
+    /// The maximum width of the link.
+    pub fn max_link_width(&self) -> PciExpressLinkWidth {
+        self.link_capabilities.max_link_width()
+    }
+
+    /// The negotiated speed of the link.
+    pub fn negotiated_link_speed(&self) -> PciExpressLinkSpeed {
+        self.link_status.current_link_speed()
+    }
+
+    /// The negotiated width of the link.
+    pub fn negotiated_link_width(&self) -> PciExpressLinkWidth {
+        self.link_status.negotiated_link_width()
+    }
+
+    /// The link speeds supported by the port, as reported by the supported
+    /// link speeds vector of the Link Capabilities 2 register. The list is
+    /// empty for version 1 capabilities.
+    pub fn supported_link_speeds(&self) -> Vec<PciExpressLinkSpeed> {
+        (1..=7)
+            .filter(|bit| self.link_capabilities_2 & (1 << bit) != 0)
+            .map(|bit| PciExpressLinkSpeed::from_code(bit as u8))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_headers::test_support::decode_capability;
+
+    #[test]
+    fn decodes_link_registers() {
+        // The body starts at offset 0x02 of the capability: version 2, root
+        // port
+        let mut body = [0u8; 0x3A];
+        body[0x00..0x02].copy_from_slice(&[0x42, 0x00]);
+        // Link capabilities: 16GT/s x16, port 3
+        body[0x0A..0x0E].copy_from_slice(&0x0300_0104u32.to_le_bytes());
+        // Link status: 8GT/s x4, DL active
+        body[0x10..0x12].copy_from_slice(&0x2043u16.to_le_bytes());
+        // Link capabilities 2: 2.5, 5, 8 and 16 GT/s
+        body[0x2A..0x2E].copy_from_slice(&0x0000_001Eu32.to_le_bytes());
+
+        let pcie = decode_capability(
+            PciExpressCapability::ID,
+            &body,
+            PciExpressCapability::with_capability,
+        );
+
+        assert_eq!(pcie.capabilities.version(), 2);
+        assert_eq!(pcie.device_port_type(), PciExpressDevicePortType::RootPort);
+        assert_eq!(pcie.max_link_speed(), PciExpressLinkSpeed::Gt16);
+        assert_eq!(pcie.max_link_width(), PciExpressLinkWidth::X16);
+        assert_eq!(pcie.link_capabilities.port_number(), 3);
+        assert_eq!(pcie.negotiated_link_speed(), PciExpressLinkSpeed::Gt8);
+        assert_eq!(pcie.negotiated_link_width(), PciExpressLinkWidth::X4);
+        assert!(pcie.link_status.data_link_layer_link_active());
+        assert_eq!(
+            pcie.supported_link_speeds(),
+            vec![
+                PciExpressLinkSpeed::Gt2_5,
+                PciExpressLinkSpeed::Gt5,
+                PciExpressLinkSpeed::Gt8,
+                PciExpressLinkSpeed::Gt16
+            ]
+        );
+        assert_eq!(pcie.negotiated_link_speed().to_string(), "8GT/s");
+        assert_eq!(PciExpressLinkSpeed::Gt2_5.to_string(), "2.5GT/s");
+    }
+
+    #[test]
+    fn reserved_sizes_are_not_decoded() {
+        // Max_Read_Request_Size 512 bytes, Max_Payload_Size 256 bytes
+        let control = PciExpressDeviceControl(2 << 12 | 1 << 5);
+        assert_eq!(control.max_read_request_size(), Some(512));
+        assert_eq!(control.max_payload_size(), Some(256));
+
+        let control = PciExpressDeviceControl(7 << 12 | 6 << 5);
+        assert_eq!(control.max_read_request_size(), None);
+        assert_eq!(control.max_payload_size(), None);
+
+        assert_eq!(
+            PciExpressDeviceCapabilities(5).max_payload_size_supported(),
+            Some(4096)
+        );
+        assert_eq!(
+            PciExpressDeviceCapabilities(6).max_payload_size_supported(),
+            None
+        );
+    }
+}
@@ -0,0 +1,362 @@
+use crate::PciInfoError;
+
+use super::{PciConfigAccess, PciExtendedCapabilityId};
+
+/// A capability found in the extended capability list of a PCI Express
+/// device.
+///
+/// The bytes of the capability start at the 32-bit extended capability
+/// header and extend up to the end of the configuration space access, as
+/// the length of a capability depends on its type. The capability is read
+/// through the [`PciConfigAccess`] it was found in, which is a byte slice
+/// unless specified otherwise.
+pub struct PciExtendedCapability<'a, A: ?Sized = [u8]> {
+    id: u16,
+    version: u8,
+    offset: u16,
+    next: u16,
+    config: &'a A,
+    end: usize,
+}
+
+impl<A: ?Sized> Clone for PciExtendedCapability<'_, A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<A: ?Sized> Copy for PciExtendedCapability<'_, A> {}
+
+impl<'a> PciExtendedCapability<'a> {
+    /// Returns the raw bytes of this extended capability, starting at the
+    /// extended capability header.
+    pub fn bytes(&self) -> &'a [u8] {
+        &self.config[self.offset as usize..self.end]
+    }
+}
+
+impl<'a, A: PciConfigAccess + ?Sized> PciExtendedCapability<'a, A> {
+    /// Returns the raw ID of this extended capability.
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    /// Returns the ID of this extended capability as a `PciExtendedCapabilityId`.
+    pub fn capability_id(&self) -> PciExtendedCapabilityId {
+        PciExtendedCapabilityId::from_code(self.id)
+    }
+
+    /// Returns the version of this extended capability.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Returns the offset of this extended capability in the configuration space.
+    pub fn offset(&self) -> u16 {
+        self.offset
+    }
+
+    /// Returns the raw offset of the next extended capability in the list.
+    pub fn next_offset(&self) -> u16 {
+        self.next
+    }
+
+    /// Returns the configuration space this extended capability was found in.
+    pub fn config(&self) -> &'a A {
+        self.config
+    }
+
+    /// Reads a byte at `offset` bytes from the start of the capability.
+    pub fn read_u8(&self, offset: usize) -> Result<u8, PciInfoError> {
+        self.config.read_u8(self.absolute_offset(offset, 1)?)
+    }
+
+    /// Reads a `u16` at `offset` bytes from the start of the capability.
+    pub fn read_u16(&self, offset: usize) -> Result<u16, PciInfoError> {
+        self.config.read_u16(self.absolute_offset(offset, 2)?)
+    }
+
+    /// Reads a `u32` at `offset` bytes from the start of the capability.
+    pub fn read_u32(&self, offset: usize) -> Result<u32, PciInfoError> {
+        self.config.read_u32(self.absolute_offset(offset, 4)?)
+    }
+
+    fn absolute_offset(&self, offset: usize, len: usize) -> Result<usize, PciInfoError> {
+        let absolute = (self.offset as usize)
+            .checked_add(offset)
+            .ok_or(PciInfoError::UnexpectedEof)?;
+
+        match absolute.checked_add(len) {
+            Some(end) if end <= self.end => Ok(absolute),
+            _ => Err(PciInfoError::UnexpectedEof),
+        }
+    }
+
+    pub(super) fn assert_id(&self, id: PciExtendedCapabilityId) -> Result<(), PciInfoError> {
+        if self.capability_id() != id {
+            return Err(PciInfoError::ParseError(
+                format!(
+                    "expected extended capability {id:?}, found {:?}",
+                    self.capability_id()
+                )
+                .into(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl<A: PciConfigAccess + ?Sized> std::fmt::Debug for PciExtendedCapability<'_, A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{:?} (0x{:04X}) v{} at 0x{:03X}, next: 0x{:03X}]",
+            self.capability_id(),
+            self.id,
+            self.version,
+            self.offset,
+            self.next
+        )
+    }
+}
+
+/// An iterator over the extended capability list of a PCI Express device,
+/// located in the `0x100..0x1000` region of the configuration space.
+///
+/// If the buffer does not extend past the standard configuration space, or
+/// if the first extended capability header is empty, the iterator is empty.
+/// The iterator yields an error, and then stops, when a capability offset
+/// is out of range, when a loop is detected in the list or when the buffer
+/// ends before a capability header is complete.
+///
+/// Like [`PciCapabilityIterator`], the iterator reads the configuration
+/// space through [`PciConfigAccess`]; use
+/// [`PciExtendedCapabilityIterator::with_access`] for sources other than a
+/// byte slice.
+///
+/// # Example
+/// ```rust
+/// use pci_info::pci_headers::{PciExtendedCapabilityId, PciExtendedCapabilityIterator};
+///
+/// # fn main() -> Result<(), pci_info::PciInfoError> {
+/// let mut config = vec![0u8; 4096];
+/// // AER, version 1, next at 0x140
+/// config[0x100..0x104].copy_from_slice(&0x1401_0001u32.to_le_bytes());
+/// // DSN, version 1, end of list
+/// config[0x140..0x144].copy_from_slice(&0x0001_0003u32.to_le_bytes());
+///
+/// for cap in PciExtendedCapabilityIterator::with_bytes(&config) {
+///     let cap = cap?;
+///     println!("{:?} at 0x{:03X}", cap.capability_id(), cap.offset());
+/// }
+///
+/// let dsn = PciExtendedCapabilityIterator::with_bytes(&config)
+///     .find_capability(PciExtendedCapabilityId::DeviceSerialNumber)?;
+/// assert_eq!(dsn.map(|c| c.offset()), Some(0x140));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`PciCapabilityIterator`]: super::PciCapabilityIterator
+pub struct PciExtendedCapabilityIterator<'a, A: ?Sized = [u8]> {
+    config: &'a A,
+    next: u16,
+    visited: [u64; 16],
+}
+
+impl<A: ?Sized> Clone for PciExtendedCapabilityIterator<'_, A> {
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config,
+            next: self.next,
+            visited: self.visited,
+        }
+    }
+}
+
+impl<'a> PciExtendedCapabilityIterator<'a> {
+    /// The length of the PCI Express extended configuration space.
+    pub const EXTENDED_CONFIG_SPACE_LEN: usize = 0x1000;
+    /// The offset of the first extended capability.
+    pub const FIRST_EXTENDED_CAPABILITY_OFFSET: usize = 0x100;
+
+    /// Creates an iterator over the extended capabilities of the configuration
+    /// space contained in `bytes`, which must start at the beginning of the
+    /// common header.
+    pub fn with_bytes(bytes: &'a [u8]) -> Self {
+        Self::with_access(bytes)
+    }
+}
+
+impl<'a, A: PciConfigAccess + ?Sized> PciExtendedCapabilityIterator<'a, A> {
+    /// Creates an iterator over the extended capabilities of the
+    /// configuration space accessed through `config`, like
+    /// [`PciExtendedCapabilityIterator::with_bytes`].
+    pub fn with_access(config: &'a A) -> Self {
+        let first = PciExtendedCapabilityIterator::FIRST_EXTENDED_CAPABILITY_OFFSET;
+
+        // Absent extended configuration spaces read either as
+        // all zeros or all ones.
+        let next = match config.read_u32(first) {
+            Ok(0) | Ok(0xFFFF_FFFF) => 0,
+            Ok(_) => first as u16,
+            Err(_) if config.size() <= first => 0,
+            Err(_) => first as u16,
+        };
+
+        Self {
+            config,
+            next,
+            visited: [0; 16],
+        }
+    }
+
+    /// Walks the extended capability list looking for the first capability
+    /// with the specified id.
+    pub fn find_capability(
+        self,
+        id: PciExtendedCapabilityId,
+    ) -> Result<Option<PciExtendedCapability<'a, A>>, PciInfoError> {
+        for cap in self {
+            let cap = cap?;
+
+            if cap.capability_id() == id {
+                return Ok(Some(cap));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl<'a, A: PciConfigAccess + ?Sized> Iterator for PciExtendedCapabilityIterator<'a, A> {
+    type Item = Result<PciExtendedCapability<'a, A>, PciInfoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pointer = std::mem::take(&mut self.next);
+
+        if pointer == 0 {
+            return None;
+        }
+
+        // The two lowest bits of extended capability offsets are reserved
+        let offset = pointer & 0xFFC;
+
+        if (offset as usize) < PciExtendedCapabilityIterator::FIRST_EXTENDED_CAPABILITY_OFFSET {
+            return Some(Err(PciInfoError::PciCapabilityPointerOutOfRange(pointer)));
+        }
+
+        let dword = (offset / 4) as usize;
+        let visited_bit = 1u64 << (dword % 64);
+
+        if self.visited[dword / 64] & visited_bit != 0 {
+            return Some(Err(PciInfoError::PciCapabilityLoop(offset)));
+        }
+
+        self.visited[dword / 64] |= visited_bit;
+
+        let header = match self.config.read_u32(offset as usize) {
+            Ok(0xFFFF_FFFF) => return None,
+            Ok(h) => h,
+            Err(e) => return Some(Err(e)),
+        };
+
+        let id = (header & 0xFFFF) as u16;
+        let version = ((header >> 16) & 0xF) as u8;
+        let next = (header >> 20) as u16;
+        let end = self
+            .config
+            .size()
+            .min(PciExtendedCapabilityIterator::EXTENDED_CONFIG_SPACE_LEN);
+
+        self.next = next;
+
+        Some(Ok(PciExtendedCapability {
+            id,
+            version,
+            offset,
+            next,
+            config: self.config,
+            end,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with_caps(caps: &[(u16, u16, u16)]) -> Vec<u8> {
+        let mut config = vec![0u8; 4096];
+
+        for (offset, id, next) in caps.iter() {
+            let header = (*id as u32) | (1 << 16) | ((*next as u32) << 20);
+            let offset = *offset as usize;
+            config[offset..offset + 4].copy_from_slice(&header.to_le_bytes());
+        }
+
+        config
+    }
+
+    #[test]
+    fn walks_extended_capability_list() {
+        let config = config_with_caps(&[
+            (0x100, 0x0001, 0x148),
+            (0x148, 0x0010, 0x200),
+            (0x200, 0x0003, 0),
+        ]);
+        let caps = PciExtendedCapabilityIterator::with_bytes(&config)
+            .map(|c| c.map(|c| (c.offset(), c.capability_id(), c.version())))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            caps,
+            vec![
+                (0x100, PciExtendedCapabilityId::AdvancedErrorReporting, 1),
+                (
+                    0x148,
+                    PciExtendedCapabilityId::SingleRootIoVirtualization,
+                    1
+                ),
+                (0x200, PciExtendedCapabilityId::DeviceSerialNumber, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_extended_space_yields_nothing() {
+        let config = config_with_caps(&[]);
+
+        assert_eq!(
+            PciExtendedCapabilityIterator::with_bytes(&config).count(),
+            0
+        );
+        assert_eq!(
+            PciExtendedCapabilityIterator::with_bytes(&config[0..256]).count(),
+            0
+        );
+    }
+
+    #[test]
+    fn detects_loops_and_out_of_range_offsets() {
+        let config = config_with_caps(&[(0x100, 0x0001, 0x148), (0x148, 0x0010, 0x100)]);
+        let res = PciExtendedCapabilityIterator::with_bytes(&config).collect::<Vec<_>>();
+
+        assert_eq!(res.len(), 3);
+        assert!(matches!(
+            res[2],
+            Err(PciInfoError::PciCapabilityLoop(0x100))
+        ));
+
+        let config = config_with_caps(&[(0x100, 0x0001, 0x0C0)]);
+        let res = PciExtendedCapabilityIterator::with_bytes(&config).collect::<Vec<_>>();
+
+        assert_eq!(res.len(), 2);
+        assert!(matches!(
+            res[1],
+            Err(PciInfoError::PciCapabilityPointerOutOfRange(0x0C0))
+        ));
+    }
+}
@@ -0,0 +1,238 @@
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+/// Represent the ID of a PCI Express extended capability as an enumeration,
+/// for easier matching with known valid values.
+///
+/// See the PCI Code and ID Assignment Specification for possible values.
+pub enum PciExtendedCapabilityId {
+    /// Enumeration matching extended capability ID 0000h (null capability).
+    Null,
+    /// Enumeration matching extended capability ID 0001h.
+    AdvancedErrorReporting,
+    /// Enumeration matching extended capability ID 0002h.
+    VirtualChannel,
+    /// Enumeration matching extended capability ID 0003h.
+    DeviceSerialNumber,
+    /// Enumeration matching extended capability ID 0004h.
+    PowerBudgeting,
+    /// Enumeration matching extended capability ID 0005h.
+    RootComplexLinkDeclaration,
+    /// Enumeration matching extended capability ID 0006h.
+    RootComplexInternalLinkControl,
+    /// Enumeration matching extended capability ID 0007h.
+    RootComplexEventCollectorEndpointAssociation,
+    /// Enumeration matching extended capability ID 0008h.
+    MultiFunctionVirtualChannel,
+    /// Enumeration matching extended capability ID 0009h.
+    VirtualChannelWithMfvc,
+    /// Enumeration matching extended capability ID 000Ah.
+    RootComplexRegisterBlock,
+    /// Enumeration matching extended capability ID 000Bh.
+    VendorSpecific,
+    /// Enumeration matching extended capability ID 000Ch.
+    ConfigurationAccessCorrelation,
+    /// Enumeration matching extended capability ID 000Dh.
+    AccessControlServices,
+    /// Enumeration matching extended capability ID 000Eh.
+    AlternativeRoutingId,
+    /// Enumeration matching extended capability ID 000Fh.
+    AddressTranslationServices,
+    /// Enumeration matching extended capability ID 0010h.
+    SingleRootIoVirtualization,
+    /// Enumeration matching extended capability ID 0011h.
+    MultiRootIoVirtualization,
+    /// Enumeration matching extended capability ID 0012h.
+    Multicast,
+    /// Enumeration matching extended capability ID 0013h.
+    PageRequestInterface,
+    /// Enumeration matching extended capability ID 0014h.
+    ReservedForAmd,
+    /// Enumeration matching extended capability ID 0015h.
+    ResizableBar,
+    /// Enumeration matching extended capability ID 0016h.
+    DynamicPowerAllocation,
+    /// Enumeration matching extended capability ID 0017h.
+    TphRequester,
+    /// Enumeration matching extended capability ID 0018h.
+    LatencyToleranceReporting,
+    /// Enumeration matching extended capability ID 0019h.
+    SecondaryPciExpress,
+    /// Enumeration matching extended capability ID 001Ah.
+    ProtocolMultiplexing,
+    /// Enumeration matching extended capability ID 001Bh.
+    ProcessAddressSpaceId,
+    /// Enumeration matching extended capability ID 001Ch.
+    LnRequester,
+    /// Enumeration matching extended capability ID 001Dh.
+    DownstreamPortContainment,
+    /// Enumeration matching extended capability ID 001Eh.
+    L1PmSubstates,
+    /// Enumeration matching extended capability ID 001Fh.
+    PrecisionTimeMeasurement,
+    /// Enumeration matching extended capability ID 0020h.
+    PciExpressOverMphy,
+    /// Enumeration matching extended capability ID 0021h.
+    FrsQueueing,
+    /// Enumeration matching extended capability ID 0022h.
+    ReadinessTimeReporting,
+    /// Enumeration matching extended capability ID 0023h.
+    DesignatedVendorSpecific,
+    /// Enumeration matching extended capability ID 0024h.
+    VfResizableBar,
+    /// Enumeration matching extended capability ID 0025h.
+    DataLinkFeature,
+    /// Enumeration matching extended capability ID 0026h.
+    PhysicalLayer16GT,
+    /// Enumeration matching extended capability ID 0027h.
+    LaneMarginingAtReceiver,
+    /// Enumeration matching extended capability ID 0028h.
+    HierarchyId,
+    /// Enumeration matching extended capability ID 0029h.
+    NativePcieEnclosureManagement,
+    /// Enumeration matching extended capability ID 002Ah.
+    PhysicalLayer32GT,
+    /// Enumeration matching extended capability ID 002Bh.
+    AlternateProtocol,
+    /// Enumeration matching extended capability ID 002Ch.
+    SystemFirmwareIntermediary,
+    /// Enumeration matching extended capability ID 002Dh.
+    ShadowFunctions,
+    /// Enumeration matching extended capability ID 002Eh.
+    DataObjectExchange,
+    /// Enumeration matching extended capability ID 002Fh.
+    Device3,
+    /// Enumeration matching extended capability ID 0030h.
+    IntegrityAndDataEncryption,
+    /// Enumeration matching extended capability ID 0031h.
+    PhysicalLayer64GT,
+    /// Enumeration matching unknown extended capability IDs.
+    Unknown(u16),
+}
+
+impl PciExtendedCapabilityId {
+    /// Create a `PciExtendedCapabilityId` from the `u16` value that it
+    /// represents
+    pub fn from_code(id: u16) -> Self {
+        match id {
+            0x0000 => Self::Null,
+            0x0001 => Self::AdvancedErrorReporting,
+            0x0002 => Self::VirtualChannel,
+            0x0003 => Self::DeviceSerialNumber,
+            0x0004 => Self::PowerBudgeting,
+            0x0005 => Self::RootComplexLinkDeclaration,
+            0x0006 => Self::RootComplexInternalLinkControl,
+            0x0007 => Self::RootComplexEventCollectorEndpointAssociation,
+            0x0008 => Self::MultiFunctionVirtualChannel,
+            0x0009 => Self::VirtualChannelWithMfvc,
+            0x000a => Self::RootComplexRegisterBlock,
+            0x000b => Self::VendorSpecific,
+            0x000c => Self::ConfigurationAccessCorrelation,
+            0x000d => Self::AccessControlServices,
+            0x000e => Self::AlternativeRoutingId,
+            0x000f => Self::AddressTranslationServices,
+            0x0010 => Self::SingleRootIoVirtualization,
+            0x0011 => Self::MultiRootIoVirtualization,
+            0x0012 => Self::Multicast,
+            0x0013 => Self::PageRequestInterface,
+            0x0014 => Self::ReservedForAmd,
+            0x0015 => Self::ResizableBar,
+            0x0016 => Self::DynamicPowerAllocation,
+            0x0017 => Self::TphRequester,
+            0x0018 => Self::LatencyToleranceReporting,
+            0x0019 => Self::SecondaryPciExpress,
+            0x001a => Self::ProtocolMultiplexing,
+            0x001b => Self::ProcessAddressSpaceId,
+            0x001c => Self::LnRequester,
+            0x001d => Self::DownstreamPortContainment,
+            0x001e => Self::L1PmSubstates,
+            0x001f => Self::PrecisionTimeMeasurement,
+            0x0020 => Self::PciExpressOverMphy,
+            0x0021 => Self::FrsQueueing,
+            0x0022 => Self::ReadinessTimeReporting,
+            0x0023 => Self::DesignatedVendorSpecific,
+            0x0024 => Self::VfResizableBar,
+            0x0025 => Self::DataLinkFeature,
+            0x0026 => Self::PhysicalLayer16GT,
+            0x0027 => Self::LaneMarginingAtReceiver,
+            0x0028 => Self::HierarchyId,
+            0x0029 => Self::NativePcieEnclosureManagement,
+            0x002a => Self::PhysicalLayer32GT,
+            0x002b => Self::AlternateProtocol,
+            0x002c => Self::SystemFirmwareIntermediary,
+            0x002d => Self::ShadowFunctions,
+            0x002e => Self::DataObjectExchange,
+            0x002f => Self::Device3,
+            0x0030 => Self::IntegrityAndDataEncryption,
+            0x0031 => Self::PhysicalLayer64GT,
+            unk => Self::Unknown(unk),
+        }
+    }
+
+    /// Gets the `u16` value that this `PciExtendedCapabilityId` represents
+    pub fn as_code(&self) -> u16 {
+        match self {
+            Self::Null => 0x0000,
+            Self::AdvancedErrorReporting => 0x0001,
+            Self::VirtualChannel => 0x0002,
+            Self::DeviceSerialNumber => 0x0003,
+            Self::PowerBudgeting => 0x0004,
+            Self::RootComplexLinkDeclaration => 0x0005,
+            Self::RootComplexInternalLinkControl => 0x0006,
+            Self::RootComplexEventCollectorEndpointAssociation => 0x0007,
+            Self::MultiFunctionVirtualChannel => 0x0008,
+            Self::VirtualChannelWithMfvc => 0x0009,
+            Self::RootComplexRegisterBlock => 0x000a,
+            Self::VendorSpecific => 0x000b,
+            Self::ConfigurationAccessCorrelation => 0x000c,
+            Self::AccessControlServices => 0x000d,
+            Self::AlternativeRoutingId => 0x000e,
+            Self::AddressTranslationServices => 0x000f,
+            Self::SingleRootIoVirtualization => 0x0010,
+            Self::MultiRootIoVirtualization => 0x0011,
+            Self::Multicast => 0x0012,
+            Self::PageRequestInterface => 0x0013,
+            Self::ReservedForAmd => 0x0014,
+            Self::ResizableBar => 0x0015,
+            Self::DynamicPowerAllocation => 0x0016,
+            Self::TphRequester => 0x0017,
+            Self::LatencyToleranceReporting => 0x0018,
+            Self::SecondaryPciExpress => 0x0019,
+            Self::ProtocolMultiplexing => 0x001a,
+            Self::ProcessAddressSpaceId => 0x001b,
+            Self::LnRequester => 0x001c,
+            Self::DownstreamPortContainment => 0x001d,
+            Self::L1PmSubstates => 0x001e,
+            Self::PrecisionTimeMeasurement => 0x001f,
+            Self::PciExpressOverMphy => 0x0020,
+            Self::FrsQueueing => 0x0021,
+            Self::ReadinessTimeReporting => 0x0022,
+            Self::DesignatedVendorSpecific => 0x0023,
+            Self::VfResizableBar => 0x0024,
+            Self::DataLinkFeature => 0x0025,
+            Self::PhysicalLayer16GT => 0x0026,
+            Self::LaneMarginingAtReceiver => 0x0027,
+            Self::HierarchyId => 0x0028,
+            Self::NativePcieEnclosureManagement => 0x0029,
+            Self::PhysicalLayer32GT => 0x002a,
+            Self::AlternateProtocol => 0x002b,
+            Self::SystemFirmwareIntermediary => 0x002c,
+            Self::ShadowFunctions => 0x002d,
+            Self::DataObjectExchange => 0x002e,
+            Self::Device3 => 0x002f,
+            Self::IntegrityAndDataEncryption => 0x0030,
+            Self::PhysicalLayer64GT => 0x0031,
+            Self::Unknown(unk) => *unk,
+        }
+    }
+}
+
+impl From<u16> for PciExtendedCapabilityId {
+    fn from(value: u16) -> Self {
+        Self::from_code(value)
+    }
+}
+
+impl From<PciExtendedCapabilityId> for u16 {
+    fn from(value: PciExtendedCapabilityId) -> Self {
+        value.as_code()
+    }
+}
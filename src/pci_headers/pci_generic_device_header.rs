@@ -1,6 +1,7 @@
 use crate::PciInfoError;
 
-use super::pci_config_buffer::PciConfigBuffer;
+use super::pci_config_buffer::{PciConfigBuffer, PciConfigBufferMut};
+use super::PciBar;
 
 /// The header for generic PCI devices.
 ///
@@ -27,7 +28,7 @@ use super::pci_config_buffer::PciConfigBuffer;
 /// |   0xF    |   0x3C  | Max latency |   Min Grant  | Interrupt PIN  | Interrupt Line |
 /// +----------+---------+-------------+--------------+----------------+----------------+
 /// ```
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct PciGenericDeviceHeader {
     pub base_addr: [u32; 6],
     pub cardbus_cis_ptr: u32,
@@ -45,6 +46,12 @@ impl PciGenericDeviceHeader {
     pub const LENGTH: usize = 64;
     pub const ID: u8 = 0;
 
+    /// Decodes the Base Address Registers of the header, merging the two
+    /// halves of 64-bit BARs and skipping unused registers.
+    pub fn bars(&self) -> Vec<PciBar> {
+        PciBar::from_registers(&self.base_addr)
+    }
+
     pub(super) fn with_pci_cfg(pci_cfg: &PciConfigBuffer<'_>) -> Result<Self, PciInfoError> {
         pci_cfg.assert_registers_available(4, 0xF)?;
 
@@ -61,11 +68,81 @@ impl PciGenericDeviceHeader {
             subsystem_device_id: pci_cfg.read_u16_hi(0xB),
             subsystem_vendor_id: pci_cfg.read_u16_lo(0xB),
             expansion_rom_base_addr: pci_cfg.read_u32(0xC),
-            capabilities_ptr: pci_cfg.read_u16_hi(0xD),
+            capabilities_ptr: pci_cfg.read_u8(0xD, 0) as u16,
             max_latency: pci_cfg.read_u8(0xF, 3),
             min_grant: pci_cfg.read_u8(0xF, 2),
             interrupt_pin: pci_cfg.read_u8(0xF, 1),
             interrupt_line: pci_cfg.read_u8(0xF, 0),
         })
     }
+
+    pub(super) fn write_pci_cfg(
+        &self,
+        pci_cfg: &mut PciConfigBufferMut<'_>,
+    ) -> Result<(), PciInfoError> {
+        pci_cfg.assert_registers_available(4, 0xF)?;
+
+        for (i, bar) in self.base_addr.iter().enumerate() {
+            pci_cfg.write_u32(0x4 + i, *bar);
+        }
+        pci_cfg.write_u32(0xA, self.cardbus_cis_ptr);
+        pci_cfg.write_u16_hi(0xB, self.subsystem_device_id);
+        pci_cfg.write_u16_lo(0xB, self.subsystem_vendor_id);
+        pci_cfg.write_u32(0xC, self.expansion_rom_base_addr);
+        pci_cfg.write_u8(0xD, 0, self.capabilities_ptr as u8);
+        pci_cfg.write_u8(0xF, 3, self.max_latency);
+        pci_cfg.write_u8(0xF, 2, self.min_grant);
+        pci_cfg.write_u8(0xF, 1, self.interrupt_pin);
+        pci_cfg.write_u8(0xF, 0, self.interrupt_line);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pci_headers::PciSpecializedHeader;
+    use crate::PciInfoError;
+
+    #[test]
+    fn capabilities_pointer_is_read_from_its_byte() {
+        let mut bytes = [0u8; 64];
+        bytes[0x34] = 0x40;
+        // Reserved bytes following the pointer must be ignored
+        bytes[0x35] = 0xAA;
+        bytes[0x36] = 0x55;
+
+        let header = match PciSpecializedHeader::read_subheader(0, &bytes, true).unwrap() {
+            PciSpecializedHeader::GenericDevice(header) => header,
+            header => panic!("unexpected header {header:?}"),
+        };
+
+        assert_eq!(header.capabilities_ptr, 0x40);
+    }
+
+    #[test]
+    fn bridge_capability_pointer_is_read_from_its_byte() {
+        let mut bytes = [0u8; 64];
+        bytes[0x34] = 0x40;
+        bytes[0x35] = 0xAA;
+
+        let header = match PciSpecializedHeader::read_subheader(1, &bytes, true).unwrap() {
+            PciSpecializedHeader::PciToPciBridge(header) => header,
+            header => panic!("unexpected header {header:?}"),
+        };
+
+        assert_eq!(header.capability_pointer, 0x40);
+    }
+
+    #[test]
+    fn short_subheader_is_rejected() {
+        // Only 20 of the 48 bytes following the common header
+        let bytes = [0u8; 20];
+
+        assert!(matches!(
+            PciSpecializedHeader::read_subheader(0, &bytes, false),
+            Err(PciInfoError::ParseError(_))
+        ));
+        assert!(PciSpecializedHeader::read_subheader(0, &[0u8; 48], false).is_ok());
+    }
 }
@@ -0,0 +1,180 @@
+use crate::PciInfoError;
+
+use super::pci_ltr_capability::latency_ns;
+use super::{PciConfigAccess, PciExtendedCapability, PciExtendedCapabilityId};
+
+// Decodes a T_POWER_ON time, encoded as a 5-bit value and a 2-bit scale of
+// 2us, 10us or 100us (scale 3 is reserved).
+fn t_power_on_ns(value: u32, scale: u32) -> Option<u64> {
+    let scale_ns = match scale {
+        0 => 2_000,
+        1 => 10_000,
+        2 => 100_000,
+        _ => return None,
+    };
+
+    Some(value as u64 * scale_ns)
+}
+
+pci_register_bits! {
+    /// The L1 PM Substates Capabilities register of the L1 PM Substates
+    /// capability.
+    pub struct PciL1PmSubstatesCapabilities(u32) {
+        /// PCI-PM L1.2 is supported.
+        pci_pm_l1_2_supported, set_pci_pm_l1_2_supported: 0 => "PCI-PM_L1.2";
+        /// PCI-PM L1.1 is supported.
+        pci_pm_l1_1_supported, set_pci_pm_l1_1_supported: 1 => "PCI-PM_L1.1";
+        /// ASPM L1.2 is supported.
+        aspm_l1_2_supported, set_aspm_l1_2_supported: 2 => "ASPM_L1.2";
+        /// ASPM L1.1 is supported.
+        aspm_l1_1_supported, set_aspm_l1_1_supported: 3 => "ASPM_L1.1";
+        /// L1 PM Substates are supported.
+        l1_pm_substates_supported, set_l1_pm_substates_supported: 4 => "L1_PM_Substates";
+        /// Link Activation is supported.
+        link_activation_supported, set_link_activation_supported: 5 => "LinkActivation";
+    }
+}
+
+impl PciL1PmSubstatesCapabilities {
+    /// The time, in microseconds, that this port requires the opposite
+    /// port to wait for the common mode to be restored when exiting L1.2.
+    pub fn port_common_mode_restore_time_us(&self) -> u32 {
+        self.field(8, 0xFF)
+    }
+
+    /// The time, in nanoseconds, that this port requires the opposite port
+    /// to wait in L1.2 before driving the link, or `None` if the scale is
+    /// reserved.
+    pub fn port_t_power_on_ns(&self) -> Option<u64> {
+        t_power_on_ns(self.field(19, 0x1F), self.field(16, 0x3))
+    }
+}
+
+pci_register_bits! {
+    /// The L1 PM Substates Control 1 register of the L1 PM Substates
+    /// capability.
+    pub struct PciL1PmSubstatesControl1(u32) {
+        /// PCI-PM L1.2 is enabled.
+        pci_pm_l1_2_enable, set_pci_pm_l1_2_enable: 0 => "PCI-PM_L1.2";
+        /// PCI-PM L1.1 is enabled.
+        pci_pm_l1_1_enable, set_pci_pm_l1_1_enable: 1 => "PCI-PM_L1.1";
+        /// ASPM L1.2 is enabled.
+        aspm_l1_2_enable, set_aspm_l1_2_enable: 2 => "ASPM_L1.2";
+        /// ASPM L1.1 is enabled.
+        aspm_l1_1_enable, set_aspm_l1_1_enable: 3 => "ASPM_L1.1";
+        /// The Link Activation interrupt is enabled.
+        link_activation_interrupt_enable, set_link_activation_interrupt_enable: 4 => "LinkActivationIntEn";
+        /// Link Activation is requested.
+        link_activation_control, set_link_activation_control: 5 => "LinkActivationCtl";
+    }
+}
+
+impl PciL1PmSubstatesControl1 {
+    /// The time, in microseconds, that the downstream port waits for the
+    /// common mode to be restored when exiting L1.2.
+    pub fn common_mode_restore_time_us(&self) -> u32 {
+        self.field(8, 0xFF)
+    }
+
+    /// The LTR latency threshold, in nanoseconds, above which L1.2 is
+    /// entered, or `None` if the scale is not permitted.
+    pub fn ltr_l1_2_threshold_ns(&self) -> Option<u64> {
+        latency_ns(self.field(16, 0x3FF) as u16, self.field(29, 0x7) as u8)
+    }
+}
+
+/// The L1 PM Substates extended capability (extended capability ID 001Eh)
+/// of a PCI Express port, controlling the L1.1 and L1.2 low power substates
+/// of the link.
+///
+/// The format of the capability in PCI configuration space is the following.
+///
+///  ```text
+/// +---------+-------------+--------------+----------------+----------------+
+/// | Offset  | Bits 31-24  | Bits 23-16   | Bits 15-8      | Bits 7-0       |
+/// +---------+-------------+--------------+----------------+----------------+
+/// |   0x00  | Next offset | Version      |       Extended capability ID    |
+/// |   0x04  |               L1 PM Substates Capabilities                   |
+/// |   0x08  |               L1 PM Substates Control 1                      |
+/// |   0x0C  |               L1 PM Substates Control 2                      |
+/// +---------+-------------+--------------+----------------+----------------+
+/// ```
+///
+/// Control 2 contains the T_POWER_ON value (bits 7:3) and scale (bits 1:0);
+/// see [`PciL1PmSubstatesCapability::t_power_on_ns`].
+#[derive(Clone, Debug, Default)]
+pub struct PciL1PmSubstatesCapability {
+    pub capabilities: PciL1PmSubstatesCapabilities,
+    pub control1: PciL1PmSubstatesControl1,
+    /// The raw L1 PM Substates Control 2 register.
+    pub control2: u32,
+}
+
+impl PciL1PmSubstatesCapability {
+    pub const ID: PciExtendedCapabilityId = PciExtendedCapabilityId::L1PmSubstates;
+    pub const LENGTH: usize = 0x10;
+
+    /// Decodes an L1 PM Substates capability found in the extended
+    /// capability list.
+    pub fn with_capability<A: PciConfigAccess + ?Sized>(
+        cap: &PciExtendedCapability<'_, A>,
+    ) -> Result<Self, PciInfoError> {
+        cap.assert_id(Self::ID)?;
+
+        Ok(Self {
+            capabilities: PciL1PmSubstatesCapabilities(cap.read_u32(0x04)?),
+            control1: PciL1PmSubstatesControl1(cap.read_u32(0x08)?),
+            control2: cap.read_u32(0x0C)?,
+        })
+    }
+
+    /// The time, in nanoseconds, that the port waits in L1.2 before
+    /// driving the link, or `None` if the scale is reserved.
+    pub fn t_power_on_ns(&self) -> Option<u64> {
+        t_power_on_ns((self.control2 >> 3) & 0x1F, self.control2 & 0x3)
+    }
+
+    /// The common mode restore time, in nanoseconds, programmed in the
+    /// Control 1 register.
+    pub fn common_mode_restore_time_ns(&self) -> u64 {
+        self.control1.common_mode_restore_time_us() as u64 * 1000
+    }
+
+    /// Returns true if any L1 substate is supported but not enabled.
+    pub fn has_disabled_substates(&self) -> bool {
+        self.capabilities.bits() & !self.control1.bits() & 0xF != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_headers::test_support::decode_extended_capability;
+
+    #[test]
+    fn decodes_substates_and_times() {
+        // All substates supported, Port Common_Mode_Restore_Time 40us,
+        // Port T_POWER_ON 6 * 10us; ASPM L1.2 disabled, LTR threshold
+        // 0x50 * 1024ns, T_POWER_ON 5 * 100us.
+        let mut body = Vec::new();
+        body.extend_from_slice(&0x0031_281Fu32.to_le_bytes());
+        body.extend_from_slice(&0x4050_280Bu32.to_le_bytes());
+        body.extend_from_slice(&0x0000_002Au32.to_le_bytes());
+
+        let l1ss = decode_extended_capability(
+            PciL1PmSubstatesCapability::ID,
+            &body,
+            PciL1PmSubstatesCapability::with_capability,
+        );
+
+        assert!(l1ss.capabilities.aspm_l1_2_supported());
+        assert!(!l1ss.control1.aspm_l1_2_enable());
+        assert!(l1ss.control1.pci_pm_l1_2_enable());
+        assert!(l1ss.has_disabled_substates());
+        assert_eq!(l1ss.capabilities.port_common_mode_restore_time_us(), 40);
+        assert_eq!(l1ss.capabilities.port_t_power_on_ns(), Some(60_000));
+        assert_eq!(l1ss.common_mode_restore_time_ns(), 40_000);
+        assert_eq!(l1ss.control1.ltr_l1_2_threshold_ns(), Some(0x50 * 1024));
+        assert_eq!(l1ss.t_power_on_ns(), Some(500_000));
+    }
+}
@@ -0,0 +1,93 @@
+use crate::PciInfoError;
+
+use super::{PciConfigAccess, PciExtendedCapability, PciExtendedCapabilityId};
+
+// Decodes a latency encoded as a 10-bit value and a 3-bit scale, in which
+// the value is multiplied by 32^scale nanoseconds. Scales 6 and 7 are not
+// permitted.
+pub(super) fn latency_ns(value: u16, scale: u8) -> Option<u64> {
+    if scale > 5 {
+        return None;
+    }
+
+    Some((value as u64 & 0x3FF) << (5 * scale as u32))
+}
+
+/// The Latency Tolerance Reporting extended capability (extended capability
+/// ID 0018h) of a PCI Express upstream port, containing the maximum
+/// latencies that the port is allowed to report. LTR messages are enabled
+/// by the LTR Mechanism Enable bit of the Device Control 2 register of the
+/// PCI Express capability.
+///
+/// The format of the capability in PCI configuration space is the following.
+///
+///  ```text
+/// +---------+-------------+--------------+----------------+----------------+
+/// | Offset  | Bits 31-24  | Bits 23-16   | Bits 15-8      | Bits 7-0       |
+/// +---------+-------------+--------------+----------------+----------------+
+/// |   0x00  | Next offset | Version      |       Extended capability ID    |
+/// |   0x04  |   Max No-Snoop Latency     |       Max Snoop Latency         |
+/// +---------+-------------+--------------+----------------+----------------+
+/// ```
+///
+/// Each latency register contains a value in bits 9:0 and a scale in bits
+/// 12:10; see [`PciLtrCapability::max_snoop_latency_ns`].
+#[derive(Clone, Debug, Default)]
+pub struct PciLtrCapability {
+    /// The raw Max Snoop Latency register.
+    pub max_snoop_latency: u16,
+    /// The raw Max No-Snoop Latency register.
+    pub max_no_snoop_latency: u16,
+}
+
+impl PciLtrCapability {
+    pub const ID: PciExtendedCapabilityId = PciExtendedCapabilityId::LatencyToleranceReporting;
+    pub const LENGTH: usize = 0x08;
+
+    /// Decodes an LTR capability found in the extended capability list.
+    pub fn with_capability<A: PciConfigAccess + ?Sized>(
+        cap: &PciExtendedCapability<'_, A>,
+    ) -> Result<Self, PciInfoError> {
+        cap.assert_id(Self::ID)?;
+
+        Ok(Self {
+            max_snoop_latency: cap.read_u16(0x04)?,
+            max_no_snoop_latency: cap.read_u16(0x06)?,
+        })
+    }
+
+    /// The maximum snoop latency in nanoseconds, or `None` if the register
+    /// uses a scale that is not permitted.
+    pub fn max_snoop_latency_ns(&self) -> Option<u64> {
+        let reg = self.max_snoop_latency;
+        latency_ns(reg, ((reg >> 10) & 0x7) as u8)
+    }
+
+    /// The maximum no-snoop latency in nanoseconds, or `None` if the
+    /// register uses a scale that is not permitted.
+    pub fn max_no_snoop_latency_ns(&self) -> Option<u64> {
+        let reg = self.max_no_snoop_latency;
+        latency_ns(reg, ((reg >> 10) & 0x7) as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_headers::test_support::decode_extended_capability;
+
+    #[test]
+    fn decodes_latencies() {
+        // Snoop: 0x46 * 1024ns (scale 2); no-snoop: 3 * 32^6 (invalid scale)
+        let ltr = decode_extended_capability(
+            PciLtrCapability::ID,
+            &[0x46, 0x08, 0x03, 0x18],
+            PciLtrCapability::with_capability,
+        );
+
+        assert_eq!(ltr.max_snoop_latency, 0x0846);
+        assert_eq!(ltr.max_snoop_latency_ns(), Some(0x46 * 1024));
+        assert_eq!(ltr.max_no_snoop_latency_ns(), None);
+        assert_eq!(latency_ns(0x3FF, 5), Some(0x3FF << 25));
+    }
+}
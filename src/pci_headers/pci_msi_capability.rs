@@ -0,0 +1,256 @@
+use crate::PciInfoError;
+
+use super::pci_config_buffer::{write_u16_at, write_u32_at};
+use super::{PciCapability, PciCapabilityId, PciConfigAccess};
+
+pci_register_bits! {
+    /// The Message Control register of the MSI capability.
+    pub struct PciMsiMessageControl(u16) {
+        /// MSI is enabled.
+        msi_enable, set_msi_enable: 0 => "Enable";
+        /// The function supports 64-bit message addresses.
+        address_64bit_capable, set_address_64bit_capable: 7 => "64bit";
+        /// The function supports masking of individual vectors.
+        per_vector_masking_capable, set_per_vector_masking_capable: 8 => "Maskable";
+        /// The function supports extended (32-bit) message data.
+        extended_message_data_capable, set_extended_message_data_capable: 9 => "ExtData";
+        /// Extended (32-bit) message data is enabled.
+        extended_message_data_enable, set_extended_message_data_enable: 10 => "ExtDataEnable";
+    }
+}
+
+impl PciMsiMessageControl {
+    /// The number of vectors requested by the function (the decoded
+    /// Multiple Message Capable field).
+    pub fn multiple_message_capable(&self) -> u8 {
+        1 << self.field(1, 0x7).min(5)
+    }
+
+    /// Sets the number of vectors requested by the function. As the field
+    /// encodes powers of two from 1 to 32, the value is rounded down to a
+    /// power of two and clamped to that range (e.g. both 33 and 64 vectors
+    /// are encoded as 32).
+    pub fn set_multiple_message_capable(&mut self, vectors: u8) {
+        self.set_field(1, 0x7, vectors.clamp(1, 32).ilog2() as u16)
+    }
+
+    /// The number of vectors allocated to the function (the decoded
+    /// Multiple Message Enable field).
+    pub fn multiple_message_enable(&self) -> u8 {
+        1 << self.field(4, 0x7).min(5)
+    }
+
+    /// Sets the number of vectors allocated to the function. As the field
+    /// encodes powers of two from 1 to 32, the value is rounded down to a
+    /// power of two and clamped to that range (e.g. both 33 and 64 vectors
+    /// are encoded as 32).
+    pub fn set_multiple_message_enable(&mut self, vectors: u8) {
+        self.set_field(4, 0x7, vectors.clamp(1, 32).ilog2() as u16)
+    }
+}
+
+/// The MSI capability (capability ID 05h) of a PCI function.
+///
+/// The format of the capability depends on whether 64-bit addresses and
+/// per-vector masking are supported. The format of the capability with both
+/// features in PCI configuration space is the following.
+///
+///  ```text
+/// +---------+-------------+--------------+----------------+----------------+
+/// | Offset  | Bits 31-24  | Bits 23-16   | Bits 15-8      | Bits 7-0       |
+/// +---------+-------------+--------------+----------------+----------------+
+/// |   0x00  |      Message Control       | Next pointer   | Capability ID  |
+/// |   0x04  |                  Message Address                             |
+/// |   0x08  |               Message Upper Address                          |
+/// |   0x0C  |   Extended Message Data    |          Message Data           |
+/// |   0x10  |                     Mask Bits                                |
+/// |   0x14  |                    Pending Bits                              |
+/// +---------+-------------+--------------+----------------+----------------+
+/// ```
+///
+/// Without 64-bit addresses the Message Upper Address register is missing
+/// and the following registers are shifted back by four bytes.
+#[derive(Clone, Debug, Default)]
+pub struct PciMsiCapability {
+    pub message_control: PciMsiMessageControl,
+    pub message_address: u64,
+    pub message_data: u16,
+    pub mask_bits: Option<u32>,
+    pub pending_bits: Option<u32>,
+}
+
+impl PciMsiCapability {
+    pub const ID: PciCapabilityId = PciCapabilityId::Msi;
+
+    /// Decodes an MSI capability found in the capability list.
+    pub fn with_capability<A: PciConfigAccess + ?Sized>(
+        cap: &PciCapability<'_, A>,
+    ) -> Result<Self, PciInfoError> {
+        cap.assert_id(Self::ID)?;
+
+        let message_control = PciMsiMessageControl(cap.read_u16(0x02)?);
+        let address_lo = cap.read_u32(0x04)? as u64;
+
+        let (message_address, data_offset) = if message_control.address_64bit_capable() {
+            (address_lo | (cap.read_u32(0x08)? as u64) << 32, 0x0C)
+        } else {
+            (address_lo, 0x08)
+        };
+
+        let (mask_bits, pending_bits) = if message_control.per_vector_masking_capable() {
+            (
+                Some(cap.read_u32(data_offset + 0x04)?),
+                Some(cap.read_u32(data_offset + 0x08)?),
+            )
+        } else {
+            (None, None)
+        };
+
+        Ok(Self {
+            message_control,
+            message_address,
+            message_data: cap.read_u16(data_offset)?,
+            mask_bits,
+            pending_bits,
+        })
+    }
+
+    /// Encodes the capability as it appears in configuration space, with a
+    /// null next capability pointer. The upper half of the message address
+    /// and the mask and pending bits are written only if supported by the
+    /// message control register.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0u8; self.length()];
+        bytes[0x00] = Self::ID.as_code();
+        write_u16_at(&mut bytes, 0x02, self.message_control.bits());
+        write_u32_at(&mut bytes, 0x04, self.message_address as u32);
+
+        let data_offset = if self.message_control.address_64bit_capable() {
+            write_u32_at(&mut bytes, 0x08, (self.message_address >> 32) as u32);
+            0x0C
+        } else {
+            0x08
+        };
+
+        write_u16_at(&mut bytes, data_offset, self.message_data);
+
+        if self.message_control.per_vector_masking_capable() {
+            write_u32_at(&mut bytes, data_offset + 0x04, self.mask_bits.unwrap_or(0));
+            write_u32_at(
+                &mut bytes,
+                data_offset + 0x08,
+                self.pending_bits.unwrap_or(0),
+            );
+        }
+
+        bytes
+    }
+
+    /// Returns the length of the capability in configuration space.
+    pub fn length(&self) -> usize {
+        match (
+            self.message_control.address_64bit_capable(),
+            self.message_control.per_vector_masking_capable(),
+        ) {
+            (false, false) => 0x0C,
+            (true, false) => 0x10,
+            (false, true) => 0x14,
+            (true, true) => 0x18,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_headers::test_support::decode_capability;
+
+    // `cap` contains the whole capability, including its id and next pointer
+    fn decode(cap: &[u8]) -> PciMsiCapability {
+        decode_capability(
+            PciMsiCapability::ID,
+            &cap[2..],
+            PciMsiCapability::with_capability,
+        )
+    }
+
+    #[test]
+    fn decodes_64bit_maskable_layout() {
+        // 64-bit, per-vector masking, 8 vectors capable, 4 enabled
+        let msi = decode(&[
+            0x05, 0x00, 0xA7, 0x01, 0x00, 0x00, 0xE0, 0xFE, 0x01, 0x00, 0x00, 0x00, 0x21, 0x40,
+            0x00, 0x00, 0x0C, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00,
+        ]);
+
+        assert!(msi.message_control.msi_enable());
+        assert!(msi.message_control.address_64bit_capable());
+        assert!(msi.message_control.per_vector_masking_capable());
+        assert_eq!(msi.message_control.multiple_message_capable(), 8);
+        assert_eq!(msi.message_control.multiple_message_enable(), 4);
+        assert_eq!(msi.message_address, 0x1_FEE0_0000);
+        assert_eq!(msi.message_data, 0x4021);
+        assert_eq!(msi.mask_bits, Some(0xC));
+        assert_eq!(msi.pending_bits, Some(0x4));
+        assert_eq!(msi.length(), 0x18);
+    }
+
+    #[test]
+    fn decodes_32bit_and_unmaskable_layouts() {
+        // 32-bit, no masking: data follows the address
+        let msi = decode(&[
+            0x05, 0x00, 0x01, 0x00, 0x00, 0x10, 0xE0, 0xFE, 0x30, 0x00, 0x00, 0x00,
+        ]);
+        assert!(!msi.message_control.address_64bit_capable());
+        assert_eq!(msi.message_address, 0xFEE0_1000);
+        assert_eq!(msi.message_data, 0x0030);
+        assert_eq!(msi.mask_bits, None);
+        assert_eq!(msi.length(), 0x0C);
+
+        // 64-bit, no masking: data follows the upper address
+        let msi = decode(&[
+            0x05, 0x00, 0x80, 0x00, 0x00, 0x20, 0xE0, 0xFE, 0x00, 0x00, 0x00, 0x00, 0x31, 0x00,
+            0x00, 0x00,
+        ]);
+        assert_eq!(msi.message_address, 0xFEE0_2000);
+        assert_eq!(msi.message_data, 0x0031);
+        assert_eq!(msi.pending_bits, None);
+        assert_eq!(msi.length(), 0x10);
+
+        // 32-bit with masking: mask and pending bits shifted back
+        let msi = decode(&[
+            0x05, 0x00, 0x00, 0x01, 0x00, 0x30, 0xE0, 0xFE, 0x32, 0x00, 0x00, 0x00, 0x01, 0x00,
+            0x00, 0x00, 0x02, 0x00, 0x00, 0x00,
+        ]);
+        assert_eq!(msi.message_data, 0x0032);
+        assert_eq!(msi.mask_bits, Some(1));
+        assert_eq!(msi.pending_bits, Some(2));
+        assert_eq!(msi.length(), 0x14);
+    }
+
+    #[test]
+    fn multiple_message_fields() {
+        // 32 vectors capable, 1 enabled
+        let mut control = PciMsiMessageControl(0x000A);
+        assert_eq!(control.multiple_message_capable(), 32);
+        assert_eq!(control.multiple_message_enable(), 1);
+
+        // Reserved encodings are clamped to 32 vectors
+        assert_eq!(PciMsiMessageControl(0x000E).multiple_message_capable(), 32);
+
+        control.set_multiple_message_enable(16);
+        control.set_multiple_message_capable(3);
+        assert_eq!(control.multiple_message_enable(), 16);
+        assert_eq!(control.multiple_message_capable(), 2);
+        assert_eq!(control.bits(), 0x0042);
+
+        // Counts above 32 vectors never produce the reserved encodings
+        for vectors in [33, 64, 255] {
+            control.set_multiple_message_capable(vectors);
+            control.set_multiple_message_enable(vectors);
+            assert_eq!(control.bits(), 0x005A);
+        }
+
+        control.set_multiple_message_enable(0);
+        assert_eq!(control.multiple_message_enable(), 1);
+    }
+}
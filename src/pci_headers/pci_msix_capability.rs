@@ -0,0 +1,155 @@
+use crate::PciInfoError;
+
+use super::pci_config_buffer::{write_u16_at, write_u32_at};
+use super::{PciCapability, PciCapabilityId, PciConfigAccess, PciGenericDeviceHeader};
+
+pci_register_bits! {
+    /// The Message Control register of the MSI-X capability.
+    pub struct PciMsixMessageControl(u16) {
+        /// All the vectors of the function are masked.
+        function_mask, set_function_mask: 14 => "FunctionMask";
+        /// MSI-X is enabled.
+        msix_enable, set_msix_enable: 15 => "Enable";
+    }
+}
+
+impl PciMsixMessageControl {
+    /// The number of entries of the MSI-X table (the decoded Table Size field).
+    pub fn table_size(&self) -> u16 {
+        self.field(0, 0x7FF) + 1
+    }
+
+    /// Sets the number of entries of the MSI-X table.
+    pub fn set_table_size(&mut self, entries: u16) {
+        self.set_field(0, 0x7FF, entries.max(1) - 1)
+    }
+}
+
+/// The MSI-X capability (capability ID 11h) of a PCI function.
+///
+/// The format of the capability in PCI configuration space is the following.
+///
+///  ```text
+/// +---------+-------------+--------------+----------------+----------------+
+/// | Offset  | Bits 31-24  | Bits 23-16   | Bits 15-8      | Bits 7-0       |
+/// +---------+-------------+--------------+----------------+----------------+
+/// |   0x00  |      Message Control       | Next pointer   | Capability ID  |
+/// |   0x04  |                 Table Offset                    | Table BIR  |
+/// |   0x08  |                  PBA Offset                     |  PBA BIR   |
+/// +---------+-------------+--------------+----------------+----------------+
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PciMsixCapability {
+    pub message_control: PciMsixMessageControl,
+    pub table_bir: u8,
+    pub table_offset: u32,
+    pub pba_bir: u8,
+    pub pba_offset: u32,
+}
+
+impl PciMsixCapability {
+    pub const ID: PciCapabilityId = PciCapabilityId::MsiX;
+    /// The length of the capability in configuration space.
+    pub const LENGTH: usize = 0x0C;
+
+    /// Decodes an MSI-X capability found in the capability list.
+    pub fn with_capability<A: PciConfigAccess + ?Sized>(
+        cap: &PciCapability<'_, A>,
+    ) -> Result<Self, PciInfoError> {
+        cap.assert_id(Self::ID)?;
+
+        let table = cap.read_u32(0x04)?;
+        let pba = cap.read_u32(0x08)?;
+
+        Ok(Self {
+            message_control: PciMsixMessageControl(cap.read_u16(0x02)?),
+            table_bir: (table & 0x7) as u8,
+            table_offset: table & !0x7,
+            pba_bir: (pba & 0x7) as u8,
+            pba_offset: pba & !0x7,
+        })
+    }
+
+    /// Encodes the capability as it appears in configuration space, with a
+    /// null next capability pointer.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0u8; Self::LENGTH];
+        bytes[0x00] = Self::ID.as_code();
+        write_u16_at(&mut bytes, 0x02, self.message_control.bits());
+        write_u32_at(
+            &mut bytes,
+            0x04,
+            self.table_offset & !0x7 | (self.table_bir & 0x7) as u32,
+        );
+        write_u32_at(
+            &mut bytes,
+            0x08,
+            self.pba_offset & !0x7 | (self.pba_bir & 0x7) as u32,
+        );
+        bytes
+    }
+
+    /// Returns the address of the MSI-X table, using the base address
+    /// registers of `header`. Returns `None` if the table BIR does not
+    /// indicate a valid memory BAR.
+    pub fn table_address(&self, header: &PciGenericDeviceHeader) -> Option<u64> {
+        memory_bar_address(header, self.table_bir).map(|base| base + self.table_offset as u64)
+    }
+
+    /// Returns the address of the MSI-X Pending Bit Array, using the base
+    /// address registers of `header`. Returns `None` if the PBA BIR does not
+    /// indicate a valid memory BAR.
+    pub fn pba_address(&self, header: &PciGenericDeviceHeader) -> Option<u64> {
+        memory_bar_address(header, self.pba_bir).map(|base| base + self.pba_offset as u64)
+    }
+}
+
+fn memory_bar_address(header: &PciGenericDeviceHeader, bir: u8) -> Option<u64> {
+    header
+        .bars()
+        .into_iter()
+        .find(|bar| bar.index() == bir)
+        // I/O space BARs cannot hold MSI-X structures
+        .filter(|bar| bar.is_memory())
+        .map(|bar| bar.base_address())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_headers::test_support::decode_capability;
+    use crate::pci_headers::PciSpecializedHeader;
+
+    #[test]
+    fn decodes_msix_table_and_pba() {
+        // 64 entries, enabled, table in BAR0 at 0x2000, PBA in BAR2 at 0x3000
+        let mut body = vec![0x3F, 0x80];
+        body.extend_from_slice(&0x0000_2000u32.to_le_bytes());
+        body.extend_from_slice(&0x0000_3002u32.to_le_bytes());
+
+        let msix = decode_capability(
+            PciMsixCapability::ID,
+            &body,
+            PciMsixCapability::with_capability,
+        );
+
+        let mut config = [0u8; 64];
+        // BAR0 + BAR1: 64-bit memory at 0x1_2340_0000, BAR2: 32-bit memory
+        config[0x10..0x14].copy_from_slice(&0x2340_0004u32.to_le_bytes());
+        config[0x14..0x18].copy_from_slice(&0x0000_0001u32.to_le_bytes());
+        config[0x18..0x1C].copy_from_slice(&0xFE00_0000u32.to_le_bytes());
+
+        assert!(msix.message_control.msix_enable());
+        assert!(!msix.message_control.function_mask());
+        assert_eq!(msix.message_control.table_size(), 64);
+
+        let Ok(PciSpecializedHeader::GenericDevice(header)) =
+            PciSpecializedHeader::read_subheader(0, &config, true)
+        else {
+            panic!("unexpected header type");
+        };
+
+        assert_eq!(msix.table_address(&header), Some(0x1_2340_2000));
+        assert_eq!(msix.pba_address(&header), Some(0xFE00_3000));
+    }
+}
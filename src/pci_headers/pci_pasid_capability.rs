@@ -0,0 +1,97 @@
+use crate::PciInfoError;
+
+use super::{PciConfigAccess, PciExtendedCapability, PciExtendedCapabilityId};
+
+pci_register_bits! {
+    /// The PASID Capability register of the PASID capability.
+    pub struct PciPasidCapabilities(u16) {
+        /// The Execute Requested bit of PASID TLP prefixes is supported.
+        execute_permission_supported, set_execute_permission_supported: 1 => "Exec";
+        /// The Privileged Mode Requested bit of PASID TLP prefixes is
+        /// supported.
+        privileged_mode_supported, set_privileged_mode_supported: 2 => "Priv";
+        /// Translated requests with a PASID are supported.
+        translated_requests_with_pasid_supported, set_translated_requests_with_pasid_supported: 3 => "TranslatedReq";
+    }
+}
+
+impl PciPasidCapabilities {
+    /// The width, in bits, of the PASIDs supported by the function; a
+    /// width of 20 bits allows the largest number of address spaces.
+    pub fn max_pasid_width(&self) -> u8 {
+        self.field(8, 0x1F) as u8
+    }
+}
+
+pci_register_bits! {
+    /// The PASID Control register of the PASID capability.
+    pub struct PciPasidControl(u16) {
+        /// The function is allowed to send and receive PASID TLP prefixes.
+        enable, set_enable: 0 => "Enable";
+        /// The function is allowed to request execute permission.
+        execute_permission_enable, set_execute_permission_enable: 1 => "Exec";
+        /// The function is allowed to request privileged mode.
+        privileged_mode_enable, set_privileged_mode_enable: 2 => "Priv";
+        /// The function is allowed to send translated requests with a
+        /// PASID.
+        translated_requests_with_pasid_enable, set_translated_requests_with_pasid_enable: 3 => "TranslatedReq";
+    }
+}
+
+/// The Process Address Space ID extended capability (extended capability
+/// ID 001Bh) of a PCI Express function, which allows the function to tag
+/// its requests with the address space (e.g. the process) they target.
+///
+/// The format of the capability in PCI configuration space is the following.
+///
+///  ```text
+/// +---------+-------------+--------------+----------------+----------------+
+/// | Offset  | Bits 31-24  | Bits 23-16   | Bits 15-8      | Bits 7-0       |
+/// +---------+-------------+--------------+----------------+----------------+
+/// |   0x00  | Next offset | Version      |       Extended capability ID    |
+/// |   0x04  |      PASID Control         |        PASID Capability         |
+/// +---------+-------------+--------------+----------------+----------------+
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PciPasidCapability {
+    pub capabilities: PciPasidCapabilities,
+    pub control: PciPasidControl,
+}
+
+impl PciPasidCapability {
+    pub const ID: PciExtendedCapabilityId = PciExtendedCapabilityId::ProcessAddressSpaceId;
+    pub const LENGTH: usize = 0x08;
+
+    /// Decodes a PASID capability found in the extended capability list.
+    pub fn with_capability<A: PciConfigAccess + ?Sized>(
+        cap: &PciExtendedCapability<'_, A>,
+    ) -> Result<Self, PciInfoError> {
+        cap.assert_id(Self::ID)?;
+
+        Ok(Self {
+            capabilities: PciPasidCapabilities(cap.read_u16(0x04)?),
+            control: PciPasidControl(cap.read_u16(0x06)?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_headers::test_support::decode_extended_capability;
+
+    #[test]
+    fn decodes_pasid_width_and_modes() {
+        let pasid = decode_extended_capability(
+            PciPasidCapability::ID,
+            &[0x04, 0x14, 0x01, 0x00],
+            PciPasidCapability::with_capability,
+        );
+
+        assert_eq!(pasid.capabilities.max_pasid_width(), 20);
+        assert!(!pasid.capabilities.execute_permission_supported());
+        assert!(pasid.capabilities.privileged_mode_supported());
+        assert!(pasid.control.enable());
+        assert!(!pasid.control.privileged_mode_enable());
+    }
+}
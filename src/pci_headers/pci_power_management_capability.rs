@@ -0,0 +1,278 @@
+use crate::PciInfoError;
+
+use super::pci_config_buffer::write_u16_at;
+use super::{PciCapability, PciCapabilityId, PciConfigAccess};
+
+/// A power state of a PCI function.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum PciPowerState {
+    /// Fully on.
+    D0,
+    /// Light sleep, optional.
+    D1,
+    /// Deep sleep, optional.
+    D2,
+    /// Off, with power still applied.
+    D3Hot,
+    /// Off, with main power removed. This state is never reported by the
+    /// Power Management Control/Status register, as the function cannot be
+    /// accessed in it.
+    D3Cold,
+}
+
+impl PciPowerState {
+    /// Create a `PciPowerState` from the value of the PowerState field
+    /// of the Power Management Control/Status register.
+    pub fn from_code(code: u8) -> Self {
+        match code & 0x3 {
+            0 => Self::D0,
+            1 => Self::D1,
+            2 => Self::D2,
+            _ => Self::D3Hot,
+        }
+    }
+
+    /// Gets the value of the PowerState field of the Power Management
+    /// Control/Status register that this `PciPowerState` represents.
+    /// `D3Cold` is encoded as `D3Hot`.
+    pub fn as_code(&self) -> u8 {
+        match self {
+            Self::D0 => 0,
+            Self::D1 => 1,
+            Self::D2 => 2,
+            Self::D3Hot | Self::D3Cold => 3,
+        }
+    }
+}
+
+pci_register_bits! {
+    /// The Power Management Capabilities (PMC) register.
+    pub struct PciPowerManagementCapabilities(u16) {
+        /// A PCI clock is required to generate PME#.
+        pme_clock, set_pme_clock: 3 => "PMEClk";
+        /// The function is immediately ready on return to D0.
+        immediate_readiness_on_return_to_d0, set_immediate_readiness_on_return_to_d0: 4 => "ImmReady";
+        /// A device specific initialization is required after transitioning to D0.
+        device_specific_initialization, set_device_specific_initialization: 5 => "DSI";
+        /// The D1 power state is supported.
+        d1_support, set_d1_support: 9 => "D1";
+        /// The D2 power state is supported.
+        d2_support, set_d2_support: 10 => "D2";
+        /// PME# can be asserted from D0.
+        pme_support_d0, set_pme_support_d0: 11 => "PME-D0";
+        /// PME# can be asserted from D1.
+        pme_support_d1, set_pme_support_d1: 12 => "PME-D1";
+        /// PME# can be asserted from D2.
+        pme_support_d2, set_pme_support_d2: 13 => "PME-D2";
+        /// PME# can be asserted from D3hot.
+        pme_support_d3hot, set_pme_support_d3hot: 14 => "PME-D3hot";
+        /// PME# can be asserted from D3cold.
+        pme_support_d3cold, set_pme_support_d3cold: 15 => "PME-D3cold";
+    }
+}
+
+impl PciPowerManagementCapabilities {
+    /// The version of the PCI Power Management specification implemented.
+    pub fn version(&self) -> u8 {
+        self.field(0, 0x7) as u8
+    }
+
+    /// Sets the version of the PCI Power Management specification implemented.
+    pub fn set_version(&mut self, version: u8) {
+        self.set_field(0, 0x7, version as u16)
+    }
+
+    /// The maximum current drawn from the auxiliary power source in D3cold,
+    /// in milliamperes.
+    pub fn aux_current_ma(&self) -> u16 {
+        const AUX_CURRENT_MA: [u16; 8] = [0, 55, 100, 160, 220, 270, 320, 375];
+        AUX_CURRENT_MA[self.field(6, 0x7) as usize]
+    }
+
+    /// Returns true if the function supports the specified power state.
+    pub fn supports_state(&self, state: PciPowerState) -> bool {
+        match state {
+            PciPowerState::D1 => self.d1_support(),
+            PciPowerState::D2 => self.d2_support(),
+            _ => true,
+        }
+    }
+
+    /// Returns true if the function can assert PME# from the specified
+    /// power state.
+    pub fn pme_supported(&self, state: PciPowerState) -> bool {
+        match state {
+            PciPowerState::D0 => self.pme_support_d0(),
+            PciPowerState::D1 => self.pme_support_d1(),
+            PciPowerState::D2 => self.pme_support_d2(),
+            PciPowerState::D3Hot => self.pme_support_d3hot(),
+            PciPowerState::D3Cold => self.pme_support_d3cold(),
+        }
+    }
+}
+
+pci_register_bits! {
+    /// The Power Management Control/Status (PMCSR) register.
+    pub struct PciPowerManagementControlStatus(u16) {
+        /// The function keeps its configuration when transitioning from D3hot to D0.
+        no_soft_reset, set_no_soft_reset: 3 => "NoSoftRst";
+        /// The function is allowed to assert PME#.
+        pme_enable, set_pme_enable: 8 => "PME-Enable";
+        /// The function asserted PME#.
+        pme_status, set_pme_status: 15 => "PME-Status";
+    }
+}
+
+impl PciPowerManagementControlStatus {
+    /// The current power state of the function.
+    pub fn power_state(&self) -> PciPowerState {
+        PciPowerState::from_code(self.field(0, 0x3) as u8)
+    }
+
+    /// Sets the current power state of the function.
+    pub fn set_power_state(&mut self, state: PciPowerState) {
+        self.set_field(0, 0x3, state.as_code() as u16)
+    }
+
+    /// The data currently selected in the Data register.
+    pub fn data_select(&self) -> u8 {
+        self.field(9, 0xF) as u8
+    }
+
+    /// The scaling factor of the Data register.
+    pub fn data_scale(&self) -> u8 {
+        self.field(13, 0x3) as u8
+    }
+}
+
+/// The Power Management capability (capability ID 01h) of a PCI function.
+///
+/// The format of the capability in PCI configuration space is the following.
+///
+///  ```text
+/// +---------+-------------+--------------+----------------+----------------+
+/// | Offset  | Bits 31-24  | Bits 23-16   | Bits 15-8      | Bits 7-0       |
+/// +---------+-------------+--------------+----------------+----------------+
+/// |   0x00  | Power Management Capab.    | Next pointer   | Capability ID  |
+/// |   0x04  |    Data     | PMCSR_BSE    |  Power Management Control/Status|
+/// +---------+-------------+--------------+----------------+----------------+
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PciPowerManagementCapability {
+    pub capabilities: PciPowerManagementCapabilities,
+    pub control_status: PciPowerManagementControlStatus,
+    pub bridge_support_extensions: u8,
+    pub data: u8,
+}
+
+impl PciPowerManagementCapability {
+    pub const ID: PciCapabilityId = PciCapabilityId::PowerManagement;
+    /// The length of the capability in configuration space.
+    pub const LENGTH: usize = 0x08;
+
+    /// Decodes a Power Management capability found in the capability list.
+    pub fn with_capability<A: PciConfigAccess + ?Sized>(
+        cap: &PciCapability<'_, A>,
+    ) -> Result<Self, PciInfoError> {
+        cap.assert_id(Self::ID)?;
+
+        Ok(Self {
+            capabilities: PciPowerManagementCapabilities(cap.read_u16(0x02)?),
+            control_status: PciPowerManagementControlStatus(cap.read_u16(0x04)?),
+            bridge_support_extensions: cap.read_u8(0x06)?,
+            data: cap.read_u8(0x07)?,
+        })
+    }
+
+    /// Encodes the capability as it appears in configuration space, with a
+    /// null next capability pointer.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0u8; Self::LENGTH];
+        bytes[0x00] = Self::ID.as_code();
+        write_u16_at(&mut bytes, 0x02, self.capabilities.bits());
+        write_u16_at(&mut bytes, 0x04, self.control_status.bits());
+        bytes[0x06] = self.bridge_support_extensions;
+        bytes[0x07] = self.data;
+        bytes
+    }
+
+    /// The current power state of the function.
+    pub fn power_state(&self) -> PciPowerState {
+        self.control_status.power_state()
+    }
+
+    /// Returns true if the function can wake the system, that is, if it
+    /// can assert PME# from D3hot or D3cold.
+    pub fn can_wake_from_d3(&self) -> bool {
+        self.capabilities.pme_support_d3hot() || self.capabilities.pme_support_d3cold()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_headers::test_support::decode_capability;
+
+    // `cap` contains the whole capability, including its id and next pointer
+    fn decode(cap: &[u8]) -> PciPowerManagementCapability {
+        decode_capability(
+            PciPowerManagementCapability::ID,
+            &cap[2..],
+            PciPowerManagementCapability::with_capability,
+        )
+    }
+
+    #[test]
+    fn decodes_capabilities_and_control_status() {
+        // Version 3, D1 and D2 supported, PME# from D0 to D3hot; D3hot with
+        // PME# enabled and asserted, no soft reset.
+        let pm = decode(&[0x01, 0x00, 0x03, 0x7E, 0x0B, 0x81, 0x00, 0x00]);
+
+        assert_eq!(pm.capabilities.version(), 3);
+        assert_eq!(pm.capabilities.aux_current_ma(), 0);
+        assert!(pm.capabilities.supports_state(PciPowerState::D1));
+        assert!(pm.capabilities.supports_state(PciPowerState::D2));
+        assert!(pm.capabilities.pme_supported(PciPowerState::D3Hot));
+        assert!(!pm.capabilities.pme_supported(PciPowerState::D3Cold));
+        assert!(pm.can_wake_from_d3());
+
+        assert_eq!(pm.power_state(), PciPowerState::D3Hot);
+        assert!(pm.control_status.no_soft_reset());
+        assert!(pm.control_status.pme_enable());
+        assert!(pm.control_status.pme_status());
+        assert_eq!(pm.control_status.data_select(), 0);
+
+        // 375mA of aux current, no D1 and D2, PME# from D0 and D3cold only
+        let pm = decode(&[0x01, 0x00, 0xC3, 0x89, 0x00, 0x00, 0x00, 0x00]);
+
+        assert_eq!(pm.capabilities.aux_current_ma(), 375);
+        assert!(!pm.capabilities.supports_state(PciPowerState::D1));
+        assert!(!pm.capabilities.supports_state(PciPowerState::D2));
+        assert!(pm.capabilities.supports_state(PciPowerState::D3Hot));
+        assert!(pm.capabilities.pme_supported(PciPowerState::D0));
+        assert!(!pm.capabilities.pme_supported(PciPowerState::D3Hot));
+        assert!(pm.can_wake_from_d3());
+        assert_eq!(pm.power_state(), PciPowerState::D0);
+    }
+
+    #[test]
+    fn power_state_round_trips() {
+        for state in [
+            PciPowerState::D0,
+            PciPowerState::D1,
+            PciPowerState::D2,
+            PciPowerState::D3Hot,
+        ] {
+            assert_eq!(PciPowerState::from_code(state.as_code()), state);
+
+            let mut control_status = PciPowerManagementControlStatus(0x8100);
+            control_status.set_power_state(state);
+            assert_eq!(control_status.power_state(), state);
+            assert_eq!(control_status.bits() & !0x3, 0x8100);
+        }
+
+        // D3cold cannot be reported and is encoded as D3hot
+        assert_eq!(PciPowerState::D3Cold.as_code(), 3);
+        assert_eq!(PciPowerState::from_code(3), PciPowerState::D3Hot);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::PciInfoError;
+
+use super::{PciConfigAccess, PciExtendedCapability, PciExtendedCapabilityId};
+
+pci_register_bits! {
+    /// The Page Request Control register of the Page Request Interface
+    /// capability.
+    pub struct PciPriControl(u16) {
+        /// The function is allowed to make page requests.
+        enable, set_enable: 0 => "Enable";
+        /// Clears the page request credit counter and the pending request
+        /// state (write only).
+        reset, set_reset: 1 => "Reset";
+    }
+}
+
+pci_register_bits! {
+    /// The Page Request Status register of the Page Request Interface
+    /// capability.
+    pub struct PciPriStatus(u16) {
+        /// A Page Request Group Response with a Response Failure was
+        /// received.
+        response_failure, set_response_failure: 0 => "RF";
+        /// A Page Request Group Response with an unexpected index was
+        /// received.
+        unexpected_page_request_group_index, set_unexpected_page_request_group_index: 1 => "UPRGI";
+        /// The function is stopped and has no outstanding page requests.
+        stopped, set_stopped: 8 => "Stopped";
+        /// The function expects a PASID on Page Request Group Responses
+        /// when the request had one.
+        prg_response_pasid_required, set_prg_response_pasid_required: 15 => "PASIDRequired";
+    }
+}
+
+/// The Page Request Interface extended capability (extended capability ID
+/// 0013h) of a PCI Express function, which allows the function to request
+/// that pages are made resident, resolving ATS translation faults.
+///
+/// The format of the capability in PCI configuration space is the following.
+///
+///  ```text
+/// +---------+-------------+--------------+----------------+----------------+
+/// | Offset  | Bits 31-24  | Bits 23-16   | Bits 15-8      | Bits 7-0       |
+/// +---------+-------------+--------------+----------------+----------------+
+/// |   0x00  | Next offset | Version      |       Extended capability ID    |
+/// |   0x04  |        Status              |           Control               |
+/// |   0x08  |           Outstanding Page Request Capacity                  |
+/// |   0x0C  |           Outstanding Page Request Allocation                |
+/// +---------+-------------+--------------+----------------+----------------+
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PciPriCapability {
+    pub control: PciPriControl,
+    pub status: PciPriStatus,
+    /// The maximum number of outstanding page requests that the function
+    /// can issue.
+    pub outstanding_page_request_capacity: u32,
+    /// The number of outstanding page requests that the function is
+    /// allowed to issue.
+    pub outstanding_page_request_allocation: u32,
+}
+
+impl PciPriCapability {
+    pub const ID: PciExtendedCapabilityId = PciExtendedCapabilityId::PageRequestInterface;
+    pub const LENGTH: usize = 0x10;
+
+    /// Decodes a PRI capability found in the extended capability list.
+    pub fn with_capability<A: PciConfigAccess + ?Sized>(
+        cap: &PciExtendedCapability<'_, A>,
+    ) -> Result<Self, PciInfoError> {
+        cap.assert_id(Self::ID)?;
+
+        Ok(Self {
+            control: PciPriControl(cap.read_u16(0x04)?),
+            status: PciPriStatus(cap.read_u16(0x06)?),
+            outstanding_page_request_capacity: cap.read_u32(0x08)?,
+            outstanding_page_request_allocation: cap.read_u32(0x0C)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_headers::test_support::decode_extended_capability;
+
+    #[test]
+    fn decodes_page_request_capacity() {
+        let mut body = vec![0x01, 0x00, 0x00, 0x81];
+        body.extend_from_slice(&512u32.to_le_bytes());
+        body.extend_from_slice(&256u32.to_le_bytes());
+
+        let pri = decode_extended_capability(
+            PciPriCapability::ID,
+            &body,
+            PciPriCapability::with_capability,
+        );
+
+        assert!(pri.control.enable());
+        assert!(pri.status.stopped());
+        assert!(pri.status.prg_response_pasid_required());
+        assert_eq!(pri.outstanding_page_request_capacity, 512);
+        assert_eq!(pri.outstanding_page_request_allocation, 256);
+    }
+}
@@ -0,0 +1,234 @@
+use crate::PciInfoError;
+
+use super::{PciConfigAccess, PciExtendedCapability, PciExtendedCapabilityId};
+
+/// A size of a resizable BAR, encoded as a power of two starting from 1 MB
+/// (encoded value 0) up to 8 EB (encoded value 43).
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct PciResizableBarSize(u8);
+
+impl PciResizableBarSize {
+    /// The largest encoded value of a BAR size that fits in 64 bits.
+    pub const MAX_CODE: u8 = 43;
+
+    /// Create a `PciResizableBarSize` from its encoded value, where `n`
+    /// represents a size of 2^(n + 20) bytes.
+    pub fn from_code(code: u8) -> Self {
+        Self(code)
+    }
+
+    /// Gets the encoded value that this `PciResizableBarSize` represents
+    pub fn as_code(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns the size in bytes, or `None` if the encoded value does not
+    /// represent a valid size.
+    pub fn bytes(&self) -> Option<u64> {
+        if self.0 <= Self::MAX_CODE {
+            Some(1u64 << (self.0 + 20))
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for PciResizableBarSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const UNITS: [&str; 5] = ["MB", "GB", "TB", "PB", "EB"];
+
+        match self.0 {
+            code if code <= Self::MAX_CODE => {
+                write!(f, "{}{}", 1u32 << (code % 10), UNITS[code as usize / 10])
+            }
+            unk => write!(f, "unknown size ({unk})"),
+        }
+    }
+}
+
+/// A BAR controlled by a Resizable BAR capability.
+#[derive(Clone, Debug)]
+pub struct PciResizableBar {
+    /// The index of the BAR (0 to 5).
+    pub bar_index: u8,
+    /// The sizes supported by the BAR, in ascending order.
+    pub supported_sizes: Vec<PciResizableBarSize>,
+    /// The size currently programmed in the BAR Size field of the control
+    /// register.
+    pub current_size: PciResizableBarSize,
+}
+
+impl PciResizableBar {
+    /// Returns the largest size supported by the BAR.
+    pub fn max_size(&self) -> Option<PciResizableBarSize> {
+        self.supported_sizes.last().copied()
+    }
+
+    /// Returns true if the BAR is currently set to the largest size it
+    /// supports.
+    pub fn is_max_size(&self) -> bool {
+        self.max_size().is_some_and(|max| self.current_size >= max)
+    }
+}
+
+/// The Resizable BAR extended capability (extended capability ID 0015h),
+/// or the VF Resizable BAR extended capability (extended capability ID
+/// 0024h) which has the same format and controls the VF BARs of an SR-IOV
+/// physical function.
+///
+/// The format of the capability in PCI configuration space is the following,
+/// with one capability and control register pair for each resizable BAR.
+///
+///  ```text
+/// +---------+-------------+--------------+----------------+----------------+
+/// | Offset  | Bits 31-24  | Bits 23-16   | Bits 15-8      | Bits 7-0       |
+/// +---------+-------------+--------------+----------------+----------------+
+/// |   0x00  | Next offset | Version      |       Extended capability ID    |
+/// |   0x04  |            Resizable BAR Capability Register (0)             |
+/// |   0x08  |            Resizable BAR Control Register (0)                |
+/// |   ....  |                          ...                                 |
+/// |   0x2C  |            Resizable BAR Capability Register (5)             |
+/// |   0x30  |            Resizable BAR Control Register (5)                |
+/// +---------+-------------+--------------+----------------+----------------+
+/// ```
+///
+/// Supported sizes from 1 MB to 128 TB are reported in bits 31:4 of the
+/// capability register, larger ones in bits 31:16 of the control register.
+/// The control register also contains the index of the BAR (bits 2:0),
+/// the number of resizable BARs (bits 7:5, in the first control register
+/// only) and the current size (bits 13:8).
+#[derive(Clone, Debug)]
+pub struct PciResizableBarCapability {
+    /// True if this is a VF Resizable BAR capability, whose BAR indices
+    /// refer to the VF BARs of the SR-IOV capability.
+    pub virtual_functions: bool,
+    pub bars: Vec<PciResizableBar>,
+}
+
+impl PciResizableBarCapability {
+    pub const ID: PciExtendedCapabilityId = PciExtendedCapabilityId::ResizableBar;
+    pub const VF_ID: PciExtendedCapabilityId = PciExtendedCapabilityId::VfResizableBar;
+
+    /// Decodes a Resizable BAR or VF Resizable BAR capability found in the
+    /// extended capability list.
+    pub fn with_capability<A: PciConfigAccess + ?Sized>(
+        cap: &PciExtendedCapability<'_, A>,
+    ) -> Result<Self, PciInfoError> {
+        let virtual_functions = cap.capability_id() == Self::VF_ID;
+
+        if !virtual_functions {
+            cap.assert_id(Self::ID)?;
+        }
+
+        let count = (cap.read_u32(0x08)? >> 5) & 0x7;
+
+        if !(1..=6).contains(&count) {
+            return Err(PciInfoError::ParseError(
+                format!("invalid number of resizable BARs: {count}").into(),
+            ));
+        }
+
+        let bars = (0..count as usize)
+            .map(|i| {
+                let capability = cap.read_u32(0x04 + i * 8)?;
+                let control = cap.read_u32(0x08 + i * 8)?;
+
+                let supported = ((capability >> 4) as u64) | ((control >> 16) as u64) << 28;
+                let supported_sizes = (0..=PciResizableBarSize::MAX_CODE)
+                    .filter(|code| supported & (1 << code) != 0)
+                    .map(PciResizableBarSize::from_code)
+                    .collect();
+
+                Ok(PciResizableBar {
+                    bar_index: (control & 0x7) as u8,
+                    supported_sizes,
+                    current_size: PciResizableBarSize::from_code(((control >> 8) & 0x3F) as u8),
+                })
+            })
+            .collect::<Result<Vec<_>, PciInfoError>>()?;
+
+        Ok(Self {
+            virtual_functions,
+            bars,
+        })
+    }
+
+    /// The length of the capability in configuration space, which depends
+    /// on the number of resizable BARs.
+    pub fn length(&self) -> usize {
+        0x04 + self.bars.len() * 8
+    }
+
+    /// Returns the resizable BAR with the given index, if it is controlled
+    /// by this capability.
+    pub fn bar(&self, bar_index: u8) -> Option<&PciResizableBar> {
+        self.bars.iter().find(|bar| bar.bar_index == bar_index)
+    }
+
+    /// Returns true if all the resizable BARs are set to the largest size
+    /// they support.
+    pub fn is_fully_enabled(&self) -> bool {
+        self.bars.iter().all(|bar| bar.is_max_size())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_headers::test_support::decode_extended_capability;
+
+    fn decode(id: PciExtendedCapabilityId, regs: &[u32]) -> PciResizableBarCapability {
+        let body = regs
+            .iter()
+            .flat_map(|r| r.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        decode_extended_capability(id, &body, PciResizableBarCapability::with_capability)
+    }
+
+    #[test]
+    fn decodes_supported_and_current_sizes() {
+        // BAR 0: 16 MB only. BAR 2: 256 MB to 64 GB (bits 12-20 of the
+        // capability register), currently 256 MB, plus 256 TB in the control
+        // register.
+        let rebar = decode(
+            PciResizableBarCapability::ID,
+            &[0x0000_0100, 0x0000_0440, 0x001F_F000, 0x0001_0802],
+        );
+
+        assert!(!rebar.virtual_functions);
+        assert_eq!(rebar.length(), 0x14);
+        assert_eq!(rebar.bars.len(), 2);
+
+        let bar0 = rebar.bar(0).unwrap();
+        assert_eq!(bar0.supported_sizes, [PciResizableBarSize::from_code(4)]);
+        assert_eq!(bar0.current_size.bytes(), Some(16 << 20));
+        assert!(bar0.is_max_size());
+
+        let bar2 = rebar.bar(2).unwrap();
+        let sizes = bar2
+            .supported_sizes
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sizes,
+            ["256MB", "512MB", "1GB", "2GB", "4GB", "8GB", "16GB", "32GB", "64GB", "256TB"]
+        );
+        assert_eq!(bar2.current_size.to_string(), "256MB");
+        assert!(!bar2.is_max_size());
+        assert!(!rebar.is_fully_enabled());
+    }
+
+    #[test]
+    fn decodes_vf_resizable_bar() {
+        let rebar = decode(
+            PciResizableBarCapability::VF_ID,
+            &[0x0000_00F0, 0x0000_0320],
+        );
+
+        assert!(rebar.virtual_functions);
+        assert_eq!(rebar.bars[0].max_size().unwrap().to_string(), "8MB");
+        assert!(rebar.is_fully_enabled());
+    }
+}
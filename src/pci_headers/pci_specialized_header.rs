@@ -1,9 +1,11 @@
 use crate::PciInfoError;
 
 use super::{
-    pci_config_buffer::PciConfigBuffer, pci_generic_device_header::PciGenericDeviceHeader,
+    pci_config_buffer::{PciConfigBuffer, PciConfigBufferMut},
+    pci_generic_device_header::PciGenericDeviceHeader,
     pci_to_cardbus_bridge_header::PciToCardbusBridgeHeader,
-    pci_to_pci_bridge_header::PciToPciBridgeHeader, PciCommonHeader,
+    pci_to_pci_bridge_header::PciToPciBridgeHeader,
+    PciCommonHeader, PciConfigAccess,
 };
 
 /// Enumeration of the supported specialized headers of PCI devices.
@@ -59,4 +61,130 @@ impl PciSpecializedHeader {
             _ => Err(PciInfoError::UnknownPciHeaderType(header_type)),
         }
     }
+
+    /// Reads the data of the sub-header from the configuration space
+    /// accessed through `config`, reading only the bytes of the header.
+    pub fn with_access<A: PciConfigAccess + ?Sized>(
+        header_type: u8,
+        config: &A,
+    ) -> Result<Self, PciInfoError> {
+        let Some(len) = Self::length_of_subheader(header_type) else {
+            return Err(PciInfoError::UnknownPciHeaderType(header_type));
+        };
+
+        let mut bytes = [0u8; PciCommonHeader::MAX_HEADER_LEN];
+        config.read_bytes(0, &mut bytes[..len])?;
+        Self::read_subheader(header_type, &bytes[..len], true)
+    }
+
+    /// Returns the header type of this sub-header, as found in the
+    /// [`PciCommonHeader::header_type`] field (without the multi-function bit).
+    pub fn header_type(&self) -> u8 {
+        match self {
+            PciSpecializedHeader::GenericDevice(_) => PciGenericDeviceHeader::ID,
+            PciSpecializedHeader::PciToPciBridge(_) => PciToPciBridgeHeader::ID,
+            PciSpecializedHeader::PciToCardbusBridge(_) => PciToCardbusBridgeHeader::ID,
+        }
+    }
+
+    /// Writes the data of the sub-header to a memory buffer, at the same
+    /// offsets used by [`PciSpecializedHeader::read_subheader`]. Reserved
+    /// bytes are left untouched.
+    /// - `bytes` : specifies the slice the data is written to
+    /// - `includes_common_header` : true if the slice starts at the beginning
+    ///   of the common header, false if the slice contains only the sub-header data
+    pub fn write_subheader(
+        &self,
+        bytes: &mut [u8],
+        includes_common_header: bool,
+    ) -> Result<(), PciInfoError> {
+        let bytes_offset = if includes_common_header {
+            0
+        } else {
+            PciCommonHeader::COMMON_HEADER_LEN
+        };
+
+        let mut pci_cfg = PciConfigBufferMut::new(bytes, bytes_offset);
+
+        match self {
+            PciSpecializedHeader::GenericDevice(h) => h.write_pci_cfg(&mut pci_cfg),
+            PciSpecializedHeader::PciToPciBridge(h) => h.write_pci_cfg(&mut pci_cfg),
+            PciSpecializedHeader::PciToCardbusBridge(h) => h.write_pci_cfg(&mut pci_cfg),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(bytes: &[u8]) -> Vec<u8> {
+        let common = PciCommonHeader::with_bytes(bytes).unwrap();
+        let specialized =
+            PciSpecializedHeader::read_subheader(common.header_type, bytes, true).unwrap();
+        let length = PciSpecializedHeader::length_of_subheader(common.header_type).unwrap();
+
+        assert_eq!(specialized.header_type(), common.header_type & 0x7F);
+
+        let mut written = vec![0u8; length];
+        common.write_bytes(&mut written).unwrap();
+        specialized.write_subheader(&mut written, true).unwrap();
+
+        let mut subheader_only = vec![0u8; length - PciCommonHeader::COMMON_HEADER_LEN];
+        specialized
+            .write_subheader(&mut subheader_only, false)
+            .unwrap();
+        assert_eq!(
+            subheader_only,
+            written[PciCommonHeader::COMMON_HEADER_LEN..]
+        );
+
+        written
+    }
+
+    #[test]
+    fn round_trips_linux_fixtures() {
+        let mut count = 0;
+
+        for arch in ["amd64", "aarch64"] {
+            let pci_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("test-data/linux")
+                .join(arch)
+                .join("pci");
+
+            for bus_dir in std::fs::read_dir(pci_dir).unwrap() {
+                let bus_dir = bus_dir.unwrap();
+
+                if !bus_dir.file_type().unwrap().is_dir() {
+                    continue;
+                }
+
+                for file in std::fs::read_dir(bus_dir.path()).unwrap() {
+                    let bytes = std::fs::read(file.unwrap().path()).unwrap();
+
+                    assert_eq!(round_trip(&bytes), bytes);
+                    count += 1;
+                }
+            }
+        }
+
+        assert!(count > 0);
+    }
+
+    #[test]
+    fn round_trips_cardbus_bridge() {
+        let mut bytes = (0..PciToCardbusBridgeHeader::LENGTH as u8).collect::<Vec<_>>();
+        bytes[0x0E] = 0x82;
+        // Reserved byte
+        bytes[0x15] = 0;
+
+        assert_eq!(round_trip(&bytes), bytes);
+
+        let mut short = [0u8; 64];
+        let common = PciCommonHeader::with_bytes(&bytes).unwrap();
+        let specialized = PciSpecializedHeader::read_subheader(2, &bytes, true).unwrap();
+
+        assert!(specialized.write_subheader(&mut short, true).is_err());
+        assert!(common.write_bytes(&mut short[0..8]).is_err());
+    }
 }
@@ -0,0 +1,206 @@
+use crate::{PciInfoError, PciLocation};
+
+use super::{PciConfigAccess, PciExtendedCapability, PciExtendedCapabilityId};
+
+pci_register_bits! {
+    /// The SR-IOV Capabilities register of the SR-IOV capability.
+    pub struct PciSrIovCapabilities(u32) {
+        /// VF migration is supported.
+        vf_migration_capable, set_vf_migration_capable: 0 => "Migration";
+        /// The ARI Capable Hierarchy bit is preserved across resets.
+        ari_capable_hierarchy_preserved, set_ari_capable_hierarchy_preserved: 1 => "ARIHierarchy";
+        /// VFs support 10-bit tags as requesters.
+        vf_10bit_tag_requester_supported, set_vf_10bit_tag_requester_supported: 2 => "10BitTagReq";
+    }
+}
+
+impl PciSrIovCapabilities {
+    /// The MSI/MSI-X vector used for VF migration interrupts.
+    pub fn vf_migration_interrupt_message_number(&self) -> u16 {
+        self.field(21, 0x7FF) as u16
+    }
+}
+
+pci_register_bits! {
+    /// The SR-IOV Control register of the SR-IOV capability.
+    pub struct PciSrIovControl(u16) {
+        /// Virtual functions are enabled.
+        vf_enable, set_vf_enable: 0 => "VFEnable";
+        /// VF migration is enabled.
+        vf_migration_enable, set_vf_migration_enable: 1 => "Migration";
+        /// VF migration interrupts are enabled.
+        vf_migration_interrupt_enable, set_vf_migration_interrupt_enable: 2 => "Interrupt";
+        /// Memory space is enabled for the virtual functions.
+        vf_memory_space_enable, set_vf_memory_space_enable: 3 => "MSE";
+        /// The physical function is in an ARI capable hierarchy.
+        ari_capable_hierarchy, set_ari_capable_hierarchy: 4 => "ARIHierarchy";
+        /// 10-bit tags are enabled for the virtual functions.
+        vf_10bit_tag_requester_enable, set_vf_10bit_tag_requester_enable: 5 => "10BitTagReq";
+    }
+}
+
+pci_register_bits! {
+    /// The SR-IOV Status register of the SR-IOV capability.
+    pub struct PciSrIovStatus(u16) {
+        /// A VF migration was requested.
+        vf_migration_status, set_vf_migration_status: 0 => "MigrationStatus";
+    }
+}
+
+/// The Single Root I/O Virtualization extended capability (extended
+/// capability ID 0010h) of a PCI Express physical function.
+///
+/// The format of the capability in PCI configuration space is the following.
+///
+///  ```text
+/// +---------+-------------+--------------+----------------+----------------+
+/// | Offset  | Bits 31-24  | Bits 23-16   | Bits 15-8      | Bits 7-0       |
+/// +---------+-------------+--------------+----------------+----------------+
+/// |   0x00  | Next offset | Version      |       Extended capability ID    |
+/// |   0x04  |                 SR-IOV Capabilities                          |
+/// |   0x08  |       SR-IOV Status        |         SR-IOV Control          |
+/// |   0x0C  |          TotalVFs          |           InitialVFs            |
+/// |   0x10  |          Reserved          | Func. Dep. Link|     NumVFs      |
+/// |   0x14  |          VF Stride         |        First VF Offset          |
+/// |   0x18  |        VF Device ID        |            Reserved             |
+/// |   0x1C  |                Supported Page Sizes                          |
+/// |   0x20  |                  System Page Size                            |
+/// |   0x24  |               VF BAR0 ... VF BAR5                            |
+/// |   0x3C  |            VF Migration State Array Offset                   |
+/// +---------+-------------+--------------+----------------+----------------+
+/// ```
+#[derive(Clone, Debug)]
+pub struct PciSrIovCapability {
+    pub capabilities: PciSrIovCapabilities,
+    pub control: PciSrIovControl,
+    pub status: PciSrIovStatus,
+    pub initial_vfs: u16,
+    pub total_vfs: u16,
+    pub num_vfs: u16,
+    pub function_dependency_link: u8,
+    pub first_vf_offset: u16,
+    pub vf_stride: u16,
+    pub vf_device_id: u16,
+    pub supported_page_sizes: u32,
+    pub system_page_size: u32,
+    pub vf_base_addr: [u32; 6],
+    pub vf_migration_state_array_offset: u32,
+}
+
+impl PciSrIovCapability {
+    pub const ID: PciExtendedCapabilityId = PciExtendedCapabilityId::SingleRootIoVirtualization;
+    /// The length of the capability in configuration space.
+    pub const LENGTH: usize = 0x40;
+
+    /// Decodes an SR-IOV capability found in the extended capability list.
+    pub fn with_capability<A: PciConfigAccess + ?Sized>(
+        cap: &PciExtendedCapability<'_, A>,
+    ) -> Result<Self, PciInfoError> {
+        cap.assert_id(Self::ID)?;
+
+        Ok(Self {
+            capabilities: PciSrIovCapabilities(cap.read_u32(0x04)?),
+            control: PciSrIovControl(cap.read_u16(0x08)?),
+            status: PciSrIovStatus(cap.read_u16(0x0A)?),
+            initial_vfs: cap.read_u16(0x0C)?,
+            total_vfs: cap.read_u16(0x0E)?,
+            num_vfs: cap.read_u16(0x10)?,
+            function_dependency_link: cap.read_u8(0x12)?,
+            first_vf_offset: cap.read_u16(0x14)?,
+            vf_stride: cap.read_u16(0x16)?,
+            vf_device_id: cap.read_u16(0x1A)?,
+            supported_page_sizes: cap.read_u32(0x1C)?,
+            system_page_size: cap.read_u32(0x20)?,
+            vf_base_addr: [
+                cap.read_u32(0x24)?,
+                cap.read_u32(0x28)?,
+                cap.read_u32(0x2C)?,
+                cap.read_u32(0x30)?,
+                cap.read_u32(0x34)?,
+                cap.read_u32(0x38)?,
+            ],
+            vf_migration_state_array_offset: cap.read_u32(0x3C)?,
+        })
+    }
+
+    /// The page sizes supported by the physical function, in bytes.
+    pub fn supported_page_sizes_bytes(&self) -> Vec<u64> {
+        (0..32)
+            .filter(|bit| self.supported_page_sizes & (1 << bit) != 0)
+            .map(|bit| 4096u64 << bit)
+            .collect()
+    }
+
+    /// Computes the location of the virtual function with the given
+    /// zero-based `index`, given the location `pf` of the physical function.
+    ///
+    /// The routing ID of the virtual function is calculated from the First VF
+    /// Offset and VF Stride fields, carrying into the bus number if needed.
+    /// Note that devices are allowed to change these fields depending on
+    /// the value of NumVFs.
+    pub fn vf_location(&self, pf: PciLocation, index: u16) -> Result<PciLocation, PciInfoError> {
+        let pf_routing_id =
+            (pf.bus() as u32) << 8 | (pf.device() as u32) << 3 | pf.function() as u32;
+        let routing_id =
+            pf_routing_id + self.first_vf_offset as u32 + index as u32 * self.vf_stride as u32;
+
+        if routing_id > 0xFFFF {
+            return Err(PciInfoError::ParseError(
+                format!("routing id of VF {index} of {pf} is beyond the last bus").into(),
+            ));
+        }
+
+        PciLocation::with_segment(
+            pf.segment(),
+            (routing_id >> 8) as u8,
+            ((routing_id >> 3) & 0x1F) as u8,
+            (routing_id & 0x7) as u8,
+        )
+    }
+
+    /// Computes the locations of all the currently enabled virtual functions
+    /// (as reported by NumVFs), given the location `pf` of the physical
+    /// function.
+    pub fn vf_locations(&self, pf: PciLocation) -> Result<Vec<PciLocation>, PciInfoError> {
+        (0..self.num_vfs)
+            .map(|index| self.vf_location(pf, index))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vf_locations_carry_into_bus() {
+        let mut config = vec![0u8; 4096];
+        config[0x100..0x104].copy_from_slice(&0x0001_0010u32.to_le_bytes());
+        // TotalVFs 64, NumVFs 4, First VF offset 0x80, stride 0x40
+        config[0x10E..0x110].copy_from_slice(&64u16.to_le_bytes());
+        config[0x110..0x112].copy_from_slice(&4u16.to_le_bytes());
+        config[0x114..0x116].copy_from_slice(&0x80u16.to_le_bytes());
+        config[0x116..0x118].copy_from_slice(&0x40u16.to_le_bytes());
+
+        let cap = crate::pci_headers::PciExtendedCapabilityIterator::with_bytes(&config)
+            .find_capability(PciSrIovCapability::ID)
+            .unwrap()
+            .unwrap();
+        let sriov = PciSrIovCapability::with_capability(&cap).unwrap();
+        let pf = PciLocation::with_segment(1, 0x3B, 0, 1).unwrap();
+
+        assert_eq!(sriov.total_vfs, 64);
+        assert_eq!(
+            sriov.vf_locations(pf).unwrap(),
+            vec![
+                PciLocation::with_segment(1, 0x3B, 0x10, 1).unwrap(),
+                PciLocation::with_segment(1, 0x3B, 0x18, 1).unwrap(),
+                PciLocation::with_segment(1, 0x3C, 0x00, 1).unwrap(),
+                PciLocation::with_segment(1, 0x3C, 0x08, 1).unwrap(),
+            ]
+        );
+
+        let pf = PciLocation::with_bdf(0xFF, 0x1F, 0).unwrap();
+        assert!(sriov.vf_location(pf, 0).is_err());
+    }
+}
@@ -1,11 +1,43 @@
 use crate::PciInfoError;
 
-use super::pci_config_buffer::PciConfigBuffer;
+use super::pci_config_buffer::{PciConfigBuffer, PciConfigBufferMut};
+use super::{PciBridgeWindow, PciSecondaryStatus};
+
+pci_register_bits! {
+    /// The Bridge Control register of PCI to CardBus bridge headers.
+    pub struct PciCardbusBridgeControl(u16) {
+        /// The bridge responds to parity errors on the CardBus.
+        parity_error_response, set_parity_error_response: 0 => "Parity";
+        /// The bridge forwards SERR# assertions from the CardBus.
+        serr_enable, set_serr_enable: 1 => "SERR";
+        /// The bridge blocks the forwarding of ISA I/O addresses in the
+        /// top 768 bytes of each 1 KiB block of the I/O windows.
+        isa_enable, set_isa_enable: 2 => "ISA";
+        /// The bridge forwards VGA memory and I/O addresses.
+        vga_enable, set_vga_enable: 3 => "VGA";
+        /// Master aborts are reported as target aborts or SERR#.
+        master_abort_mode, set_master_abort_mode: 5 => "MAbort";
+        /// The reset signal of the CardBus is asserted.
+        cardbus_reset, set_cardbus_reset: 6 => ">Reset";
+        /// Interrupts of 16-bit PC Cards are routed through the ExCA
+        /// registers instead of the PCI interrupt.
+        pc_card_16bit_interrupt_enable, set_pc_card_16bit_interrupt_enable: 7 => "16bInt";
+        /// Memory window 0 is prefetchable.
+        memory_0_prefetch_enable, set_memory_0_prefetch_enable: 8 => "Mem0Prefetch";
+        /// Memory window 1 is prefetchable.
+        memory_1_prefetch_enable, set_memory_1_prefetch_enable: 9 => "Mem1Prefetch";
+        /// Posting of memory writes is enabled.
+        write_posting_enable, set_write_posting_enable: 10 => "PostWrite";
+    }
+}
 
 /// The header for PCI to Cardbus bus bridges.
 ///
 /// All fields are the raw values of every non-reserved register part in
-/// the configuration space.
+/// the configuration space. Typed views of the Secondary Status and Bridge
+/// Control registers are available through
+/// [`PciToCardbusBridgeHeader::secondary_status_register`] and
+/// [`PciToCardbusBridgeHeader::bridge_control_register`].
 ///
 /// The format of the header in PCI configuration space is the following.
 ///
@@ -29,7 +61,7 @@ use super::pci_config_buffer::PciConfigBuffer;
 /// |   0x11   |   0x44  |        16-bit PC Card legacy mode base address               |
 /// +----------+---------+-------------+--------------+----------------+----------------+
 /// ```
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct PciToCardbusBridgeHeader {
     pub cardbus_socket_exca_base_addr: u32,
     pub secondary_status: u16,
@@ -58,8 +90,69 @@ impl PciToCardbusBridgeHeader {
     pub const LENGTH: usize = 72;
     pub const ID: u8 = 2;
 
+    /// Returns a typed view of the Secondary Status register, reporting the
+    /// status of the CardBus.
+    pub fn secondary_status_register(&self) -> PciSecondaryStatus {
+        PciSecondaryStatus(self.secondary_status)
+    }
+
+    /// Sets the Secondary Status register from a typed value.
+    pub fn set_secondary_status_register(&mut self, status: PciSecondaryStatus) {
+        self.secondary_status = status.bits();
+    }
+
+    /// Returns a typed view of the Bridge Control register.
+    pub fn bridge_control_register(&self) -> PciCardbusBridgeControl {
+        PciCardbusBridgeControl(self.bridge_control)
+    }
+
+    /// Sets the Bridge Control register from a typed value.
+    pub fn set_bridge_control_register(&mut self, control: PciCardbusBridgeControl) {
+        self.bridge_control = control.bits();
+    }
+
+    /// Returns the first memory window forwarded by the bridge.
+    pub fn memory_window_0(&self) -> PciBridgeWindow {
+        Self::memory_window(self.memory_base_addr_0, self.memory_limit_0)
+    }
+
+    /// Returns the second memory window forwarded by the bridge.
+    pub fn memory_window_1(&self) -> PciBridgeWindow {
+        Self::memory_window(self.memory_base_addr_1, self.memory_limit_1)
+    }
+
+    /// Returns the first I/O window forwarded by the bridge.
+    pub fn io_window_0(&self) -> PciBridgeWindow {
+        Self::io_window(self.io_base_addr_0, self.io_limit_0)
+    }
+
+    /// Returns the second I/O window forwarded by the bridge.
+    pub fn io_window_1(&self) -> PciBridgeWindow {
+        Self::io_window(self.io_base_addr_1, self.io_limit_1)
+    }
+
+    fn memory_window(base: u32, limit: u32) -> PciBridgeWindow {
+        // Memory windows have a 4 KiB granularity
+        PciBridgeWindow::with_base_and_limit((base & !0xFFF) as u64, (limit | 0xFFF) as u64)
+    }
+
+    fn io_window(base: u32, limit: u32) -> PciBridgeWindow {
+        // I/O windows have a 4 bytes granularity, and the lowest bits of the
+        // base indicate whether the bridge supports 32-bit I/O addressing
+        let mask = if base & 0x3 == 0x1 {
+            0xFFFF_FFFF
+        } else {
+            0xFFFF
+        };
+
+        PciBridgeWindow::with_base_and_limit(
+            (base & mask & !0x3) as u64,
+            (limit & mask | 0x3) as u64,
+        )
+    }
+
     pub(super) fn with_pci_cfg(pci_cfg: &PciConfigBuffer<'_>) -> Result<Self, PciInfoError> {
-        pci_cfg.assert_registers_available(0, 3)?;
+        pci_cfg.assert_registers_available(4, 0x11)?;
 
         Ok(Self {
             cardbus_socket_exca_base_addr: pci_cfg.read_u32(0x4),
@@ -85,4 +178,35 @@ impl PciToCardbusBridgeHeader {
             pc_card_16bit_legacy_mode_base_addr: pci_cfg.read_u32(0x11),
         })
     }
+
+    pub(super) fn write_pci_cfg(
+        &self,
+        pci_cfg: &mut PciConfigBufferMut<'_>,
+    ) -> Result<(), PciInfoError> {
+        pci_cfg.assert_registers_available(4, 0x11)?;
+
+        pci_cfg.write_u32(0x4, self.cardbus_socket_exca_base_addr);
+        pci_cfg.write_u16_hi(0x5, self.secondary_status);
+        pci_cfg.write_u8(0x5, 0, self.offset_of_capabilities_list);
+        pci_cfg.write_u8(0x6, 3, self.cardbus_latency_timer);
+        pci_cfg.write_u8(0x6, 2, self.subordinate_bus_number);
+        pci_cfg.write_u8(0x6, 1, self.cardbus_bus_number);
+        pci_cfg.write_u8(0x6, 0, self.pci_bus_number);
+        pci_cfg.write_u32(0x7, self.memory_base_addr_0);
+        pci_cfg.write_u32(0x8, self.memory_limit_0);
+        pci_cfg.write_u32(0x9, self.memory_base_addr_1);
+        pci_cfg.write_u32(0xA, self.memory_limit_1);
+        pci_cfg.write_u32(0xB, self.io_base_addr_0);
+        pci_cfg.write_u32(0xC, self.io_limit_0);
+        pci_cfg.write_u32(0xD, self.io_base_addr_1);
+        pci_cfg.write_u32(0xE, self.io_limit_1);
+        pci_cfg.write_u16_hi(0xF, self.bridge_control);
+        pci_cfg.write_u8(0xF, 1, self.interrupt_pin);
+        pci_cfg.write_u8(0xF, 0, self.interrupt_line);
+        pci_cfg.write_u16_hi(0x10, self.subsystem_vendor_id);
+        pci_cfg.write_u16_lo(0x10, self.subsystem_device_id);
+        pci_cfg.write_u32(0x11, self.pc_card_16bit_legacy_mode_base_addr);
+
+        Ok(())
+    }
 }
@@ -1,11 +1,86 @@
 use crate::PciInfoError;
 
-use super::pci_config_buffer::PciConfigBuffer;
+use super::pci_config_buffer::{PciConfigBuffer, PciConfigBufferMut};
+use super::{PciBar, PciBridgeWindow, PciDevselTiming};
+
+pci_register_bits! {
+    /// The Secondary Status register of bridge headers, reporting the status
+    /// of the secondary bus.
+    pub struct PciSecondaryStatus(u16) {
+        /// The secondary bus is capable of running at 66 MHz.
+        capable_66mhz, set_capable_66mhz: 5 => "66MHz";
+        /// The secondary bus can accept fast back-to-back transactions.
+        fast_back_to_back_capable, set_fast_back_to_back_capable: 7 => "FastB2B";
+        /// A data parity error was detected by the bridge as a bus master
+        /// on the secondary bus.
+        master_data_parity_error, set_master_data_parity_error: 8 => "ParErr";
+        /// The bridge terminated a transaction on the secondary bus with a
+        /// target abort.
+        signaled_target_abort, set_signaled_target_abort: 11 => ">TAbort";
+        /// A transaction of the bridge on the secondary bus was terminated
+        /// with a target abort.
+        received_target_abort, set_received_target_abort: 12 => "<TAbort";
+        /// A transaction of the bridge on the secondary bus was terminated
+        /// with a master abort.
+        received_master_abort, set_received_master_abort: 13 => "<MAbort";
+        /// SERR# was asserted on the secondary bus.
+        received_system_error, set_received_system_error: 14 => "<SERR";
+        /// The bridge detected a parity error on the secondary bus.
+        detected_parity_error, set_detected_parity_error: 15 => "<PERR";
+    }
+}
+
+impl PciSecondaryStatus {
+    /// The DEVSEL# timing of the bridge on the secondary bus.
+    pub fn devsel_timing(&self) -> PciDevselTiming {
+        PciDevselTiming::from_code(self.field(9, 0x3) as u8)
+    }
+
+    /// Sets the DEVSEL# timing of the bridge on the secondary bus.
+    pub fn set_devsel_timing(&mut self, timing: PciDevselTiming) {
+        self.set_field(9, 0x3, timing.as_code() as u16);
+    }
+}
+
+pci_register_bits! {
+    /// The Bridge Control register of PCI to PCI bridge headers.
+    pub struct PciBridgeControl(u16) {
+        /// The bridge responds to parity errors on the secondary bus.
+        parity_error_response, set_parity_error_response: 0 => "Parity";
+        /// The bridge forwards SERR# assertions from the secondary bus.
+        serr_enable, set_serr_enable: 1 => "SERR";
+        /// The bridge blocks the forwarding of ISA I/O addresses in the
+        /// top 768 bytes of each 1 KiB block of the I/O window.
+        isa_enable, set_isa_enable: 2 => "ISA";
+        /// The bridge forwards VGA memory and I/O addresses.
+        vga_enable, set_vga_enable: 3 => "VGA";
+        /// The bridge decodes VGA I/O addresses with 16 bits instead of 10.
+        vga_16bit_decode, set_vga_16bit_decode: 4 => "VGA16";
+        /// Master aborts are reported as target aborts or SERR#.
+        master_abort_mode, set_master_abort_mode: 5 => "MAbort";
+        /// The reset signal of the secondary bus is asserted.
+        secondary_bus_reset, set_secondary_bus_reset: 6 => ">Reset";
+        /// The bridge can generate fast back-to-back transactions on the
+        /// secondary bus.
+        fast_back_to_back, set_fast_back_to_back: 7 => "FastB2B";
+        /// The primary discard timer counts 2^10 PCI clocks instead of 2^15.
+        primary_discard_timeout, set_primary_discard_timeout: 8 => "PriDiscTmr";
+        /// The secondary discard timer counts 2^10 PCI clocks instead of 2^15.
+        secondary_discard_timeout, set_secondary_discard_timeout: 9 => "SecDiscTmr";
+        /// A discard timer expired.
+        discard_timer_status, set_discard_timer_status: 10 => "DiscTmrStat";
+        /// The bridge asserts SERR# when a discard timer expires.
+        discard_timer_serr_enable, set_discard_timer_serr_enable: 11 => "DiscTmrSERREn";
+    }
+}
 
 /// The header for PCI to PCI bus bridges.
 ///
 /// All fields are the raw values of every non-reserved register part in
-/// the configuration space.
+/// the configuration space. Typed views of the Secondary Status and Bridge
+/// Control registers are available through
+/// [`PciToPciBridgeHeader::secondary_status_register`] and
+/// [`PciToPciBridgeHeader::bridge_control_register`].
 ///
 /// The format of the header in PCI configuration space is the following.
 ///
@@ -27,7 +102,7 @@ use super::pci_config_buffer::PciConfigBuffer;
 /// |   0xF    |   0x3C  |       Bridge Control       | Interrupt PIN  | Interrupt Line |
 /// +----------+---------+-------------+--------------+----------------+----------------+
 /// ```
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct PciToPciBridgeHeader {
     pub base_addr: [u32; 2],
     pub secondary_latency_timer: u8,
@@ -56,8 +131,79 @@ impl PciToPciBridgeHeader {
     pub const LENGTH: usize = 64;
     pub const ID: u8 = 1;
 
+    /// Decodes the Base Address Registers of the header, merging the two
+    /// halves of 64-bit BARs and skipping unused registers.
+    pub fn bars(&self) -> Vec<PciBar> {
+        PciBar::from_registers(&self.base_addr)
+    }
+
+    /// Returns a typed view of the Secondary Status register.
+    pub fn secondary_status_register(&self) -> PciSecondaryStatus {
+        PciSecondaryStatus(self.secondary_status)
+    }
+
+    /// Sets the Secondary Status register from a typed value.
+    pub fn set_secondary_status_register(&mut self, status: PciSecondaryStatus) {
+        self.secondary_status = status.bits();
+    }
+
+    /// Returns a typed view of the Bridge Control register.
+    pub fn bridge_control_register(&self) -> PciBridgeControl {
+        PciBridgeControl(self.bridge_control)
+    }
+
+    /// Sets the Bridge Control register from a typed value.
+    pub fn set_bridge_control_register(&mut self, control: PciBridgeControl) {
+        self.bridge_control = control.bits();
+    }
+
+    /// Returns the I/O window forwarded by the bridge, using the upper 16
+    /// bits of the addresses if the bridge supports 32-bit I/O addressing.
+    pub fn io_window(&self) -> PciBridgeWindow {
+        let mut base = ((self.io_base & 0xF0) as u64) << 8;
+        let mut limit = ((self.io_limit & 0xF0) as u64) << 8 | 0xFFF;
+
+        if self.io_base & 0x0F == 0x01 {
+            base |= (self.io_base_upper_16_bits as u64) << 16;
+            limit |= (self.io_limit_upper_16_bits as u64) << 16;
+        }
+
+        PciBridgeWindow::with_base_and_limit(base, limit)
+    }
+
+    /// Returns the non-prefetchable memory window forwarded by the bridge.
+    pub fn memory_window(&self) -> PciBridgeWindow {
+        let base = ((self.memory_base & 0xFFF0) as u64) << 16;
+        let limit = ((self.memory_limit & 0xFFF0) as u64) << 16 | 0xF_FFFF;
+
+        PciBridgeWindow::with_base_and_limit(base, limit)
+    }
+
+    /// Returns the prefetchable memory window forwarded by the bridge, using
+    /// the upper 32 bits of the addresses if the bridge supports 64-bit
+    /// prefetchable addressing.
+    pub fn prefetchable_memory_window(&self) -> PciBridgeWindow {
+        let mut base = ((self.prefetchable_memory_base & 0xFFF0) as u64) << 16;
+        let mut limit = ((self.prefetchable_memory_limit & 0xFFF0) as u64) << 16 | 0xF_FFFF;
+
+        if self.prefetchable_memory_base & 0x0F == 0x01 {
+            base |= (self.prefetchable_base_upper_32_bits as u64) << 32;
+            limit |= (self.prefetchable_limit_upper_32_bits as u64) << 32;
+        }
+
+        PciBridgeWindow::with_base_and_limit(base, limit)
+    }
+
+    /// Returns true if a memory access to `address` is forwarded by the
+    /// bridge, through either the non-prefetchable or the prefetchable
+    /// memory window.
+    pub fn forwards_memory_address(&self, address: u64) -> bool {
+        self.memory_window().contains(address)
+            || self.prefetchable_memory_window().contains(address)
+    }
+
     pub(super) fn with_pci_cfg(pci_cfg: &PciConfigBuffer<'_>) -> Result<Self, PciInfoError> {
-        pci_cfg.assert_registers_available(0, 3)?;
+        pci_cfg.assert_registers_available(4, 0xF)?;
 
         Ok(Self {
             base_addr: [pci_cfg.read_u32(0x4), pci_cfg.read_u32(0x5)],
@@ -76,11 +222,100 @@ impl PciToPciBridgeHeader {
             prefetchable_limit_upper_32_bits: pci_cfg.read_u32(0xB),
             io_limit_upper_16_bits: pci_cfg.read_u16_hi(0xC),
             io_base_upper_16_bits: pci_cfg.read_u16_lo(0xC),
-            capability_pointer: pci_cfg.read_u16_lo(0xD),
+            capability_pointer: pci_cfg.read_u8(0xD, 0) as u16,
             expansion_rom_base_addr: pci_cfg.read_u32(0xE),
             bridge_control: pci_cfg.read_u16_hi(0xF),
             interrupt_pin: pci_cfg.read_u8(0xF, 1),
             interrupt_line: pci_cfg.read_u8(0xF, 0),
         })
     }
+
+    pub(super) fn write_pci_cfg(
+        &self,
+        pci_cfg: &mut PciConfigBufferMut<'_>,
+    ) -> Result<(), PciInfoError> {
+        pci_cfg.assert_registers_available(4, 0xF)?;
+
+        pci_cfg.write_u32(0x4, self.base_addr[0]);
+        pci_cfg.write_u32(0x5, self.base_addr[1]);
+        pci_cfg.write_u8(0x6, 3, self.secondary_latency_timer);
+        pci_cfg.write_u8(0x6, 2, self.subordinate_bus_number);
+        pci_cfg.write_u8(0x6, 1, self.secondary_bus_number);
+        pci_cfg.write_u8(0x6, 0, self.primary_bus_number);
+        pci_cfg.write_u16_hi(0x7, self.secondary_status);
+        pci_cfg.write_u8(0x7, 1, self.io_limit);
+        pci_cfg.write_u8(0x7, 0, self.io_base);
+        pci_cfg.write_u16_hi(0x8, self.memory_limit);
+        pci_cfg.write_u16_lo(0x8, self.memory_base);
+        pci_cfg.write_u16_hi(0x9, self.prefetchable_memory_limit);
+        pci_cfg.write_u16_lo(0x9, self.prefetchable_memory_base);
+        pci_cfg.write_u32(0xA, self.prefetchable_base_upper_32_bits);
+        pci_cfg.write_u32(0xB, self.prefetchable_limit_upper_32_bits);
+        pci_cfg.write_u16_hi(0xC, self.io_limit_upper_16_bits);
+        pci_cfg.write_u16_lo(0xC, self.io_base_upper_16_bits);
+        pci_cfg.write_u8(0xD, 0, self.capability_pointer as u8);
+        pci_cfg.write_u32(0xE, self.expansion_rom_base_addr);
+        pci_cfg.write_u16_hi(0xF, self.bridge_control);
+        pci_cfg.write_u8(0xF, 1, self.interrupt_pin);
+        pci_cfg.write_u8(0xF, 0, self.interrupt_line);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_headers::{PciSpecializedHeader, PciToCardbusBridgeHeader};
+
+    #[test]
+    fn typed_bridge_control_and_secondary_status() {
+        let mut config = [0u8; PciToCardbusBridgeHeader::LENGTH];
+        config[0x0E] = 0x01;
+        config[0x1E..0x20].copy_from_slice(&0x2200u16.to_le_bytes());
+        config[0x3E..0x40].copy_from_slice(&0x004Au16.to_le_bytes());
+
+        let Ok(PciSpecializedHeader::PciToPciBridge(mut header)) =
+            PciSpecializedHeader::read_subheader(1, &config, true)
+        else {
+            panic!("unexpected header type");
+        };
+
+        let control = header.bridge_control_register();
+        let status = header.secondary_status_register();
+
+        assert!(control.serr_enable() && control.vga_enable() && control.secondary_bus_reset());
+        assert!(!control.isa_enable() && !control.vga_16bit_decode());
+        assert!(status.received_master_abort());
+        assert_eq!(status.devsel_timing(), PciDevselTiming::Medium);
+        assert_eq!(
+            format!("{control:?}"),
+            "PciBridgeControl(0x4A: SERR | VGA | >Reset)"
+        );
+
+        let mut control = PciBridgeControl::default();
+        control.set_secondary_bus_reset(true);
+        header.set_bridge_control_register(control);
+        assert_eq!(header.bridge_control, 0x0040);
+
+        // CardBus bridges have the bridge control register at the same
+        // offset, with PC Card specific bits, and the secondary status
+        // register at a different offset
+        config[0x0E] = 0x02;
+        config[0x16..0x18].copy_from_slice(&0x2000u16.to_le_bytes());
+        config[0x3E..0x40].copy_from_slice(&0x05C0u16.to_le_bytes());
+
+        let Ok(PciSpecializedHeader::PciToCardbusBridge(header)) =
+            PciSpecializedHeader::read_subheader(2, &config, true)
+        else {
+            panic!("unexpected header type");
+        };
+
+        let control = header.bridge_control_register();
+
+        assert!(control.cardbus_reset() && control.pc_card_16bit_interrupt_enable());
+        assert!(control.memory_0_prefetch_enable() && !control.memory_1_prefetch_enable());
+        assert!(control.write_posting_enable());
+        assert!(header.secondary_status_register().received_master_abort());
+    }
 }
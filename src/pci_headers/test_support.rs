@@ -0,0 +1,56 @@
+//! Helpers shared by the tests of the capability decoders.
+
+use crate::PciInfoError;
+
+use super::{
+    PciCapability, PciCapabilityId, PciCapabilityIterator, PciExtendedCapability,
+    PciExtendedCapabilityId, PciExtendedCapabilityIterator,
+};
+
+/// Builds a standard configuration space containing only a capability with
+/// the specified id, located at offset `0x40`, and decodes it with
+/// `decode`. `body` contains the bytes that follow the capability id and
+/// next pointer. Panics if the capability cannot be found or decoded.
+pub(crate) fn decode_capability<T>(
+    id: PciCapabilityId,
+    body: &[u8],
+    decode: impl FnOnce(&PciCapability<'_>) -> Result<T, PciInfoError>,
+) -> T {
+    let mut config = [0u8; 256];
+    // Capabilities list bit of the Status register, capabilities pointer
+    config[0x06] = 0x10;
+    config[0x34] = 0x40;
+    config[0x40] = id.as_code();
+    config[0x42..0x42 + body.len()].copy_from_slice(body);
+
+    let cap = PciCapabilityIterator::with_bytes(&config)
+        .unwrap()
+        .find_capability(id)
+        .unwrap()
+        .unwrap();
+
+    decode(&cap).unwrap()
+}
+
+/// Builds an extended configuration space containing only an extended
+/// capability with the specified id (version 1), located at offset `0x100`,
+/// and decodes it with `decode`. `body` contains the bytes that follow the
+/// extended capability header. Panics if the capability cannot be found or
+/// decoded.
+pub(crate) fn decode_extended_capability<T>(
+    id: PciExtendedCapabilityId,
+    body: &[u8],
+    decode: impl FnOnce(&PciExtendedCapability<'_>) -> Result<T, PciInfoError>,
+) -> T {
+    let mut config = vec![0u8; PciExtendedCapabilityIterator::EXTENDED_CONFIG_SPACE_LEN];
+    let header = id.as_code() as u32 | 1 << 16;
+    config[0x100..0x104].copy_from_slice(&header.to_le_bytes());
+    config[0x104..0x104 + body.len()].copy_from_slice(body);
+
+    let cap = PciExtendedCapabilityIterator::with_bytes(&config)
+        .find_capability(id)
+        .unwrap()
+        .unwrap();
+
+    decode(&cap).unwrap()
+}
@@ -0,0 +1,50 @@
+//! This module parses the contents of PCI expansion ROMs (option ROMs), such
+//! as the ones mapped by the Expansion ROM Base Address register of a device
+//! (see [`PciGenericDeviceHeader::expansion_rom_base_addr`]).
+//!
+//! An expansion ROM contains one or more images, each starting with the
+//! `55 AA` signature and described by a PCI Data Structure ([`PciDataStructure`]).
+//! The images of a ROM can be walked with a [`PciRomImageIterator`]; images
+//! containing UEFI drivers also carry a [`PciEfiImageHeader`].
+//!
+//! On Linux the contents of the ROM of a device can be read from the `rom`
+//! file in its sysfs directory, after enabling it by writing `1` to the file
+//! (which requires elevated privileges).
+//!
+//! # Example
+//! ```rust,no_run
+//! use pci_info::pci_rom::{PciRomCodeType, PciRomImageIterator};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let rom = std::fs::read("/sys/bus/pci/devices/0000:01:00.0/rom")?;
+//!
+//! for image in PciRomImageIterator::with_bytes(&rom) {
+//!     let image = image?;
+//!     let pcir = image.pci_data_structure();
+//!
+//!     println!(
+//!         "{:04x}:{:04x} {:?} image at 0x{:X}, {} bytes",
+//!         pcir.vendor_id,
+//!         pcir.device_id,
+//!         pcir.code_type,
+//!         image.offset(),
+//!         image.len()
+//!     );
+//!
+//!     if let Some(efi) = image.efi_image_header() {
+//!         println!("  {:?} for {:?}", efi.subsystem, efi.machine_type);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`PciGenericDeviceHeader::expansion_rom_base_addr`]: crate::pci_headers::PciGenericDeviceHeader::expansion_rom_base_addr
+
+mod pci_efi_image_header;
+mod pci_rom_image;
+
+pub use pci_efi_image_header::{
+    PciEfiCompressionType, PciEfiImageHeader, PciEfiMachineType, PciEfiSubsystem,
+};
+pub use pci_rom_image::{PciDataStructure, PciRomCodeType, PciRomImage, PciRomImageIterator};
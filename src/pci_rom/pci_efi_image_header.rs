@@ -0,0 +1,186 @@
+use crate::pci_headers::PciConfigAccess;
+use crate::PciInfoError;
+
+/// The subsystem of an EFI image stored in an expansion ROM.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PciEfiSubsystem {
+    /// An EFI application.
+    Application,
+    /// An EFI boot service driver, the usual type of UEFI option ROM drivers.
+    BootServiceDriver,
+    /// An EFI runtime driver.
+    RuntimeDriver,
+    /// A subsystem not known by this crate.
+    Unknown(u16),
+}
+
+impl PciEfiSubsystem {
+    /// Create a `PciEfiSubsystem` from the value of the EFI Subsystem field.
+    pub fn from_code(code: u16) -> Self {
+        match code {
+            10 => Self::Application,
+            11 => Self::BootServiceDriver,
+            12 => Self::RuntimeDriver,
+            unk => Self::Unknown(unk),
+        }
+    }
+
+    /// Gets the encoded value that this `PciEfiSubsystem` represents
+    pub fn as_code(&self) -> u16 {
+        match self {
+            Self::Application => 10,
+            Self::BootServiceDriver => 11,
+            Self::RuntimeDriver => 12,
+            Self::Unknown(unk) => *unk,
+        }
+    }
+}
+
+/// The machine type (architecture) of an EFI image stored in an expansion
+/// ROM, as defined by the PE/COFF specification.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PciEfiMachineType {
+    /// 32-bit x86 (IA-32).
+    Ia32,
+    /// Itanium.
+    Ia64,
+    /// EFI Byte Code, architecture independent.
+    Ebc,
+    /// 64-bit x86 (x64).
+    X64,
+    /// 32-bit ARM (Thumb-2).
+    Arm,
+    /// 64-bit ARM (AArch64).
+    Aarch64,
+    /// 64-bit RISC-V.
+    RiscV64,
+    /// 64-bit LoongArch.
+    LoongArch64,
+    /// A machine type not known by this crate.
+    Unknown(u16),
+}
+
+impl PciEfiMachineType {
+    /// Create a `PciEfiMachineType` from the value of the EFI Machine Type
+    /// field.
+    pub fn from_code(code: u16) -> Self {
+        match code {
+            0x014C => Self::Ia32,
+            0x0200 => Self::Ia64,
+            0x0EBC => Self::Ebc,
+            0x8664 => Self::X64,
+            0x01C2 => Self::Arm,
+            0xAA64 => Self::Aarch64,
+            0x5064 => Self::RiscV64,
+            0x6264 => Self::LoongArch64,
+            unk => Self::Unknown(unk),
+        }
+    }
+
+    /// Gets the encoded value that this `PciEfiMachineType` represents
+    pub fn as_code(&self) -> u16 {
+        match self {
+            Self::Ia32 => 0x014C,
+            Self::Ia64 => 0x0200,
+            Self::Ebc => 0x0EBC,
+            Self::X64 => 0x8664,
+            Self::Arm => 0x01C2,
+            Self::Aarch64 => 0xAA64,
+            Self::RiscV64 => 0x5064,
+            Self::LoongArch64 => 0x6264,
+            Self::Unknown(unk) => *unk,
+        }
+    }
+}
+
+/// The compression of an EFI image stored in an expansion ROM.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PciEfiCompressionType {
+    /// The image is not compressed.
+    Uncompressed,
+    /// The image is compressed with the EFI compression algorithm.
+    EfiCompressed,
+    /// A compression type not known by this crate.
+    Unknown(u16),
+}
+
+impl PciEfiCompressionType {
+    /// Create a `PciEfiCompressionType` from the value of the Compression
+    /// Type field.
+    pub fn from_code(code: u16) -> Self {
+        match code {
+            0 => Self::Uncompressed,
+            1 => Self::EfiCompressed,
+            unk => Self::Unknown(unk),
+        }
+    }
+
+    /// Gets the encoded value that this `PciEfiCompressionType` represents
+    pub fn as_code(&self) -> u16 {
+        match self {
+            Self::Uncompressed => 0,
+            Self::EfiCompressed => 1,
+            Self::Unknown(unk) => *unk,
+        }
+    }
+}
+
+/// The header of an expansion ROM image containing an EFI driver, which
+/// replaces the legacy option ROM header.
+///
+/// The format of the header is the following.
+///
+///  ```text
+/// +---------+-------------+--------------+----------------+----------------+
+/// | Offset  | Bits 31-24  | Bits 23-16   | Bits 15-8      | Bits 7-0       |
+/// +---------+-------------+--------------+----------------+----------------+
+/// |   0x00  |   Initialization Size      |      0xAA      |      0x55      |
+/// |   0x04  |              EFI Signature (0x00000EF1)                      |
+/// |   0x08  |      EFI Machine Type      |         EFI Subsystem           |
+/// |   0x0C  |         Reserved           |        Compression Type         |
+/// |   0x10  |                        Reserved                              |
+/// |   0x14  |    EFI Image Offset        |         Reserved                |
+/// |   0x18  |         Reserved           |  PCI Data Structure Offset      |
+/// +---------+-------------+--------------+----------------+----------------+
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PciEfiImageHeader {
+    /// The size of the image loaded in memory, in bytes.
+    pub initialization_size: usize,
+    pub subsystem: PciEfiSubsystem,
+    pub machine_type: PciEfiMachineType,
+    pub compression_type: PciEfiCompressionType,
+    /// The offset of the EFI (PE/COFF) image from the start of the ROM
+    /// image.
+    pub efi_image_offset: u16,
+}
+
+impl PciEfiImageHeader {
+    /// The value of the EFI Signature field.
+    pub const SIGNATURE: u32 = 0x0EF1;
+
+    /// Decodes the EFI image header at the beginning of `bytes`, which
+    /// must start at the beginning of a ROM image.
+    pub fn with_bytes(bytes: &[u8]) -> Result<Self, PciInfoError> {
+        let signature = bytes.read_u32(0x04)?;
+
+        if signature != Self::SIGNATURE {
+            return Err(PciInfoError::ParseError(
+                format!("invalid EFI image signature 0x{signature:08X}").into(),
+            ));
+        }
+
+        Ok(Self {
+            initialization_size: bytes.read_u16(0x02)? as usize * 512,
+            subsystem: PciEfiSubsystem::from_code(bytes.read_u16(0x08)?),
+            machine_type: PciEfiMachineType::from_code(bytes.read_u16(0x0A)?),
+            compression_type: PciEfiCompressionType::from_code(bytes.read_u16(0x0C)?),
+            efi_image_offset: bytes.read_u16(0x16)?,
+        })
+    }
+
+    /// Returns true if the image is compressed.
+    pub fn is_compressed(&self) -> bool {
+        self.compression_type != PciEfiCompressionType::Uncompressed
+    }
+}
@@ -0,0 +1,367 @@
+use crate::pci_headers::PciConfigAccess;
+use crate::PciInfoError;
+
+use super::PciEfiImageHeader;
+
+/// The type of code contained in an expansion ROM image.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PciRomCodeType {
+    /// Legacy x86 (PC-AT compatible) BIOS code.
+    X86,
+    /// Open Firmware (IEEE 1275) code.
+    OpenFirmware,
+    /// HP PA-RISC code.
+    HpPaRisc,
+    /// A UEFI driver.
+    Efi,
+    /// A code type not known by this crate.
+    Unknown(u8),
+}
+
+impl PciRomCodeType {
+    /// Create a `PciRomCodeType` from the value of the Code Type field.
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            0 => Self::X86,
+            1 => Self::OpenFirmware,
+            2 => Self::HpPaRisc,
+            3 => Self::Efi,
+            unk => Self::Unknown(unk),
+        }
+    }
+
+    /// Gets the encoded value that this `PciRomCodeType` represents
+    pub fn as_code(&self) -> u8 {
+        match self {
+            Self::X86 => 0,
+            Self::OpenFirmware => 1,
+            Self::HpPaRisc => 2,
+            Self::Efi => 3,
+            Self::Unknown(unk) => *unk,
+        }
+    }
+}
+
+/// The PCI Data Structure of an expansion ROM image, identifying the device
+/// the image is meant for and the type of code it contains.
+///
+/// The format of the structure is the following.
+///
+///  ```text
+/// +---------+-------------+--------------+----------------+----------------+
+/// | Offset  | Bits 31-24  | Bits 23-16   | Bits 15-8      | Bits 7-0       |
+/// +---------+-------------+--------------+----------------+----------------+
+/// |   0x00  |                 Signature ("PCIR")                           |
+/// |   0x04  |          Device ID         |           Vendor ID             |
+/// |   0x08  |    Structure Length        |      Device List Pointer        |
+/// |   0x0C  |                 Class Code                 |   Revision     |
+/// |   0x10  |     Code Revision Level    |         Image Length            |
+/// |   0x14  |   Max Runtime Image Length | Indicator    |   Code Type      |
+/// |   0x18  |      DMTF CLP Entry Point  |  Config Utility Code Header     |
+/// +---------+-------------+--------------+----------------+----------------+
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PciDataStructure {
+    pub vendor_id: u16,
+    pub device_id: u16,
+    /// Additional device IDs supported by the image (PCI Firmware 3.0 and
+    /// later), not including `device_id`.
+    pub device_list: Vec<u16>,
+    pub structure_revision: u8,
+    pub class_code: u8,
+    pub subclass_code: u8,
+    pub prog_iface_code: u8,
+    /// The length of the image, in bytes.
+    pub image_length: usize,
+    pub code_revision: u16,
+    pub code_type: PciRomCodeType,
+    /// True if this is the last image of the ROM.
+    pub last_image: bool,
+}
+
+impl PciDataStructure {
+    /// The signature of the PCI Data Structure.
+    pub const SIGNATURE: &'static [u8; 4] = b"PCIR";
+
+    /// Decodes the PCI Data Structure at `offset` in `image`, which must
+    /// start at the beginning of a ROM image.
+    pub fn with_bytes(image: &[u8], offset: usize) -> Result<Self, PciInfoError> {
+        let mut signature = [0u8; 4];
+        image.read_bytes(offset, &mut signature)?;
+
+        if &signature != Self::SIGNATURE {
+            return Err(PciInfoError::ParseError(
+                format!("invalid PCI data structure signature at 0x{offset:X}").into(),
+            ));
+        }
+
+        let structure_revision = image.read_u8(offset + 0x0C)?;
+        let device_list_pointer = image.read_u16(offset + 0x08)? as usize;
+
+        let device_list = if structure_revision >= 3 && device_list_pointer != 0 {
+            let mut list = Vec::new();
+            let mut entry = offset + device_list_pointer;
+
+            loop {
+                match image.read_u16(entry)? {
+                    0 => break list,
+                    id => list.push(id),
+                }
+                entry += 2;
+            }
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            vendor_id: image.read_u16(offset + 0x04)?,
+            device_id: image.read_u16(offset + 0x06)?,
+            device_list,
+            structure_revision,
+            prog_iface_code: image.read_u8(offset + 0x0D)?,
+            subclass_code: image.read_u8(offset + 0x0E)?,
+            class_code: image.read_u8(offset + 0x0F)?,
+            image_length: image.read_u16(offset + 0x10)? as usize * 512,
+            code_revision: image.read_u16(offset + 0x12)?,
+            code_type: PciRomCodeType::from_code(image.read_u8(offset + 0x14)?),
+            last_image: image.read_u8(offset + 0x15)? & 0x80 != 0,
+        })
+    }
+
+    /// Returns true if the image supports the device with the specified
+    /// IDs, either through `device_id` or through the device list.
+    pub fn supports_device(&self, vendor_id: u16, device_id: u16) -> bool {
+        self.vendor_id == vendor_id
+            && (self.device_id == device_id || self.device_list.contains(&device_id))
+    }
+}
+
+/// An image found in an expansion ROM.
+#[derive(Clone, Debug)]
+pub struct PciRomImage<'a> {
+    offset: usize,
+    bytes: &'a [u8],
+    pci_data: PciDataStructure,
+    efi_header: Option<PciEfiImageHeader>,
+}
+
+impl<'a> PciRomImage<'a> {
+    /// The signature at the start of every ROM image.
+    pub const SIGNATURE: u16 = 0xAA55;
+
+    /// Decodes the ROM image at the beginning of `bytes`. `offset` is only
+    /// used to report the position of the image in the ROM.
+    pub fn with_bytes(bytes: &'a [u8], offset: usize) -> Result<Self, PciInfoError> {
+        let signature = bytes.read_u16(0x00)?;
+
+        if signature != Self::SIGNATURE {
+            return Err(PciInfoError::ParseError(
+                format!("invalid ROM image signature 0x{signature:04X} at 0x{offset:X}").into(),
+            ));
+        }
+
+        let pci_data = PciDataStructure::with_bytes(bytes, bytes.read_u16(0x18)? as usize)?;
+
+        let efi_header = match pci_data.code_type {
+            PciRomCodeType::Efi => Some(PciEfiImageHeader::with_bytes(bytes)?),
+            _ => None,
+        };
+
+        let end = match pci_data.image_length {
+            0 => bytes.len(),
+            len => len.min(bytes.len()),
+        };
+
+        Ok(Self {
+            offset,
+            bytes: &bytes[..end],
+            pci_data,
+            efi_header,
+        })
+    }
+
+    /// Returns the offset of this image in the ROM.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the length of this image, as declared by its PCI Data
+    /// Structure.
+    pub fn len(&self) -> usize {
+        self.pci_data.image_length
+    }
+
+    /// Returns true if the image declares a length of zero.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the raw bytes of this image. This may be shorter than
+    /// [`PciRomImage::len`] if the ROM is truncated.
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns the PCI Data Structure of this image.
+    pub fn pci_data_structure(&self) -> &PciDataStructure {
+        &self.pci_data
+    }
+
+    /// Returns the EFI image header of this image, if it contains an EFI
+    /// driver.
+    pub fn efi_image_header(&self) -> Option<&PciEfiImageHeader> {
+        self.efi_header.as_ref()
+    }
+
+    /// The size of the image once loaded in memory, in bytes, as declared
+    /// by the option ROM header.
+    pub fn initialization_size(&self) -> usize {
+        match &self.efi_header {
+            Some(efi) => efi.initialization_size,
+            None => self.bytes.get(0x02).map_or(0, |size| *size as usize * 512),
+        }
+    }
+}
+
+/// An iterator over the images of an expansion ROM.
+///
+/// The iterator stops after the image flagged as the last one, and yields
+/// an error, and then stops, when an image is malformed or truncated.
+#[derive(Clone)]
+pub struct PciRomImageIterator<'a> {
+    bytes: &'a [u8],
+    next: Option<usize>,
+}
+
+impl<'a> PciRomImageIterator<'a> {
+    /// Creates an iterator over the images of the ROM contained in `bytes`.
+    pub fn with_bytes(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            next: Some(0),
+        }
+    }
+}
+
+impl<'a> Iterator for PciRomImageIterator<'a> {
+    type Item = Result<PciRomImage<'a>, PciInfoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.next.take()?;
+
+        let image = match self.bytes.get(offset..) {
+            Some(bytes) => PciRomImage::with_bytes(bytes, offset),
+            None => Err(PciInfoError::UnexpectedEof),
+        };
+
+        if let Ok(image) = &image {
+            if !image.pci_data.last_image && !image.is_empty() {
+                self.next = Some(offset + image.len());
+            }
+        }
+
+        Some(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci_rom::{PciEfiCompressionType, PciEfiMachineType, PciEfiSubsystem};
+
+    fn image(code_type: u8, last: bool, device_list: &[u16]) -> Vec<u8> {
+        let mut image = vec![0u8; 1024];
+        image[0x00..0x02].copy_from_slice(&[0x55, 0xAA]);
+        image[0x02] = 2;
+        image[0x18..0x1A].copy_from_slice(&0x40u16.to_le_bytes());
+
+        if code_type == 3 {
+            image[0x02..0x04].copy_from_slice(&4u16.to_le_bytes());
+            image[0x04..0x08].copy_from_slice(&0x0EF1u32.to_le_bytes());
+            image[0x08..0x0A].copy_from_slice(&11u16.to_le_bytes());
+            image[0x0A..0x0C].copy_from_slice(&0x8664u16.to_le_bytes());
+            image[0x0C..0x0E].copy_from_slice(&1u16.to_le_bytes());
+            image[0x16..0x18].copy_from_slice(&0x60u16.to_le_bytes());
+        }
+
+        let pcir = &mut image[0x40..0x60];
+        pcir[0x00..0x04].copy_from_slice(b"PCIR");
+        pcir[0x04..0x06].copy_from_slice(&0x10DEu16.to_le_bytes());
+        pcir[0x06..0x08].copy_from_slice(&0x2204u16.to_le_bytes());
+        pcir[0x0A..0x0C].copy_from_slice(&0x1Cu16.to_le_bytes());
+        pcir[0x0C] = 3;
+        pcir[0x0D..0x10].copy_from_slice(&[0x00, 0x00, 0x03]);
+        pcir[0x10..0x12].copy_from_slice(&2u16.to_le_bytes());
+        pcir[0x12..0x14].copy_from_slice(&0x0100u16.to_le_bytes());
+        pcir[0x14] = code_type;
+        pcir[0x15] = if last { 0x80 } else { 0 };
+
+        if !device_list.is_empty() {
+            image[0x48..0x4A].copy_from_slice(&0x20u16.to_le_bytes());
+
+            for (i, id) in device_list.iter().enumerate() {
+                image[0x60 + i * 2..0x62 + i * 2].copy_from_slice(&id.to_le_bytes());
+            }
+        }
+
+        image
+    }
+
+    #[test]
+    fn walks_legacy_and_efi_images() {
+        let mut rom = image(0, false, &[0x2206, 0x2208]);
+        rom.extend(image(3, true, &[]));
+        // Padding after the last image is ignored
+        rom.extend([0xFF; 512]);
+
+        let images = PciRomImageIterator::with_bytes(&rom)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(images.len(), 2);
+
+        let legacy = &images[0];
+        assert_eq!(legacy.offset(), 0);
+        assert_eq!(legacy.len(), 1024);
+        assert_eq!(legacy.initialization_size(), 1024);
+        assert_eq!(legacy.pci_data_structure().code_type, PciRomCodeType::X86);
+        assert_eq!(legacy.pci_data_structure().class_code, 0x03);
+        assert_eq!(
+            legacy.pci_data_structure().device_list,
+            vec![0x2206, 0x2208]
+        );
+        assert!(legacy.pci_data_structure().supports_device(0x10DE, 0x2208));
+        assert!(legacy.efi_image_header().is_none());
+
+        let efi = &images[1];
+        assert_eq!(efi.offset(), 1024);
+        assert!(efi.pci_data_structure().last_image);
+
+        let header = efi.efi_image_header().unwrap();
+        assert_eq!(header.subsystem, PciEfiSubsystem::BootServiceDriver);
+        assert_eq!(header.machine_type, PciEfiMachineType::X64);
+        assert_eq!(
+            header.compression_type,
+            PciEfiCompressionType::EfiCompressed
+        );
+        assert_eq!(header.efi_image_offset, 0x60);
+        assert_eq!(efi.initialization_size(), 2048);
+    }
+
+    #[test]
+    fn reports_malformed_images() {
+        let mut rom = image(0, false, &[]);
+        rom.extend([0xFF; 1024]);
+
+        let res = PciRomImageIterator::with_bytes(&rom).collect::<Vec<_>>();
+
+        assert_eq!(res.len(), 2);
+        assert!(res[0].is_ok());
+        assert!(matches!(res[1], Err(PciInfoError::ParseError(_))));
+
+        let res = PciRomImageIterator::with_bytes(&rom[0..1024]).collect::<Vec<_>>();
+
+        assert_eq!(res.len(), 2);
+        assert!(matches!(res[1], Err(PciInfoError::UnexpectedEof)));
+    }
+}
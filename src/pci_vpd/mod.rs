@@ -0,0 +1,38 @@
+//! This module parses the Vital Product Data (VPD) of PCI devices, which
+//! describes the device with information such as its part and serial
+//! numbers. See [`PciVitalProductData`].
+//!
+//! VPD is stored in a storage component of the device and is accessed
+//! through the VPD capability ([`PciCapabilityId::VitalProductData`]),
+//! which requires writing to the configuration space; this crate does not
+//! write to the configuration space, so VPD has to be read by other means.
+//! On Linux the VPD of a device can be read from the `vpd` file in its sysfs
+//! directory (which requires elevated privileges); enumerators built with
+//! `LinuxProcFsPciEnumerator::with_vital_product_data` also return it in
+//! [`PciDevice::vital_product_data`].
+//!
+//! # Example
+//! ```rust,no_run
+//! use pci_info::pci_vpd::PciVitalProductData;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let bytes = std::fs::read("/sys/bus/pci/devices/0000:01:00.0/vpd")?;
+//! let vpd = PciVitalProductData::with_bytes(&bytes)?;
+//!
+//! println!("{:?}", vpd.identifier);
+//! println!("part number: {:?}", vpd.part_number());
+//! println!("serial number: {:?}", vpd.serial_number());
+//!
+//! for keyword in vpd.vendor_specific() {
+//!     println!("{}: {}", keyword.name(), keyword.data_as_string());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`PciCapabilityId::VitalProductData`]: crate::pci_headers::PciCapabilityId::VitalProductData
+//! [`PciDevice::vital_product_data`]: crate::PciDevice::vital_product_data
+
+mod pci_vpd_data;
+
+pub use pci_vpd_data::{PciVitalProductData, PciVpdKeyword};
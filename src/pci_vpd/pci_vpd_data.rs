@@ -0,0 +1,291 @@
+use crate::PciInfoError;
+
+/// A keyword of the VPD-R (read-only) or VPD-W (read/write) resources of
+/// the Vital Product Data of a device.
+///
+/// Each keyword is stored as a two characters ASCII name, followed by a
+/// length byte and by the data of the keyword.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PciVpdKeyword {
+    /// The two characters name of the keyword (e.g. `PN`).
+    pub keyword: [u8; 2],
+    /// The raw data of the keyword.
+    pub data: Vec<u8>,
+}
+
+impl PciVpdKeyword {
+    /// Returns the name of the keyword as a string (e.g. `"PN"`).
+    pub fn name(&self) -> String {
+        String::from_utf8_lossy(&self.keyword).into_owned()
+    }
+
+    /// Returns the data of the keyword as a string, with trailing NUL
+    /// characters and whitespace removed. Most keywords defined by the
+    /// PCI specification contain ASCII strings.
+    pub fn data_as_string(&self) -> String {
+        vpd_string(&self.data)
+    }
+
+    /// Returns true if this is a vendor specific keyword (`V0`-`VZ`).
+    pub fn is_vendor_specific(&self) -> bool {
+        self.keyword[0] == b'V'
+            && (self.keyword[1].is_ascii_digit() || self.keyword[1].is_ascii_uppercase())
+    }
+}
+
+/// The Vital Product Data (VPD) of a PCI device, as a set of resources
+/// describing the device (see PCI Local Bus Specification 3.0, section 6.4,
+/// and PCI Express Base Specification, section 6.28).
+///
+/// VPD is stored as a sequence of resources in the same format of ISA Plug
+/// and Play resource data; each resource begins with a tag that can be
+/// either small or large.
+///
+///  ```text
+///  Small resource tag:       Large resource tag:
+///  +-----+-------+------+    +-----+-------+-------------------+
+///  |  7  |  6-3  | 2-0  |    |  7  |  6-0  | Byte 1 | Byte 2   |
+///  +-----+-------+------+    +-----+-------+-------------------+
+///  |  0  | Name  | Len  |    |  1  | Name  |   Length (LE)     |
+///  +-----+-------+------+    +-----+-------+-------------------+
+/// ```
+///
+/// The resources that are decoded are the identifier string (large tag
+/// `0x82`), the read-only keywords (VPD-R, large tag `0x90`) and the
+/// read/write keywords (VPD-W, large tag `0x91`); parsing stops at the end
+/// tag (small tag `0x78`) or at the end of the data. Other resources are
+/// skipped.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct PciVitalProductData {
+    /// The identifier string of the device, usually the product name.
+    pub identifier: Option<String>,
+    /// The keywords of the read-only resource (VPD-R).
+    pub read_only: Vec<PciVpdKeyword>,
+    /// The keywords of the read/write resource (VPD-W).
+    pub read_write: Vec<PciVpdKeyword>,
+    /// The result of the verification of the checksum stored in the `RV`
+    /// keyword, or `None` if the data has no `RV` keyword.
+    pub checksum_valid: Option<bool>,
+}
+
+impl PciVitalProductData {
+    const TAG_IDENTIFIER_STRING: u8 = 0x02;
+    const TAG_VPD_R: u8 = 0x10;
+    const TAG_VPD_W: u8 = 0x11;
+    const TAG_END: u8 = 0x0F;
+
+    /// Parses the Vital Product Data contained in `bytes`, starting from
+    /// its first resource tag.
+    pub fn with_bytes(bytes: &[u8]) -> Result<Self, PciInfoError> {
+        let mut vpd = Self::default();
+        let mut offset = 0;
+
+        while offset < bytes.len() {
+            let tag = bytes[offset];
+
+            let (name, start, len) = if tag & 0x80 != 0 {
+                let len = bytes
+                    .get(offset + 1..offset + 3)
+                    .ok_or(PciInfoError::UnexpectedEof)?;
+                (tag & 0x7F, offset + 3, u16::from_le_bytes([len[0], len[1]]))
+            } else {
+                ((tag >> 3) & 0x0F, offset + 1, (tag & 0x07) as u16)
+            };
+
+            if tag & 0x80 == 0 && name == Self::TAG_END {
+                break;
+            }
+
+            let end = start + len as usize;
+            let data = bytes.get(start..end).ok_or(PciInfoError::UnexpectedEof)?;
+
+            if tag & 0x80 != 0 {
+                match name {
+                    Self::TAG_IDENTIFIER_STRING => vpd.identifier = Some(vpd_string(data)),
+                    Self::TAG_VPD_R => {
+                        vpd.read_only = Self::parse_keywords(data)?;
+                        vpd.checksum_valid = Self::verify_checksum(bytes, start, &vpd.read_only);
+                    }
+                    Self::TAG_VPD_W => vpd.read_write = Self::parse_keywords(data)?,
+                    _ => (),
+                }
+            }
+
+            offset = end;
+        }
+
+        Ok(vpd)
+    }
+
+    fn parse_keywords(data: &[u8]) -> Result<Vec<PciVpdKeyword>, PciInfoError> {
+        let mut keywords = Vec::new();
+        let mut offset = 0;
+
+        while offset + 3 <= data.len() {
+            let len = data[offset + 2] as usize;
+            let value = data
+                .get(offset + 3..offset + 3 + len)
+                .ok_or(PciInfoError::UnexpectedEof)?;
+
+            keywords.push(PciVpdKeyword {
+                keyword: [data[offset], data[offset + 1]],
+                data: value.to_vec(),
+            });
+
+            offset += 3 + len;
+        }
+
+        Ok(keywords)
+    }
+
+    // The first byte of the `RV` keyword is a checksum such that the sum
+    // of all the bytes from the beginning of the VPD up to and including it
+    // is zero.
+    fn verify_checksum(bytes: &[u8], start: usize, keywords: &[PciVpdKeyword]) -> Option<bool> {
+        let mut offset = start;
+
+        for keyword in keywords {
+            if &keyword.keyword == b"RV" {
+                if keyword.data.is_empty() {
+                    return Some(false);
+                }
+
+                let sum = bytes[..offset + 4]
+                    .iter()
+                    .fold(0u8, |sum, b| sum.wrapping_add(*b));
+
+                return Some(sum == 0);
+            }
+
+            offset += 3 + keyword.data.len();
+        }
+
+        None
+    }
+
+    /// Returns the keyword with the given name, searching the read-only
+    /// keywords first and the read/write ones afterwards.
+    pub fn keyword(&self, name: &str) -> Option<&PciVpdKeyword> {
+        self.read_only
+            .iter()
+            .chain(self.read_write.iter())
+            .find(|k| k.keyword == name.as_bytes())
+    }
+
+    /// Returns the part number of the device (keyword `PN`).
+    pub fn part_number(&self) -> Option<String> {
+        self.keyword_string("PN")
+    }
+
+    /// Returns the engineering change level of the device (keyword `EC`).
+    pub fn engineering_change(&self) -> Option<String> {
+        self.keyword_string("EC")
+    }
+
+    /// Returns the serial number of the device (keyword `SN`).
+    pub fn serial_number(&self) -> Option<String> {
+        self.keyword_string("SN")
+    }
+
+    /// Returns the manufacture id of the device (keyword `MN`).
+    pub fn manufacture_id(&self) -> Option<String> {
+        self.keyword_string("MN")
+    }
+
+    /// Returns an iterator over the vendor specific keywords (`V0`-`VZ`).
+    pub fn vendor_specific(&self) -> impl Iterator<Item = &PciVpdKeyword> {
+        self.read_only
+            .iter()
+            .chain(self.read_write.iter())
+            .filter(|k| k.is_vendor_specific())
+    }
+
+    fn keyword_string(&self, name: &str) -> Option<String> {
+        self.keyword(name).map(|k| k.data_as_string())
+    }
+}
+
+fn vpd_string(data: &[u8]) -> String {
+    String::from_utf8_lossy(data)
+        .trim_end_matches(|c: char| c == '\0' || c.is_whitespace())
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_vpd(ro: &[(&[u8; 2], &[u8])], rw: &[(&[u8; 2], &[u8])]) -> Vec<u8> {
+        let mut vpd = vec![0x82, 11, 0];
+        vpd.extend_from_slice(b"Test device");
+
+        let keywords = |kws: &[(&[u8; 2], &[u8])]| {
+            let mut bytes = Vec::new();
+            for (kw, data) in kws {
+                bytes.extend_from_slice(&kw[..]);
+                bytes.push(data.len() as u8);
+                bytes.extend_from_slice(data);
+            }
+            bytes
+        };
+
+        let ro = keywords(ro);
+        vpd.push(0x90);
+        vpd.extend_from_slice(&(ro.len() as u16 + 4).to_le_bytes());
+        vpd.extend_from_slice(&ro);
+        vpd.extend_from_slice(b"RV\x01");
+        let sum = vpd.iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+        vpd.push(0u8.wrapping_sub(sum));
+
+        let rw = keywords(rw);
+        vpd.push(0x91);
+        vpd.extend_from_slice(&(rw.len() as u16).to_le_bytes());
+        vpd.extend_from_slice(&rw);
+        vpd.push(0x78);
+        vpd
+    }
+
+    #[test]
+    fn parses_identifier_and_keywords() {
+        let bytes = build_vpd(
+            &[
+                (b"PN", b"ABC-123"),
+                (b"EC", b"A1"),
+                (b"SN", b"SN0001\0\0"),
+                (b"V1", b"vendor"),
+            ],
+            &[(b"YA", b"asset"), (b"RW", &[0; 4])],
+        );
+
+        let vpd = PciVitalProductData::with_bytes(&bytes).unwrap();
+
+        assert_eq!(vpd.identifier.as_deref(), Some("Test device"));
+        assert_eq!(vpd.part_number().as_deref(), Some("ABC-123"));
+        assert_eq!(vpd.engineering_change().as_deref(), Some("A1"));
+        assert_eq!(vpd.serial_number().as_deref(), Some("SN0001"));
+        assert_eq!(vpd.manufacture_id(), None);
+        assert_eq!(vpd.keyword("YA").unwrap().data_as_string(), "asset");
+        assert_eq!(
+            vpd.vendor_specific().map(|k| k.name()).collect::<Vec<_>>(),
+            ["V1"]
+        );
+        assert_eq!(vpd.read_only.len(), 5);
+        assert_eq!(vpd.read_write.len(), 2);
+        assert_eq!(vpd.checksum_valid, Some(true));
+    }
+
+    #[test]
+    fn detects_bad_checksum_and_truncation() {
+        let mut bytes = build_vpd(&[(b"SN", b"1234")], &[]);
+        let sn = bytes.iter().position(|b| *b == b'1').unwrap();
+        bytes[sn] = b'9';
+
+        let vpd = PciVitalProductData::with_bytes(&bytes).unwrap();
+        assert_eq!(vpd.checksum_valid, Some(false));
+
+        assert!(matches!(
+            PciVitalProductData::with_bytes(&bytes[..20]),
+            Err(PciInfoError::UnexpectedEof)
+        ));
+    }
+}